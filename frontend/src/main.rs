@@ -11,14 +11,37 @@ use yew_router::prelude::*;
 use wasm_bindgen::JsCast;
 use js_sys::Math;
 use crate::services::{
-    User, Camera, ActivityLog, Report, Settings, ReportType, ReportFormat, CameraStatus, UserRole,
-    get_users, get_cameras, get_logs, get_reports, get_settings, 
+    User, Camera, ProbeError, ActivityLog, Report, Settings, ReportType, ReportFormat, ReportStatus, CameraStatus, UserRole,
+    CreateReportRequest, ReportSchedule, PeriodRule,
+    UptimePoint, DiscoveredDevice, LiveLayout, CameraGrant, GrantSubject, GrantTarget, UserGroup, EffectivePermissions, PermissionSource,
+    MaintenanceWindow, MaintenanceTarget, MaintenanceState,
+    Alert, AlertRule, AlertCondition, AlertSeverity, AlertState, SmtpSettings, SmtpSecurity, OutboxEmail, OutboxStatus,
+    SmsGatewaySettings, SmsBodyFormat, SmsMessage, SmsStatus,
+    WebhookSubscription, WebhookEvent, WebhookDelivery, WebhookDeliveryStatus, CameraEvent, CameraEventType, PtzCommand, PtzPreset, PtzError, Site, Zone, SitesSummary, StatusCounts,
+    get_users, get_cameras, get_logs, get_reports, get_settings, get_camera_status_history,
+    discover_cameras, import_cameras, refresh_camera_info, set_camera_credentials, ptz_command, get_ptz_presets, set_ptz_preset,
     update_camera, create_camera, delete_camera,
+    create_report, cancel_report, delete_report, get_report_storage, ReportStorage,
+    get_live_layouts, create_live_layout, update_live_layout, delete_live_layout,
+    get_camera_grants, create_camera_grant, delete_camera_grant, get_effective_permissions, current_user, logout, check_session, authorized_url,
+    get_upcoming_maintenance, create_maintenance_window, delete_maintenance_window,
+    get_alerts, acknowledge_alert, resolve_alert, get_alert_rules, create_alert_rule, update_alert_rule, delete_alert_rule,
+    update_settings, send_test_email, get_outbox, retry_outbox_email, request_password_reset,
+    confirm_password_reset, reset_token_from_url,
+    send_test_sms, get_sms_messages, retry_sms_message, update_user,
+    get_webhooks, create_webhook, update_webhook, delete_webhook, get_webhook_deliveries, get_webhook_dead_letters, retry_webhook_delivery,
+    get_events, issue_event_token, revoke_event_token,
+    follow_changes, FeedMessage, FeedEntity, ChangeAction,
+    get_user_groups, create_user_group, delete_user_group, add_group_member, remove_group_member,
+    get_sites, create_site, delete_site, get_sites_summary, get_zones, create_zone, delete_zone,
+    get_report_schedules, create_report_schedule, update_report_schedule, delete_report_schedule,
     login,
     fetch_data
 };
 use wasm_bindgen_futures;
 use js_sys::Date;
+use gloo::timers::callback::{Interval, Timeout};
+use futures::channel::oneshot;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Route {
@@ -37,6 +60,8 @@ pub enum Page {
     Home,
     Users,
     Cameras,
+    LiveView,
+    Events,
     Reports,
     Logs,
     Settings,
@@ -48,6 +73,8 @@ impl Page {
             Page::Home => "Dashboard".to_string(),
             Page::Users => "Users".to_string(),
             Page::Cameras => "Cameras".to_string(),
+            Page::LiveView => "Live View".to_string(),
+            Page::Events => "Events".to_string(),
             Page::Reports => "Reports".to_string(),
             Page::Logs => "Activity Logs".to_string(),
             Page::Settings => "Settings".to_string(),
@@ -59,6 +86,8 @@ impl Page {
             Page::Home => "fa-home".to_string(),
             Page::Users => "fa-users".to_string(),
             Page::Cameras => "fa-video".to_string(),
+            Page::LiveView => "fa-th-large".to_string(),
+            Page::Events => "fa-bolt".to_string(),
             Page::Reports => "fa-chart-bar".to_string(),
             Page::Logs => "fa-history".to_string(),
             Page::Settings => "fa-cog".to_string(),
@@ -172,6 +201,8 @@ fn navigation_drawer(props: &NavigationDrawerProps) -> Html {
         Page::Home,
         Page::Users,
         Page::Cameras,
+        Page::LiveView,
+        Page::Events,
         Page::Reports,
         Page::Logs,
         Page::Settings,
@@ -247,22 +278,65 @@ fn toggle_drawer() {
     }
 }
 
+// Apply one change feed message to a loaded list, matching records by ID.
+// Lists that haven't loaded yet are left for the initial load to fill.
+fn apply_change<T>(list: &mut Option<Vec<T>>, action: &ChangeAction, item: T, id: fn(&T) -> &String) {
+    let Some(list) = list else { return };
+    let position = list.iter().position(|existing| id(existing) == id(&item));
+    match (action, position) {
+        (ChangeAction::Deleted, Some(index)) => {
+            list.remove(index);
+        }
+        (ChangeAction::Deleted, None) => {}
+        (_, Some(index)) => list[index] = item,
+        (_, None) => list.push(item),
+    }
+}
+
 #[function_component(App)]
 fn app() -> Html {
     let current_page = use_state(|| Page::Home);
     let drawer_open = use_state(|| false);
     let dark_mode = use_state(|| false);
     
-    // User state would typically come from authentication service
-    let username = use_state(|| Some("Admin".to_string()));
-    let role = use_state(|| Some("Admin".to_string()));
+    // Camera data depends on who is asking, so nothing loads until someone signs in
+    let signed_in_user = use_state(current_user);
+    let username = signed_in_user.as_ref().map(|user| user.name.clone());
+    let role = signed_in_user.as_ref().map(|user| match user.role {
+        UserRole::SuperAdmin => "Super Admin".to_string(),
+        UserRole::Admin => "Admin".to_string(),
+        UserRole::Viewer => "Viewer".to_string(),
+    });
     
     // Data states
     let users = use_state(|| None);
     let cameras = use_state(|| None);
     let logs = use_state(|| None);
     let reports = use_state(|| None);
+    let report_schedules = use_state(|| None);
     let settings = use_state(|| None);
+    let report_storage = use_state(|| None);
+    let sites = use_state(|| None);
+    let zones = use_state(|| None);
+    let sites_summary = use_state(|| None);
+    // Site the Cameras page is limited to; empty shows every camera
+    let camera_site_filter = use_state(String::new);
+    // Bumped to load everything again, when the change feed says it missed changes
+    let data_version = use_state(|| 0u32);
+    
+    // Make sure a stored session is still good before loading anything with it
+    {
+        let signed_in_user = signed_in_user.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    signed_in_user.set(check_session().await);
+                });
+                || ()
+            },
+            (),
+        );
+    }
     
     // Load data effect
     {
@@ -270,13 +344,20 @@ fn app() -> Html {
         let cameras = cameras.clone();
         let logs = logs.clone();
         let reports = reports.clone();
+        let report_schedules = report_schedules.clone();
         let settings = settings.clone();
+        let report_storage = report_storage.clone();
+        let sites = sites.clone();
+        let zones = zones.clone();
+        let sites_summary = sites_summary.clone();
         
         use_effect_with_deps(
-            move |_| {
+            move |(signed_in_id, _): &(Option<String>, u32)| {
+                let signed_in = signed_in_id.is_some();
                 wasm_bindgen_futures::spawn_local(async move {
-                    // In a real app, these would be API calls
-                    // For now we'll use our mock service functions
+                    if !signed_in {
+                        return;
+                    }
                     match get_users().await {
                         Ok(data) => users.set(Some(data)),
                         Err(e) => log::error!("Failed to load users: {:?}", e),
@@ -297,14 +378,176 @@ fn app() -> Html {
                         Err(e) => log::error!("Failed to load reports: {:?}", e),
                     }
                     
+                    match get_report_schedules().await {
+                        Ok(data) => report_schedules.set(Some(data)),
+                        Err(e) => log::error!("Failed to load report schedules: {:?}", e),
+                    }
+                    
                     match get_settings().await {
                         Ok(data) => settings.set(Some(data)),
                         Err(e) => log::error!("Failed to load settings: {:?}", e),
                     }
+                    
+                    match get_report_storage().await {
+                        Ok(data) => report_storage.set(Some(data)),
+                        Err(e) => log::error!("Failed to load report storage usage: {:?}", e),
+                    }
+                    
+                    match get_sites().await {
+                        Ok(data) => sites.set(Some(data)),
+                        Err(e) => log::error!("Failed to load sites: {:?}", e),
+                    }
+                    
+                    match get_zones().await {
+                        Ok(data) => zones.set(Some(data)),
+                        Err(e) => log::error!("Failed to load zones: {:?}", e),
+                    }
+                    
+                    match get_sites_summary().await {
+                        Ok(data) => sites_summary.set(Some(data)),
+                        Err(e) => log::error!("Failed to load site summary: {:?}", e),
+                    }
                 });
                 || ()
             },
-            (),
+            (signed_in_user.as_ref().map(|user| user.id.clone()), *data_version),
+        );
+    }
+    
+    // Follow the server's change feed while signed in. Messages are queued and
+    // applied on the next render, where the state handles hold current values.
+    let feed_queue = use_mut_ref(Vec::<FeedMessage>::new);
+    let force_update = use_force_update();
+    {
+        let feed_queue = feed_queue.clone();
+        
+        use_effect_with_deps(
+            move |signed_in_id: &Option<String>| {
+                let (stop, stopped) = oneshot::channel::<()>();
+                if signed_in_id.is_some() {
+                    let on_message = Callback::from(move |message: FeedMessage| {
+                        feed_queue.borrow_mut().push(message);
+                        force_update.force_update();
+                    });
+                    wasm_bindgen_futures::spawn_local(follow_changes(on_message, stopped));
+                }
+                move || drop(stop)
+            },
+            signed_in_user.as_ref().map(|user| user.id.clone()),
+        );
+    }
+    {
+        let users = users.clone();
+        let cameras = cameras.clone();
+        let logs = logs.clone();
+        let reports = reports.clone();
+        let settings = settings.clone();
+        let data_version = data_version.clone();
+        
+        use_effect(move || {
+            let pending: Vec<FeedMessage> = feed_queue.borrow_mut().drain(..).collect();
+            if !pending.is_empty() {
+                let mut user_list: Option<Vec<User>> = (*users).clone();
+                let mut camera_list: Option<Vec<Camera>> = (*cameras).clone();
+                let mut log_list: Option<Vec<ActivityLog>> = (*logs).clone();
+                let mut report_list: Option<Vec<Report>> = (*reports).clone();
+                let mut new_settings: Option<Settings> = None;
+                let mut resync = false;
+                
+                for message in pending {
+                    match message {
+                        FeedMessage::Change { action, entity } => match entity {
+                            FeedEntity::User(user) => apply_change(&mut user_list, &action, user, |u| &u.id),
+                            FeedEntity::Camera(camera) => apply_change(&mut camera_list, &action, camera, |c| &c.id),
+                            FeedEntity::ActivityLog(log) => apply_change(&mut log_list, &action, log, |l| &l.id),
+                            FeedEntity::Report(report) => apply_change(&mut report_list, &action, report, |r| &r.id),
+                            FeedEntity::Settings(data) => new_settings = Some(data),
+                        },
+                        FeedMessage::CameraStatus { camera_id, status, timestamp, .. } => {
+                            let camera = camera_list.iter_mut().flatten().find(|camera| camera.id == camera_id);
+                            if let Some(camera) = camera {
+                                camera.status = status;
+                                camera.last_update = timestamp;
+                            }
+                        }
+                        FeedMessage::Resync => resync = true,
+                    }
+                }
+                
+                if resync {
+                    data_version.set(*data_version + 1);
+                } else {
+                    users.set(user_list);
+                    cameras.set(camera_list);
+                    logs.set(log_list);
+                    reports.set(report_list);
+                    if new_settings.is_some() {
+                        settings.set(new_settings);
+                    }
+                }
+            }
+            || ()
+        });
+    }
+    
+    // Poll the report list while any report job is queued or running
+    {
+        let reports_handle = reports.clone();
+        
+        use_effect_with_deps(
+            move |current: &Option<Vec<Report>>| {
+                let pending = current
+                    .as_ref()
+                    .map_or(false, |list| list.iter().any(|r| r.status.is_pending()));
+                
+                let timeout = if pending {
+                    Some(Timeout::new(2_000, move || {
+                        wasm_bindgen_futures::spawn_local(async move {
+                            match get_reports().await {
+                                Ok(data) => reports_handle.set(Some(data)),
+                                Err(e) => log::error!("Failed to refresh reports: {:?}", e),
+                            }
+                        });
+                    }))
+                } else {
+                    None
+                };
+                
+                move || drop(timeout)
+            },
+            (*reports).clone(),
+        );
+    }
+    
+    // Reload cameras on the settings refresh interval so the status the
+    // backend health checker maintains shows up without a page reload
+    {
+        let cameras_handle = cameras.clone();
+        let sites_summary_handle = sites_summary.clone();
+        let refresh_seconds = settings
+            .as_ref()
+            .map_or(10, |s: &Settings| s.refresh_interval.max(1));
+        
+        use_effect_with_deps(
+            move |refresh_seconds: &u32| {
+                let interval = Interval::new(refresh_seconds * 1_000, move || {
+                    let cameras_handle = cameras_handle.clone();
+                    let sites_summary_handle = sites_summary_handle.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match get_cameras().await {
+                            Ok(data) => cameras_handle.set(Some(data)),
+                            Err(e) => log::error!("Failed to refresh cameras: {:?}", e),
+                        }
+                        match get_sites_summary().await {
+                            Ok(data) => sites_summary_handle.set(Some(data)),
+                            Err(e) => log::error!("Failed to refresh site summary: {:?}", e),
+                        }
+                    });
+                });
+                
+                move || drop(interval)
+            },
+            refresh_seconds,
         );
     }
     
@@ -333,13 +576,23 @@ fn app() -> Html {
         })
     };
     
-    let _handle_logout = {
+    let handle_logout = {
+        let signed_in_user = signed_in_user.clone();
+        let cameras = cameras.clone();
+        let reports = reports.clone();
         Callback::from(move |_: MouseEvent| {
-            // In a real app, this would call a logout API
-            log::info!("User logged out");
+            wasm_bindgen_futures::spawn_local(logout());
+            cameras.set(None);
+            reports.set(None);
+            signed_in_user.set(None);
         })
     };
     
+    let on_signed_in = {
+        let signed_in_user = signed_in_user.clone();
+        Callback::from(move |user: User| signed_in_user.set(Some(user)))
+    };
+    
     let on_page_change = {
         let current_page = current_page.clone();
         let drawer_open = drawer_open.clone();
@@ -386,9 +639,27 @@ fn app() -> Html {
                     >
                         <i class={if *dark_mode { "fas fa-sun" } else { "fas fa-moon" }}></i>
                     </button>
+                    {
+                        if signed_in_user.is_some() {
+                            html! { <AlertsPanel /> }
+                        } else {
+                            html! {}
+                        }
+                    }
                     <div class="user-avatar">
                         {username.as_ref().map_or("G", |name| &name[0..1])}
                     </div>
+                    {
+                        if signed_in_user.is_some() {
+                            html! {
+                                <button class="icon-button" title="Sign out" onclick={handle_logout}>
+                                    <i class="fas fa-sign-out-alt"></i>
+                                </button>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
                 </div>
             </header>
             
@@ -410,6 +681,8 @@ fn app() -> Html {
                                 Page::Home,
                                 Page::Users,
                                 Page::Cameras,
+                                Page::LiveView,
+                                Page::Events,
                                 Page::Reports,
                                 Page::Logs,
                                 Page::Settings,
@@ -441,14 +714,24 @@ fn app() -> Html {
                 </nav>
                 
                 <main class="page-content">
+                    if signed_in_user.is_none() {
+                        <SignIn on_signed_in={on_signed_in} />
+                    } else {
                     {render_page(
                         &*current_page,
                         users.clone(),
                         cameras.clone(),
                         logs.clone(),
                         reports.clone(),
+                        report_schedules.clone(),
                         settings.clone(),
+                        report_storage.clone(),
+                        sites.clone(),
+                        zones.clone(),
+                        sites_summary.clone(),
+                        camera_site_filter.clone(),
                     )}
+                    }
                 </main>
             </div>
             
@@ -490,6 +773,72 @@ fn get_input_value(id: &str) -> String {
     String::new()
 }
 
+fn is_checked(id: &str) -> bool {
+    document()
+        .get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .is_some_and(|input| input.checked())
+}
+
+fn set_input_value(id: &str, value: &str) {
+    if let Some(input) = document()
+        .get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+    {
+        input.set_value(value);
+    }
+}
+
+fn get_checked_value(name: &str) -> String {
+    document()
+        .query_selector(&format!("input[name='{}']:checked", name))
+        .ok()
+        .flatten()
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .map(|input| input.value())
+        .unwrap_or_default()
+}
+
+// Empty form values mean "not set"
+fn optional_value(value: String) -> Option<String> {
+    if value.is_empty() { None } else { Some(value) }
+}
+
+// "Site / Zone" for a camera's zone, or "-" while it is unassigned
+fn zone_label(zone_id: &Option<String>, sites: &[Site], zones: &[Zone]) -> String {
+    let Some(zone) = zones.iter().find(|z| Some(&z.id) == zone_id.as_ref()) else {
+        return "-".to_string();
+    };
+    match sites.iter().find(|s| s.id == zone.site_id) {
+        Some(site) => format!("{} / {}", site.name, zone.name),
+        None => zone.name.clone(),
+    }
+}
+
+// Options for a site select, led by one that covers every site
+fn role_label(role: &UserRole) -> &'static str {
+    match role {
+        UserRole::SuperAdmin => "Super Admin",
+        UserRole::Admin => "Admin",
+        UserRole::Viewer => "Viewer",
+    }
+}
+
+fn site_options(sites: &[Site], selected: &str) -> Html {
+    html! {
+        <>
+            <option value="" selected={selected.is_empty()}>{"All sites"}</option>
+            {
+                sites.iter().map(|site| html! {
+                    <option key={site.id.clone()} value={site.id.clone()} selected={site.id == selected}>
+                        {&site.name}
+                    </option>
+                }).collect::<Html>()
+            }
+        </>
+    }
+}
+
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::Renderer::<App>::new().render();
@@ -502,7 +851,13 @@ fn render_page(
     cameras: UseStateHandle<Option<Vec<Camera>>>,
     logs: UseStateHandle<Option<Vec<ActivityLog>>>,
     reports: UseStateHandle<Option<Vec<Report>>>,
+    report_schedules: UseStateHandle<Option<Vec<ReportSchedule>>>,
     settings: UseStateHandle<Option<Settings>>,
+    report_storage: UseStateHandle<Option<ReportStorage>>,
+    sites: UseStateHandle<Option<Vec<Site>>>,
+    zones: UseStateHandle<Option<Vec<Zone>>>,
+    sites_summary: UseStateHandle<Option<SitesSummary>>,
+    camera_site_filter: UseStateHandle<String>,
 ) -> Html {
     match current_page {
         Page::Home => {
@@ -589,6 +944,68 @@ fn render_page(
                             }
                         </div>
                         
+                        <div class="widget">
+                            <h3>{"Upcoming Maintenance"}</h3>
+                            <MaintenancePanel
+                                cameras={(*cameras).clone().unwrap_or_default()}
+                                sites={(*sites).clone().unwrap_or_default()}
+                                zones={(*zones).clone().unwrap_or_default()}
+                            />
+                        </div>
+                        
+                        <div class="widget">
+                            <h3>{"Cameras by Site"}</h3>
+                            {
+                                if let Some(summary) = sites_summary.as_ref() {
+                                    let counts_row = |key: String, class: &'static str, label: String, counts: &StatusCounts| html! {
+                                        <tr key={key} class={class}>
+                                            <td>{label}</td>
+                                            <td>{counts.total}</td>
+                                            <td><span class="status-online">{counts.online}</span></td>
+                                            <td><span class="status-offline">{counts.offline}</span></td>
+                                            <td><span class="status-maintenance">{counts.maintenance}</span></td>
+                                        </tr>
+                                    };
+                                    html! {
+                                        <table class="dashboard-table">
+                                            <thead>
+                                                <tr>
+                                                    <th>{"Site / Zone"}</th>
+                                                    <th>{"Total"}</th>
+                                                    <th>{"Online"}</th>
+                                                    <th>{"Offline"}</th>
+                                                    <th>{"Maintenance"}</th>
+                                                </tr>
+                                            </thead>
+                                            <tbody>
+                                                {
+                                                    summary.sites.iter().map(|site| html! {
+                                                        <>
+                                                            {counts_row(site.site.id.clone(), "site-row", site.site.name.clone(), &site.counts)}
+                                                            {
+                                                                site.zones.iter().map(|zone| {
+                                                                    counts_row(zone.zone.id.clone(), "zone-row", zone.zone.name.clone(), &zone.counts)
+                                                                }).collect::<Html>()
+                                                            }
+                                                        </>
+                                                    }).collect::<Html>()
+                                                }
+                                                {
+                                                    if summary.unassigned.total > 0 {
+                                                        counts_row("unassigned".to_string(), "site-row", "Unassigned".to_string(), &summary.unassigned)
+                                                    } else {
+                                                        html! {}
+                                                    }
+                                                }
+                                            </tbody>
+                                        </table>
+                                    }
+                                } else {
+                                    html! { <div class="loading-container">{"Loading sites..."}</div> }
+                                }
+                            }
+                        </div>
+                        
                         <div class="widget">
                             <h3>{"Recent Activity"}</h3>
                             {
@@ -627,6 +1044,10 @@ fn render_page(
             }
         },
         Page::Users => {
+            let camera_list = (*cameras).clone().unwrap_or_default();
+            let site_list = (*sites).clone().unwrap_or_default();
+            let zone_list = (*zones).clone().unwrap_or_default();
+            
             html! {
                 <div class="users-page">
                     <div class="page-header">
@@ -642,6 +1063,7 @@ fn render_page(
                                         <tr>
                                             <th>{"Name"}</th>
                                             <th>{"Email"}</th>
+                                            <th>{"Phone"}</th>
                                             <th>{"Role"}</th>
                                             <th>{"Status"}</th>
                                             <th>{"Last Login"}</th>
@@ -651,23 +1073,55 @@ fn render_page(
                                     <tbody>
                                         {
                                             user_list.iter().map(|user| {
-                                                let role_text = match user.role {
-                                                    UserRole::SuperAdmin => "Super Admin",
-                                                    UserRole::Admin => "Admin",
-                                                    UserRole::Viewer => "Viewer",
-                                                };
+                                                let role_text = role_label(&user.role);
                                                 
                                                 let status_class = if user.active { "status-online" } else { "status-offline" };
                                                 let status_text = if user.active { "Active" } else { "Inactive" };
                                                 
+                                                let on_edit_phone = {
+                                                    let users = users.clone();
+                                                    let user = user.clone();
+                                                    Callback::from(move |_: MouseEvent| {
+                                                        let Some(phone) = gloo::dialogs::prompt(
+                                                            &format!("Mobile number for {} in international format, e.g. +15550100 (empty for none)", user.name),
+                                                            Some(&user.phone),
+                                                        ) else {
+                                                            return;
+                                                        };
+                                                        let updated = User { phone: phone.trim().replace(' ', ""), ..user.clone() };
+                                                        let users = users.clone();
+                                                        wasm_bindgen_futures::spawn_local(async move {
+                                                            match update_user(&updated.id, &updated).await {
+                                                                Ok(saved) => {
+                                                                    let list = (*users).clone().unwrap_or_default().into_iter().map(|u| if u.id == saved.id { saved.clone() } else { u }).collect();
+                                                                    users.set(Some(list));
+                                                                }
+                                                                Err(e) => gloo::dialogs::alert(&e),
+                                                            }
+                                                        });
+                                                    })
+                                                };
+                                                
                                                 html! {
                                                     <tr key={user.id.clone()}>
                                                         <td>{&user.name}</td>
                                                         <td>{&user.email}</td>
+                                                        <td>
+                                                            {if user.phone.is_empty() { "-" } else { user.phone.as_str() }}
+                                                            <button class="action-button" onclick={on_edit_phone}>{"Edit"}</button>
+                                                        </td>
                                                         <td>{role_text}</td>
                                                         <td><span class={format!("status-indicator {}", status_class)}>{status_text}</span></td>
                                                         <td>{&user.last_login}</td>
                                                         <td>
+                                                            <PermissionEditor
+                                                                subject={GrantSubject::User(user.id.clone())}
+                                                                name={user.name.clone()}
+                                                                role={user.role.clone()}
+                                                                cameras={camera_list.clone()}
+                                                                sites={site_list.clone()}
+                                                                zones={zone_list.clone()}
+                                                            />
                                                             <button class="action-button">{"Edit"}</button>
                                                             <button class="action-button danger">{"Delete"}</button>
                                                         </td>
@@ -682,27 +1136,75 @@ fn render_page(
                             html! { <div class="loading-container">{"Loading users..."}</div> }
                         }
                     }
+                    
+                    <GroupsPanel
+                        users={(*users).clone().unwrap_or_default()}
+                        cameras={camera_list}
+                        sites={site_list}
+                        zones={zone_list}
+                    />
                 </div>
             }
         },
         Page::Cameras => {
-            // Simple Camera page implementation without state management challenges
-            let cameras_list = mock_cameras();
+            let site_list = (*sites).clone().unwrap_or_default();
+            let zone_list = (*zones).clone().unwrap_or_default();
+            let site_filter = (*camera_site_filter).clone();
+            let cameras_list: Vec<Camera> = (*cameras)
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|camera| {
+                    site_filter.is_empty()
+                        || zone_list
+                            .iter()
+                            .any(|z| Some(&z.id) == camera.zone_id.as_ref() && z.site_id == site_filter)
+                })
+                .collect();
+            
+            let on_site_filter = {
+                let camera_site_filter = camera_site_filter.clone();
+                Callback::from(move |e: Event| {
+                    if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                        camera_site_filter.set(select.value());
+                    }
+                })
+            };
+            
+            let on_imported = {
+                let cameras = cameras.clone();
+                Callback::from(move |imported: Vec<Camera>| {
+                    let mut list = (*cameras).clone().unwrap_or_default();
+                    list.extend(imported);
+                    cameras.set(Some(list));
+                })
+            };
                 
             html! {
                 <div class="cameras-page">
                     <div class="page-header">
                         <h2>{"Camera Management"}</h2>
-                        <button class="primary-button">{"Add Camera"}</button>
+                        <div class="action-buttons">
+                            <select class="site-filter" onchange={on_site_filter}>
+                                {site_options(&site_list, &site_filter)}
+                            </select>
+                            <DiscoverDialog on_imported={on_imported} />
+                            <button class="primary-button">{"Add Camera"}</button>
+                        </div>
                     </div>
                     
                     <table class="data-table">
                         <thead>
                             <tr>
+                                <th>{"Preview"}</th>
                                 <th>{"Name"}</th>
                                 <th>{"IP Address"}</th>
                                 <th>{"Location"}</th>
+                                <th>{"Site / Zone"}</th>
                                 <th>{"Status"}</th>
+                                <th>{"Device"}</th>
+                                <th>{"Stream"}</th>
+                                <th>{"Availability"}</th>
                                 <th>{"Last Update"}</th>
                                 <th>{"Actions"}</th>
                             </tr>
@@ -722,19 +1224,106 @@ fn render_page(
                                         CameraStatus::Maintenance => "Maintenance",
                                     };
                                     
+                                    // What the last RTSP probe learned about the stream, or why it failed
+                                    let stream_text = match (&camera.probe_error, &camera.stream_info) {
+                                        (Some(ProbeError::AuthFailed), _) => "Authentication failed".to_string(),
+                                        (Some(ProbeError::StreamNotFound), _) => "Stream not found".to_string(),
+                                        (Some(ProbeError::Unreachable(_)), _) => "Unreachable".to_string(),
+                                        (Some(ProbeError::Protocol(message)), _) => message.clone(),
+                                        (None, Some(info)) => {
+                                            let mut parts = Vec::new();
+                                            if let Some(codec) = &info.codec {
+                                                parts.push(codec.clone());
+                                            }
+                                            if let (Some(width), Some(height)) = (info.width, info.height) {
+                                                parts.push(format!("{}x{}", width, height));
+                                            }
+                                            if let Some(frame_rate) = info.frame_rate {
+                                                parts.push(format!("{} fps", frame_rate));
+                                            }
+                                            parts.join(" ")
+                                        }
+                                        (None, None) => "-".to_string(),
+                                    };
+                                    
+                                    let device_text = match &camera.device_info {
+                                        Some(info) => format!("{} {} (fw {})", info.manufacturer, info.model, info.firmware_version),
+                                        None => "-".to_string(),
+                                    };
+                                    
+                                    let on_camera_updated = {
+                                        let cameras = cameras.clone();
+                                        Callback::from(move |updated: Camera| {
+                                            let list = (*cameras)
+                                                .clone()
+                                                .unwrap_or_default()
+                                                .into_iter()
+                                                .map(|c| if c.id == updated.id { updated.clone() } else { c })
+                                                .collect();
+                                            cameras.set(Some(list));
+                                        })
+                                    };
+                                    
+                                    let on_refresh_info = {
+                                        let id = camera.id.clone();
+                                        let on_camera_updated = on_camera_updated.clone();
+                                        Callback::from(move |_: MouseEvent| {
+                                            let id = id.clone();
+                                            let on_camera_updated = on_camera_updated.clone();
+                                            wasm_bindgen_futures::spawn_local(async move {
+                                                match refresh_camera_info(&id).await {
+                                                    Ok(updated) => on_camera_updated.emit(updated),
+                                                    Err(e) => gloo::dialogs::alert(&e),
+                                                }
+                                            });
+                                        })
+                                    };
+                                    
+                                    // last_update changes with every health check, so the
+                                    // thumbnail follows the table's refresh
+                                    let thumbnail = if camera.media_profiles.iter().any(|p| p.snapshot_uri.is_some()) {
+                                        let on_error = Callback::from(|e: Event| {
+                                            if let Some(img) = e.target_dyn_into::<web_sys::Element>() {
+                                                let _ = img.set_attribute("style", "visibility: hidden");
+                                            }
+                                        });
+                                        html! {
+                                            <img
+                                                class="camera-thumbnail"
+                                                src={authorized_url(&format!("/api/cameras/{}/snapshot?t={}", camera.id, camera.last_update))}
+                                                alt={camera.name.clone()}
+                                                loading="lazy"
+                                                onerror={on_error}
+                                            />
+                                        }
+                                    } else {
+                                        html! { {"-"} }
+                                    };
+                                    
                                     html! {
                                         <tr key={camera.id.clone()}>
+                                            <td>{thumbnail}</td>
                                             <td>{&camera.name}</td>
                                             <td>{format!("{}:{}", camera.ip_address, camera.port)}</td>
                                             <td>{&camera.location}</td>
+                                            <td>{zone_label(&camera.zone_id, &site_list, &zone_list)}</td>
                                             <td>
                                                 <span class={format!("status-indicator {}", status_class)}>
                                                     {status_text}
                                                 </span>
                                             </td>
+                                            <td>{device_text}</td>
+                                            <td>{stream_text}</td>
+                                            <td><UptimeSparkline camera_id={camera.id.clone()} /></td>
                                             <td>{&camera.last_update}</td>
                                             <td class="action-buttons">
-                                                <button class="action-button">{"View"}</button>
+                                                <CameraDetail
+                                                    camera={camera.clone()}
+                                                    sites={site_list.clone()}
+                                                    zones={zone_list.clone()}
+                                                    on_updated={on_camera_updated}
+                                                />
+                                                <button class="action-button" onclick={on_refresh_info}>{"Refresh Info"}</button>
                                                 <button class="action-button">{"Edit"}</button>
                                                 <button class="action-button danger">{"Delete"}</button>
                                             </td>
@@ -748,6 +1337,111 @@ fn render_page(
             }
         },
         Page::Reports => {
+            let site_list = (*sites).clone().unwrap_or_default();
+            
+            let on_generate = {
+                let reports = reports.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let type_ = match get_input_value("report-type").as_str() {
+                        "camera-status" => ReportType::CameraStatus,
+                        "user-activity" => ReportType::UserActivity,
+                        "uptime-sla" => ReportType::UptimeSla,
+                        "offline-incidents" => ReportType::OfflineIncidents,
+                        _ => ReportType::UsageSummary,
+                    };
+                    let format = match get_checked_value("format").as_str() {
+                        "csv" => ReportFormat::CSV,
+                        "xlsx" => ReportFormat::XLSX,
+                        "json" => ReportFormat::JSON,
+                        "html" => ReportFormat::HTML,
+                        _ => ReportFormat::PDF,
+                    };
+                    let request = CreateReportRequest {
+                        name: None,
+                        type_,
+                        format,
+                        from: get_input_value("date-from"),
+                        to: get_input_value("date-to"),
+                        site_id: optional_value(get_input_value("report-site")),
+                    };
+                    
+                    let reports = reports.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match create_report(&request).await {
+                            Ok(response) => {
+                                let mut list = (*reports).clone().unwrap_or_default();
+                                list.push(response.report);
+                                reports.set(Some(list));
+                            }
+                            Err(e) => gloo::dialogs::alert(&e),
+                        }
+                    });
+                })
+            };
+            
+            let on_add_schedule = {
+                let report_schedules = report_schedules.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let name = get_input_value("schedule-name");
+                    let cron = get_input_value("schedule-cron");
+                    if name.trim().is_empty() || cron.trim().is_empty() {
+                        gloo::dialogs::alert("Please enter a name and a schedule expression");
+                        return;
+                    }
+                    
+                    let schedule = ReportSchedule {
+                        id: String::new(),
+                        name,
+                        cron,
+                        type_: match get_input_value("schedule-type").as_str() {
+                            "camera-status" => ReportType::CameraStatus,
+                            "user-activity" => ReportType::UserActivity,
+                            "uptime-sla" => ReportType::UptimeSla,
+                            "offline-incidents" => ReportType::OfflineIncidents,
+                            _ => ReportType::UsageSummary,
+                        },
+                        format: match get_input_value("schedule-format").as_str() {
+                            "csv" => ReportFormat::CSV,
+                            "xlsx" => ReportFormat::XLSX,
+                            "json" => ReportFormat::JSON,
+                            "html" => ReportFormat::HTML,
+                            _ => ReportFormat::PDF,
+                        },
+                        period_rule: match get_input_value("schedule-period").as_str() {
+                            "previous-day" => PeriodRule::PreviousDay,
+                            "previous-week" => PeriodRule::PreviousWeek,
+                            "previous-quarter" => PeriodRule::PreviousQuarter,
+                            "last-7-days" => PeriodRule::Last7Days,
+                            "last-30-days" => PeriodRule::Last30Days,
+                            _ => PeriodRule::PreviousMonth,
+                        },
+                        recipients: get_input_value("schedule-recipients")
+                            .split(',')
+                            .map(|r| r.trim().to_string())
+                            .filter(|r| !r.is_empty())
+                            .collect(),
+                        enabled: true,
+                        created_by: String::new(),
+                        site_id: optional_value(get_input_value("schedule-site")),
+                        last_run: None,
+                        next_run: None,
+                        last_report_id: None,
+                    };
+                    
+                    let report_schedules = report_schedules.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match create_report_schedule(&schedule).await {
+                            Ok(created) => {
+                                let mut list = (*report_schedules).clone().unwrap_or_default();
+                                list.push(created);
+                                report_schedules.set(Some(list));
+                            }
+                            Err(e) => gloo::dialogs::alert(&e),
+                        }
+                    });
+                })
+            };
+            
             html! {
                 <div class="reports-page">
                     <h2>{"Reports"}</h2>
@@ -760,6 +1454,15 @@ fn render_page(
                                 <option value="usage">{"Usage Summary"}</option>
                                 <option value="camera-status">{"Camera Status"}</option>
                                 <option value="user-activity">{"User Activity"}</option>
+                                <option value="uptime-sla">{"Uptime SLA"}</option>
+                                <option value="offline-incidents">{"Offline Incidents"}</option>
+                            </select>
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="report-site">{"Site"}</label>
+                            <select id="report-site">
+                                {site_options(&site_list, "")}
                             </select>
                         </div>
                         
@@ -788,11 +1491,23 @@ fn render_page(
                                     <input type="radio" name="format" value="csv" />
                                     {"CSV"}
                                 </label>
+                                <label>
+                                    <input type="radio" name="format" value="xlsx" />
+                                    {"Excel (XLSX)"}
+                                </label>
+                                <label>
+                                    <input type="radio" name="format" value="json" />
+                                    {"JSON"}
+                                </label>
+                                <label>
+                                    <input type="radio" name="format" value="html" />
+                                    {"HTML"}
+                                </label>
                             </div>
                         </div>
                         
                         <div>
-                            <button class="primary-button">{"Generate Report"}</button>
+                            <button class="primary-button" onclick={on_generate}>{"Generate Report"}</button>
                         </div>
                     </div>
                     
@@ -808,6 +1523,7 @@ fn render_page(
                                             <th>{"Created"}</th>
                                             <th>{"Period"}</th>
                                             <th>{"Format"}</th>
+                                            <th>{"Status"}</th>
                                             <th>{"Actions"}</th>
                                         </tr>
                                     </thead>
@@ -818,11 +1534,89 @@ fn render_page(
                                                     ReportType::UsageSummary => "Usage Summary",
                                                     ReportType::CameraStatus => "Camera Status",
                                                     ReportType::UserActivity => "User Activity",
+                                                    ReportType::UptimeSla => "Uptime SLA",
+                                                    ReportType::OfflineIncidents => "Offline Incidents",
                                                 };
                                                 
                                                 let format_text = match report.format {
                                                     ReportFormat::PDF => "PDF",
                                                     ReportFormat::CSV => "CSV",
+                                                    ReportFormat::XLSX => "XLSX",
+                                                    ReportFormat::JSON => "JSON",
+                                                    ReportFormat::HTML => "HTML",
+                                                };
+                                                
+                                                let (status_class, status_text) = match report.status {
+                                                    ReportStatus::Queued => ("status-maintenance", "Queued".to_string()),
+                                                    ReportStatus::Running => ("status-maintenance", format!("Running {}%", report.progress)),
+                                                    ReportStatus::Done => ("status-online", "Done".to_string()),
+                                                    ReportStatus::Failed => ("status-offline", format!(
+                                                        "Failed: {}",
+                                                        report.error.clone().unwrap_or_default()
+                                                    )),
+                                                    ReportStatus::Cancelled => ("status-offline", "Cancelled".to_string()),
+                                                };
+                                                
+                                                let on_delete = {
+                                                    let id = report.id.clone();
+                                                    let reports = reports.clone();
+                                                    let report_storage = report_storage.clone();
+                                                    Callback::from(move |_: MouseEvent| {
+                                                        let id = id.clone();
+                                                        let reports = reports.clone();
+                                                        let report_storage = report_storage.clone();
+                                                        wasm_bindgen_futures::spawn_local(async move {
+                                                            match delete_report(&id).await {
+                                                                Ok(()) => {
+                                                                    let list = (*reports)
+                                                                        .clone()
+                                                                        .unwrap_or_default()
+                                                                        .into_iter()
+                                                                        .filter(|r| r.id != id)
+                                                                        .collect();
+                                                                    reports.set(Some(list));
+                                                                    if let Ok(data) = get_report_storage().await {
+                                                                        report_storage.set(Some(data));
+                                                                    }
+                                                                }
+                                                                Err(e) => gloo::dialogs::alert(&e),
+                                                            }
+                                                        });
+                                                    })
+                                                };
+                                                
+                                                let action = if report.status == ReportStatus::Done {
+                                                    html! {
+                                                        <>
+                                                            <a class="action-button" href={authorized_url(&report.url)}>{"Download"}</a>
+                                                            <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                                                        </>
+                                                    }
+                                                } else if report.status.is_pending() {
+                                                    let on_cancel = {
+                                                        let id = report.id.clone();
+                                                        let reports = reports.clone();
+                                                        Callback::from(move |_: MouseEvent| {
+                                                            let id = id.clone();
+                                                            let reports = reports.clone();
+                                                            wasm_bindgen_futures::spawn_local(async move {
+                                                                if let Err(e) = cancel_report(&id).await {
+                                                                    log::error!("Failed to cancel report {}: {:?}", id, e);
+                                                                }
+                                                                match get_reports().await {
+                                                                    Ok(data) => reports.set(Some(data)),
+                                                                    Err(e) => log::error!("Failed to refresh reports: {:?}", e),
+                                                                }
+                                                            });
+                                                        })
+                                                    };
+                                                    html! {
+                                                        <button class="action-button danger" onclick={on_cancel}>{"Cancel"}</button>
+                                                    }
+                                                } else {
+                                                    html! {
+                                                        <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                                                    }
                                                 };
                                                 
                                                 html! {
@@ -833,8 +1627,11 @@ fn render_page(
                                                         <td>{&report.period}</td>
                                                         <td>{format_text}</td>
                                                         <td>
-                                                            <button class="action-button">{"Download"}</button>
+                                                            <span class={format!("status-indicator {}", status_class)}>
+                                                                {status_text}
+                                                            </span>
                                                         </td>
+                                                        <td>{action}</td>
                                                     </tr>
                                                 }
                                             }).collect::<Html>()
@@ -846,35 +1643,224 @@ fn render_page(
                             html! { <div class="loading-container">{"Loading reports..."}</div> }
                         }
                     }
-                </div>
-            }
-        },
-        Page::Logs => {
-            html! {
-                <div class="logs-page">
-                    <h2>{"Activity Logs"}</h2>
+                    
+                    <h3>{"Scheduled Reports"}</h3>
+                    <div class="widget">
+                        <div class="form-group">
+                            <label for="schedule-name">{"Name"}</label>
+                            <input type="text" id="schedule-name" placeholder="Monthly Camera Status" />
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-cron">{"Schedule (cron, UTC)"}</label>
+                            <input type="text" id="schedule-cron" placeholder="0 6 1 * *" />
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-type">{"Report Type"}</label>
+                            <select id="schedule-type">
+                                <option value="usage">{"Usage Summary"}</option>
+                                <option value="camera-status">{"Camera Status"}</option>
+                                <option value="user-activity">{"User Activity"}</option>
+                                <option value="uptime-sla">{"Uptime SLA"}</option>
+                                <option value="offline-incidents">{"Offline Incidents"}</option>
+                            </select>
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-format">{"Format"}</label>
+                            <select id="schedule-format">
+                                <option value="pdf">{"PDF"}</option>
+                                <option value="csv">{"CSV"}</option>
+                                <option value="xlsx">{"Excel (XLSX)"}</option>
+                                <option value="json">{"JSON"}</option>
+                                <option value="html">{"HTML"}</option>
+                            </select>
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-period">{"Period"}</label>
+                            <select id="schedule-period">
+                                <option value="previous-month">{"Previous month"}</option>
+                                <option value="previous-week">{"Previous week"}</option>
+                                <option value="previous-day">{"Previous day"}</option>
+                                <option value="previous-quarter">{"Previous quarter"}</option>
+                                <option value="last-7-days">{"Last 7 days"}</option>
+                                <option value="last-30-days">{"Last 30 days"}</option>
+                            </select>
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-site">{"Site"}</label>
+                            <select id="schedule-site">
+                                {site_options(&site_list, "")}
+                            </select>
+                        </div>
+                        
+                        <div class="form-group">
+                            <label for="schedule-recipients">{"Recipients (comma separated)"}</label>
+                            <input type="text" id="schedule-recipients" placeholder="manager@example.com" />
+                        </div>
+                        
+                        <div>
+                            <button class="primary-button" onclick={on_add_schedule}>{"Add Schedule"}</button>
+                        </div>
+                    </div>
                     
                     {
-                        if let Some(log_list) = logs.as_ref() {
+                        if let Some(schedule_list) = report_schedules.as_ref() {
                             html! {
                                 <table class="data-table">
                                     <thead>
                                         <tr>
-                                            <th>{"Timestamp"}</th>
-                                            <th>{"User"}</th>
-                                            <th>{"Action"}</th>
-                                            <th>{"Target"}</th>
-                                            <th>{"Details"}</th>
+                                            <th>{"Name"}</th>
+                                            <th>{"Schedule"}</th>
+                                            <th>{"Type"}</th>
+                                            <th>{"Period"}</th>
+                                            <th>{"Recipients"}</th>
+                                            <th>{"Next Run"}</th>
+                                            <th>{"Status"}</th>
+                                            <th>{"Actions"}</th>
                                         </tr>
                                     </thead>
                                     <tbody>
                                         {
-                                            log_list.iter().map(|log| {
-                                                html! {
-                                                    <tr key={log.id.clone()}>
-                                                        <td>{&log.timestamp}</td>
-                                                        <td>{&log.user_id}</td>
-                                                        <td>{&log.action}</td>
+                                            schedule_list.iter().map(|schedule| {
+                                                let type_text = match schedule.type_ {
+                                                    ReportType::UsageSummary => "Usage Summary",
+                                                    ReportType::CameraStatus => "Camera Status",
+                                                    ReportType::UserActivity => "User Activity",
+                                                    ReportType::UptimeSla => "Uptime SLA",
+                                                    ReportType::OfflineIncidents => "Offline Incidents",
+                                                };
+                                                
+                                                let period_text = match schedule.period_rule {
+                                                    PeriodRule::PreviousDay => "Previous day",
+                                                    PeriodRule::PreviousWeek => "Previous week",
+                                                    PeriodRule::PreviousMonth => "Previous month",
+                                                    PeriodRule::PreviousQuarter => "Previous quarter",
+                                                    PeriodRule::Last7Days => "Last 7 days",
+                                                    PeriodRule::Last30Days => "Last 30 days",
+                                                };
+                                                
+                                                let on_toggle = {
+                                                    let schedule = schedule.clone();
+                                                    let report_schedules = report_schedules.clone();
+                                                    Callback::from(move |_: MouseEvent| {
+                                                        let mut updated = schedule.clone();
+                                                        updated.enabled = !updated.enabled;
+                                                        let report_schedules = report_schedules.clone();
+                                                        wasm_bindgen_futures::spawn_local(async move {
+                                                            match update_report_schedule(&updated.id, &updated).await {
+                                                                Ok(saved) => {
+                                                                    let list = (*report_schedules)
+                                                                        .clone()
+                                                                        .unwrap_or_default()
+                                                                        .into_iter()
+                                                                        .map(|s| if s.id == saved.id { saved.clone() } else { s })
+                                                                        .collect();
+                                                                    report_schedules.set(Some(list));
+                                                                }
+                                                                Err(e) => gloo::dialogs::alert(&e),
+                                                            }
+                                                        });
+                                                    })
+                                                };
+                                                
+                                                let on_delete = {
+                                                    let id = schedule.id.clone();
+                                                    let report_schedules = report_schedules.clone();
+                                                    Callback::from(move |_: MouseEvent| {
+                                                        let id = id.clone();
+                                                        let report_schedules = report_schedules.clone();
+                                                        wasm_bindgen_futures::spawn_local(async move {
+                                                            match delete_report_schedule(&id).await {
+                                                                Ok(()) => {
+                                                                    let list = (*report_schedules)
+                                                                        .clone()
+                                                                        .unwrap_or_default()
+                                                                        .into_iter()
+                                                                        .filter(|s| s.id != id)
+                                                                        .collect();
+                                                                    report_schedules.set(Some(list));
+                                                                }
+                                                                Err(e) => gloo::dialogs::alert(&e),
+                                                            }
+                                                        });
+                                                    })
+                                                };
+                                                
+                                                let (status_class, status_text) = if schedule.enabled {
+                                                    ("status-online", "Enabled")
+                                                } else {
+                                                    ("status-offline", "Disabled")
+                                                };
+                                                
+                                                html! {
+                                                    <tr key={schedule.id.clone()}>
+                                                        <td>{&schedule.name}</td>
+                                                        <td><code>{&schedule.cron}</code></td>
+                                                        <td>{type_text}</td>
+                                                        <td>{period_text}</td>
+                                                        <td>{schedule.recipients.join(", ")}</td>
+                                                        <td>{schedule.next_run.clone().unwrap_or_else(|| "-".to_string())}</td>
+                                                        <td>
+                                                            <span class={format!("status-indicator {}", status_class)}>
+                                                                {status_text}
+                                                            </span>
+                                                        </td>
+                                                        <td class="action-buttons">
+                                                            <button class="action-button" onclick={on_toggle}>
+                                                                {if schedule.enabled { "Disable" } else { "Enable" }}
+                                                            </button>
+                                                            <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            }).collect::<Html>()
+                                        }
+                                    </tbody>
+                                </table>
+                            }
+                        } else {
+                            html! { <div class="loading-container">{"Loading report schedules..."}</div> }
+                        }
+                    }
+                </div>
+            }
+        },
+        Page::LiveView => {
+            html! { <LiveView cameras={(*cameras).clone().unwrap_or_default()} /> }
+        },
+        Page::Events => {
+            html! { <EventTimeline cameras={(*cameras).clone().unwrap_or_default()} /> }
+        },
+        Page::Logs => {
+            html! {
+                <div class="logs-page">
+                    <h2>{"Activity Logs"}</h2>
+                    
+                    {
+                        if let Some(log_list) = logs.as_ref() {
+                            html! {
+                                <table class="data-table">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Timestamp"}</th>
+                                            <th>{"User"}</th>
+                                            <th>{"Action"}</th>
+                                            <th>{"Target"}</th>
+                                            <th>{"Details"}</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {
+                                            log_list.iter().map(|log| {
+                                                html! {
+                                                    <tr key={log.id.clone()}>
+                                                        <td>{&log.timestamp}</td>
+                                                        <td>{&log.user_id}</td>
+                                                        <td>{&log.action}</td>
                                                         <td>{&log.target}</td>
                                                         <td>{&log.details}</td>
                                                     </tr>
@@ -892,6 +1878,153 @@ fn render_page(
             }
         },
         Page::Settings => {
+            let site_list = (*sites).clone().unwrap_or_default();
+            let zone_list = (*zones).clone().unwrap_or_default();
+            
+            let on_add_site = {
+                let sites = sites.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let name = get_input_value("site-name");
+                    if name.trim().is_empty() {
+                        gloo::dialogs::alert("Please enter a site name");
+                        return;
+                    }
+                    let site = Site { id: String::new(), name, address: get_input_value("site-address") };
+                    
+                    let sites = sites.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match create_site(&site).await {
+                            Ok(created) => {
+                                set_input_value("site-name", "");
+                                set_input_value("site-address", "");
+                                let mut list = (*sites).clone().unwrap_or_default();
+                                list.push(created);
+                                sites.set(Some(list));
+                            }
+                            Err(e) => gloo::dialogs::alert(&e),
+                        }
+                    });
+                })
+            };
+            
+            let on_add_zone = {
+                let zones = zones.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let name = get_input_value("zone-name");
+                    let site_id = get_input_value("zone-site");
+                    if name.trim().is_empty() || site_id.is_empty() {
+                        gloo::dialogs::alert("Please choose a site and enter a zone name");
+                        return;
+                    }
+                    let zone = Zone { id: String::new(), site_id, name };
+                    
+                    let zones = zones.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match create_zone(&zone).await {
+                            Ok(created) => {
+                                set_input_value("zone-name", "");
+                                let mut list = (*zones).clone().unwrap_or_default();
+                                list.push(created);
+                                zones.set(Some(list));
+                            }
+                            Err(e) => gloo::dialogs::alert(&e),
+                        }
+                    });
+                })
+            };
+            
+            let sites_section = html! {
+                <div class="settings-section">
+                    <h3>{"Sites & Zones"}</h3>
+                    {
+                        site_list.iter().map(|site| {
+                            let on_delete_site = {
+                                let id = site.id.clone();
+                                let sites = sites.clone();
+                                Callback::from(move |_: MouseEvent| {
+                                    let id = id.clone();
+                                    let sites = sites.clone();
+                                    wasm_bindgen_futures::spawn_local(async move {
+                                        match delete_site(&id).await {
+                                            Ok(()) => {
+                                                let list = (*sites).clone().unwrap_or_default().into_iter().filter(|s| s.id != id).collect();
+                                                sites.set(Some(list));
+                                            }
+                                            Err(e) => gloo::dialogs::alert(&e),
+                                        }
+                                    });
+                                })
+                            };
+                            
+                            html! {
+                                <div class="settings-item" key={site.id.clone()}>
+                                    <div class="settings-label">
+                                        <strong>{&site.name}</strong>
+                                        <div>{&site.address}</div>
+                                    </div>
+                                    <div class="settings-value">
+                                        <ul class="zone-list">
+                                            {
+                                                zone_list.iter().filter(|z| z.site_id == site.id).map(|zone| {
+                                                    let on_delete_zone = {
+                                                        let id = zone.id.clone();
+                                                        let zones = zones.clone();
+                                                        let cameras = cameras.clone();
+                                                        Callback::from(move |_: MouseEvent| {
+                                                            let id = id.clone();
+                                                            let zones = zones.clone();
+                                                            let cameras = cameras.clone();
+                                                            wasm_bindgen_futures::spawn_local(async move {
+                                                                match delete_zone(&id).await {
+                                                                    Ok(()) => {
+                                                                        let list = (*zones).clone().unwrap_or_default().into_iter().filter(|z| z.id != id).collect();
+                                                                        zones.set(Some(list));
+                                                                        // Its cameras are now unassigned
+                                                                        if let Ok(data) = get_cameras().await {
+                                                                            cameras.set(Some(data));
+                                                                        }
+                                                                    }
+                                                                    Err(e) => gloo::dialogs::alert(&e),
+                                                                }
+                                                            });
+                                                        })
+                                                    };
+                                                    html! {
+                                                        <li key={zone.id.clone()}>
+                                                            {&zone.name}
+                                                            <button class="action-button danger" onclick={on_delete_zone}>{"Delete"}</button>
+                                                        </li>
+                                                    }
+                                                }).collect::<Html>()
+                                            }
+                                        </ul>
+                                        <button class="action-button danger" onclick={on_delete_site}>{"Delete Site"}</button>
+                                    </div>
+                                </div>
+                            }
+                        }).collect::<Html>()
+                    }
+                    
+                    <div class="form-group">
+                        <input type="text" id="site-name" placeholder="Site name" />
+                        <input type="text" id="site-address" placeholder="Address" />
+                        <button class="secondary-button" onclick={on_add_site}>{"Add Site"}</button>
+                    </div>
+                    
+                    <div class="form-group">
+                        <select id="zone-site">
+                            {
+                                site_list.iter().map(|site| html! {
+                                    <option key={site.id.clone()} value={site.id.clone()}>{&site.name}</option>
+                                }).collect::<Html>()
+                            }
+                        </select>
+                        <input type="text" id="zone-name" placeholder="Zone name" />
+                        <button class="secondary-button" onclick={on_add_zone}>{"Add Zone"}</button>
+                    </div>
+                </div>
+            };
+            
             html! {
                 <div class="settings-page">
                     <h2>{"Settings"}</h2>
@@ -989,6 +2122,52 @@ fn render_page(
                                                 <span>{" seconds"}</span>
                                             </div>
                                         </div>
+
+                                        <div class="settings-item">
+                                            <div class="settings-label">{"SLA Target"}</div>
+                                            <div class="settings-value">
+                                                <input type="number" value={app_settings.sla_target_percent.to_string()} min="0" max="100" step="0.1" />
+                                                <span>{" %"}</span>
+                                            </div>
+                                        </div>
+                                        
+                                        <div class="settings-item">
+                                            <div class="settings-label">{"Report Retention"}</div>
+                                            <div class="settings-value">
+                                                <input type="number" value={app_settings.report_retention_days.to_string()} min="0" />
+                                                <span>{" days (0 keeps reports forever)"}</span>
+                                            </div>
+                                        </div>
+                                        
+                                        <div class="settings-item">
+                                            <div class="settings-label">{"Report Storage"}</div>
+                                            <div class="settings-value">
+                                                {
+                                                    if let Some(storage) = report_storage.as_ref() {
+                                                        let used_mb = storage.used_bytes as f64 / (1024.0 * 1024.0);
+                                                        if storage.quota_bytes > 0 {
+                                                            let percent = (storage.used_bytes as f64 * 100.0 / storage.quota_bytes as f64).min(100.0);
+                                                            html! {
+                                                                <>
+                                                                    <progress value={format!("{:.1}", percent)} max="100"></progress>
+                                                                    <span>{format!(
+                                                                        " {:.2} MB of {} MB used ({} files)",
+                                                                        used_mb, app_settings.report_storage_quota_mb, storage.stored_files
+                                                                    )}</span>
+                                                                </>
+                                                            }
+                                                        } else {
+                                                            // A quota of 0 turns eviction off
+                                                            html! {
+                                                                <span>{format!("{:.2} MB used ({} files), no limit", used_mb, storage.stored_files)}</span>
+                                                            }
+                                                        }
+                                                    } else {
+                                                        html! { <span>{"Loading..."}</span> }
+                                                    }
+                                                }
+                                            </div>
+                                        </div>
                                         
                                         <div class="settings-actions">
                                             <button class="primary-button">{"Save Changes"}</button>
@@ -1001,88 +2180,3054 @@ fn render_page(
                             html! { <div class="loading-container">{"Loading settings..."}</div> }
                         }
                     }
+                    
+                    {sites_section}
+                    
+                    <AlertRulesEditor />
+                    
+                    <EmailSettings />
+                    
+                    <SmsSettings />
+                    
+                    <WebhooksPanel />
                 </div>
             }
         }
     }
 }
 
-// Modal Component
+// Number of days shown in each camera's availability sparkline
+const SPARKLINE_DAYS: i64 = 14;
+
 #[derive(Properties, PartialEq)]
-pub struct ModalProps {
-    #[prop_or_default]
-    pub title: String,
-    #[prop_or_default]
-    pub is_open: bool,
-    #[prop_or(Callback::noop())]
-    pub on_close: Callback<MouseEvent>,
-    #[prop_or_default]
-    pub children: Children,
+pub struct UptimeSparklineProps {
+    pub camera_id: String,
 }
 
-#[function_component(Modal)]
-fn modal(props: &ModalProps) -> Html {
-    if !props.is_open {
-        return html! {};
+// Daily availability bars for the last SPARKLINE_DAYS days of one camera
+#[function_component(UptimeSparkline)]
+fn uptime_sparkline(props: &UptimeSparklineProps) -> Html {
+    let points = use_state(|| None::<Vec<UptimePoint>>);
+    
+    {
+        let points = points.clone();
+        use_effect_with_deps(
+            move |camera_id: &String| {
+                let camera_id = camera_id.clone();
+                let from = (Utc::now() - chrono::Duration::days(SPARKLINE_DAYS - 1)).format("%Y-%m-%d").to_string();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match get_camera_status_history(&camera_id, &from, "").await {
+                        Ok(history) => points.set(Some(history.daily)),
+                        Err(e) => log::error!("Failed to load status history for {}: {:?}", camera_id, e),
+                    }
+                });
+                || ()
+            },
+            props.camera_id.clone(),
+        );
+    }
+    
+    let Some(points) = points.as_ref() else {
+        return html! { <span>{"-"}</span> };
+    };
+    
+    const BAR_WIDTH: usize = 5;
+    const HEIGHT: f64 = 20.0;
+    let width = points.len() * (BAR_WIDTH + 1);
+    
+    html! {
+        <svg class="sparkline" width={width.to_string()} height={HEIGHT.to_string()}>
+            {
+                points.iter().enumerate().map(|(index, point)| {
+                    let (height, color, label) = match point.availability_percent {
+                        Some(percent) => {
+                            let color = if percent >= 99.0 {
+                                "#4caf50"
+                            } else if percent >= 90.0 {
+                                "#ff9800"
+                            } else {
+                                "#f44336"
+                            };
+                            ((percent / 100.0 * HEIGHT).max(1.0), color, format!("{:.1}%", percent))
+                        }
+                        None => (1.0, "#9e9e9e", "No data".to_string()),
+                    };
+                    let day = point.start.get(..10).unwrap_or(&point.start).to_string();
+                    
+                    html! {
+                        <rect
+                            x={(index * (BAR_WIDTH + 1)).to_string()}
+                            y={(HEIGHT - height).to_string()}
+                            width={BAR_WIDTH.to_string()}
+                            height={height.to_string()}
+                            fill={color}
+                        >
+                            <title>{format!("{}: {}", day, label)}</title>
+                        </rect>
+                    }
+                }).collect::<Html>()
+            }
+        </svg>
     }
+}
 
-    let onclick = props.on_close.clone();
+#[derive(Properties, PartialEq)]
+pub struct SignInProps {
+    pub on_signed_in: Callback<User>,
+}
+
+// Shown instead of the pages until someone signs in
+#[function_component(SignIn)]
+fn sign_in(props: &SignInProps) -> Html {
+    let error = use_state(|| None::<String>);
+    let show_reset = use_state(|| false);
+    let reset_notice = use_state(|| None::<String>);
+    // Set when the page was opened from an emailed reset link
+    let reset_token = use_state(reset_token_from_url);
     
-    let modal_onclick = Callback::from(move |e: MouseEvent| {
-        e.stop_propagation();
-    });
+    let on_submit = {
+        let on_signed_in = props.on_signed_in.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let username = get_input_value("sign-in-username");
+            let password = get_input_value("sign-in-password");
+            if username.trim().is_empty() || password.is_empty() {
+                error.set(Some("Please enter your username and password".to_string()));
+                return;
+            }
+            
+            let on_signed_in = on_signed_in.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match login(&username, &password).await {
+                    Ok(response) => on_signed_in.emit(response.user),
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    let on_show_reset = {
+        let show_reset = show_reset.clone();
+        Callback::from(move |_: MouseEvent| show_reset.set(true))
+    };
+    
+    let on_reset = {
+        let show_reset = show_reset.clone();
+        let reset_notice = reset_notice.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let email = get_input_value("reset-email");
+            if email.trim().is_empty() {
+                error.set(Some("Please enter the email address of your account".to_string()));
+                return;
+            }
+            
+            let show_reset = show_reset.clone();
+            let reset_notice = reset_notice.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match request_password_reset(email.trim()).await {
+                    // The server gives the same answer for unknown addresses
+                    Ok(()) => {
+                        show_reset.set(false);
+                        error.set(None);
+                        reset_notice.set(Some("If that address has an account, a reset link is on its way".to_string()));
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    let on_set_password = {
+        let reset_token = reset_token.clone();
+        let reset_notice = reset_notice.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(token) = (*reset_token).clone() else { return };
+            let password = get_input_value("new-password");
+            if password.chars().count() < 8 {
+                error.set(Some("Passwords must be at least 8 characters".to_string()));
+                return;
+            }
+            if password != get_input_value("new-password-confirm") {
+                error.set(Some("The passwords don't match".to_string()));
+                return;
+            }
+            
+            let reset_token = reset_token.clone();
+            let reset_notice = reset_notice.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match confirm_password_reset(&token, &password).await {
+                    Ok(()) => {
+                        reset_token.set(None);
+                        error.set(None);
+                        reset_notice.set(Some("Your password has been changed. Sign in with the new one".to_string()));
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    if reset_token.is_some() {
+        return html! {
+            <div class="sign-in">
+                <h2>{"Choose a New Password"}</h2>
+                <form onsubmit={on_set_password}>
+                    <div class="form-group">
+                        <label for="new-password">{"New Password"}</label>
+                        <input type="password" id="new-password" autocomplete="new-password" />
+                    </div>
+                    <div class="form-group">
+                        <label for="new-password-confirm">{"Confirm Password"}</label>
+                        <input type="password" id="new-password-confirm" autocomplete="new-password" />
+                    </div>
+                    {
+                        match &*error {
+                            Some(message) => html! { <div class="error-message">{message}</div> },
+                            None => html! {},
+                        }
+                    }
+                    <button type="submit" class="primary-button">{"Set Password"}</button>
+                </form>
+            </div>
+        };
+    }
     
     html! {
-        <div class="modal-overlay" onclick={onclick.clone()}>
-            <div class="modal-container" onclick={modal_onclick}>
-                <div class="modal-header">
-                    <h3 class="modal-title">{&props.title}</h3>
-                    <button class="close-button" onclick={onclick}>{"×"}</button>
+        <div class="sign-in">
+            <h2>{"Sign In"}</h2>
+            <form onsubmit={on_submit}>
+                <div class="form-group">
+                    <label for="sign-in-username">{"Username"}</label>
+                    <input type="text" id="sign-in-username" autocomplete="username" />
                 </div>
-                <div class="modal-content">
-                    { for props.children.iter() }
+                <div class="form-group">
+                    <label for="sign-in-password">{"Password"}</label>
+                    <input type="password" id="sign-in-password" autocomplete="current-password" />
                 </div>
-            </div>
+                {
+                    match &*error {
+                        Some(message) => html! { <div class="error-message">{message}</div> },
+                        None => html! {},
+                    }
+                }
+                <button type="submit" class="primary-button">{"Sign In"}</button>
+            </form>
+            {
+                if *show_reset {
+                    html! {
+                        <div class="password-reset">
+                            <div class="form-group">
+                                <label for="reset-email">{"Email"}</label>
+                                <input type="email" id="reset-email" autocomplete="email" />
+                            </div>
+                            <button class="secondary-button" onclick={on_reset}>{"Send Reset Link"}</button>
+                        </div>
+                    }
+                } else if let Some(notice) = &*reset_notice {
+                    html! { <div class="settings-status">{notice}</div> }
+                } else {
+                    html! { <button class="link-button" onclick={on_show_reset}>{"Forgot password?"}</button> }
+                }
+            }
         </div>
     }
 }
 
-// Mock data for development
-fn mock_cameras() -> Vec<Camera> {
-    vec![
-        Camera {
-            id: "camera1".to_string(),
-            name: "Front Door".to_string(),
-            ip_address: "192.168.1.101".to_string(),
-            port: 8080,
-            location: "Main Entrance".to_string(),
-            active: true,
-            status: CameraStatus::Online,
-            last_update: "2023-01-15 10:30:45".to_string(),
-        },
-        Camera {
-            id: "camera2".to_string(),
-            name: "Back Yard".to_string(),
-            ip_address: "192.168.1.102".to_string(),
-            port: 8080,
-            location: "Rear Exit".to_string(),
-            active: true,
-            status: CameraStatus::Offline,
-            last_update: "2023-01-15 09:15:22".to_string(),
-        },
-        Camera {
-            id: "camera3".to_string(),
-            name: "Garage".to_string(),
-            ip_address: "192.168.1.103".to_string(),
-            port: 8080,
-            location: "Vehicle Entry".to_string(),
-            active: false,
-            status: CameraStatus::Maintenance,
-            last_update: "2023-01-14 14:45:30".to_string(),
-        },
-    ]
+#[derive(Properties, PartialEq)]
+pub struct PermissionEditorProps {
+    pub subject: GrantSubject,
+    pub name: String,
+    pub role: UserRole,
+    pub cameras: Vec<Camera>,
+    pub sites: Vec<Site>,
+    pub zones: Vec<Zone>,
+}
+
+// Users also get their effective access, which includes whatever their groups
+// have been granted
+fn load_permissions(
+    subject: GrantSubject,
+    grants: UseStateHandle<Option<Vec<CameraGrant>>>,
+    effective: UseStateHandle<Option<EffectivePermissions>>,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match get_camera_grants(&subject).await {
+            Ok(data) => grants.set(Some(data)),
+            Err(e) => gloo::dialogs::alert(&e),
+        }
+        if let GrantSubject::User(user_id) = &subject {
+            match get_effective_permissions(user_id).await {
+                Ok(data) => effective.set(Some(data)),
+                Err(e) => gloo::dialogs::alert(&e),
+            }
+        }
+    });
 }
 
+// "Permissions" button and dialog listing the cameras, zones and sites a user
+// or group has been granted, with controls to grant and revoke access
+#[function_component(PermissionEditor)]
+fn permission_editor(props: &PermissionEditorProps) -> Html {
+    let is_open = use_state(|| false);
+    let grants = use_state(|| None::<Vec<CameraGrant>>);
+    let effective = use_state(|| None::<EffectivePermissions>);
+    // Which kind of target the grant form offers: "camera", "zone" or "site"
+    let target_kind = use_state(|| "site".to_string());
+    
+    let on_open = {
+        let is_open = is_open.clone();
+        let grants = grants.clone();
+        let effective = effective.clone();
+        let subject = props.subject.clone();
+        Callback::from(move |_: MouseEvent| {
+            is_open.set(true);
+            load_permissions(subject.clone(), grants.clone(), effective.clone());
+        })
+    };
+    
+    let on_close = {
+        let is_open = is_open.clone();
+        Callback::from(move |_: MouseEvent| is_open.set(false))
+    };
+    
+    let on_kind_change = {
+        let target_kind = target_kind.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                target_kind.set(select.value());
+            }
+        })
+    };
+    
+    let on_grant = {
+        let grants = grants.clone();
+        let effective = effective.clone();
+        let target_kind = target_kind.clone();
+        let subject = props.subject.clone();
+        Callback::from(move |_: MouseEvent| {
+            let target_id = get_input_value("grant-target");
+            if target_id.is_empty() {
+                return;
+            }
+            let target = match target_kind.as_str() {
+                "camera" => GrantTarget::Camera(target_id),
+                "zone" => GrantTarget::Zone(target_id),
+                _ => GrantTarget::Site(target_id),
+            };
+            let grant = CameraGrant {
+                id: String::new(),
+                subject: subject.clone(),
+                target,
+                created_by: String::new(),
+            };
+            
+            let grants = grants.clone();
+            let effective = effective.clone();
+            let subject = subject.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_camera_grant(&grant).await {
+                    Ok(_) => load_permissions(subject, grants, effective),
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let target_label = |target: &GrantTarget| match target {
+        GrantTarget::Camera(id) => format!(
+            "Camera: {}",
+            props.cameras.iter().find(|c| &c.id == id).map_or(id.clone(), |c| c.name.clone())
+        ),
+        GrantTarget::Zone(id) => format!("Zone: {}", zone_label(&Some(id.clone()), &props.sites, &props.zones)),
+        GrantTarget::Site(id) => format!(
+            "Site: {}",
+            props.sites.iter().find(|s| &s.id == id).map_or(id.clone(), |s| s.name.clone())
+        ),
+    };
+    
+    let target_options: Vec<(String, String)> = match target_kind.as_str() {
+        "camera" => props.cameras.iter().map(|c| (c.id.clone(), c.name.clone())).collect(),
+        "zone" => props
+            .zones
+            .iter()
+            .map(|z| (z.id.clone(), zone_label(&Some(z.id.clone()), &props.sites, &props.zones)))
+            .collect(),
+        _ => props.sites.iter().map(|s| (s.id.clone(), s.name.clone())).collect(),
+    };
+    
+    html! {
+        <>
+            <button class="action-button" onclick={on_open}>{"Permissions"}</button>
+            <Modal title={format!("Camera access for {}", props.name)} is_open={*is_open} on_close={on_close}>
+                {
+                    if props.role != UserRole::Viewer {
+                        html! { <p>{"Administrators can see every camera; grants only matter once the user is a viewer."}</p> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    match &*grants {
+                        Some(list) if list.is_empty() => html! { <p>{"No cameras granted yet."}</p> },
+                        Some(list) => html! {
+                            <ul class="grant-list">
+                                {
+                                    list.iter().map(|grant| {
+                                        let on_revoke = {
+                                            let grants = grants.clone();
+                                            let effective = effective.clone();
+                                            let subject = props.subject.clone();
+                                            let id = grant.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let grants = grants.clone();
+                                                let effective = effective.clone();
+                                                let subject = subject.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match delete_camera_grant(&id).await {
+                                                        Ok(()) => load_permissions(subject, grants, effective),
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        html! {
+                                            <li key={grant.id.clone()}>
+                                                {target_label(&grant.target)}
+                                                <button class="action-button danger" onclick={on_revoke}>{"Revoke"}</button>
+                                            </li>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </ul>
+                        },
+                        None => html! { <div class="loading-container">{"Loading permissions..."}</div> },
+                    }
+                }
+                <div class="form-group">
+                    <select onchange={on_kind_change}>
+                        <option value="site" selected={*target_kind == "site"}>{"Site"}</option>
+                        <option value="zone" selected={*target_kind == "zone"}>{"Zone"}</option>
+                        <option value="camera" selected={*target_kind == "camera"}>{"Camera"}</option>
+                    </select>
+                    <select id="grant-target">
+                        {
+                            target_options.into_iter().map(|(id, label)| html! {
+                                <option key={id.clone()} value={id}>{label}</option>
+                            }).collect::<Html>()
+                        }
+                    </select>
+                    <button class="secondary-button" onclick={on_grant}>{"Grant Access"}</button>
+                </div>
+                {
+                    if let Some(effective) = effective.as_ref() {
+                        let source_label = |source: &PermissionSource| match source {
+                            PermissionSource::Direct => "direct".to_string(),
+                            PermissionSource::Group { name, .. } => format!("group {}", name),
+                        };
+                        html! {
+                            <div class="effective-permissions">
+                                <h4>{"Effective Access"}</h4>
+                                <p>{format!("Role: {} ({})", role_label(&effective.role), source_label(&effective.role_source))}</p>
+                                {
+                                    if effective.all_cameras {
+                                        html! { <p>{"Sees every camera."}</p> }
+                                    } else if effective.cameras.is_empty() {
+                                        html! { <p>{"Sees no cameras."}</p> }
+                                    } else {
+                                        html! {
+                                            <ul class="grant-list">
+                                                {
+                                                    effective.cameras.iter().map(|camera| {
+                                                        let sources = camera
+                                                            .sources
+                                                            .iter()
+                                                            .map(|s| format!("{} ({})", target_label(&s.target), source_label(&s.source)))
+                                                            .collect::<Vec<_>>()
+                                                            .join(", ");
+                                                        html! {
+                                                            <li key={camera.camera_id.clone()}>
+                                                                <span>{&camera.camera_name}</span>
+                                                                <span class="grant-sources">{sources}</span>
+                                                            </li>
+                                                        }
+                                                    }).collect::<Html>()
+                                                }
+                                            </ul>
+                                        }
+                                    }
+                                }
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </Modal>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GroupsPanelProps {
+    pub users: Vec<User>,
+    pub cameras: Vec<Camera>,
+    pub sites: Vec<Site>,
+    pub zones: Vec<Zone>,
+}
+
+// User groups with their members, role and camera grants. Members inherit the
+// group's role when it ranks above their own.
+#[function_component(GroupsPanel)]
+fn groups_panel(props: &GroupsPanelProps) -> Html {
+    let groups = use_state(|| None::<Vec<UserGroup>>);
+    
+    {
+        let groups = groups.clone();
+        use_effect_with_deps(move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_user_groups().await {
+                    Ok(data) => groups.set(Some(data)),
+                    // Viewers can't manage groups, so there is nothing to show
+                    Err(_) => groups.set(Some(Vec::new())),
+                }
+            });
+            || ()
+        }, ());
+    }
+    
+    // Swap in the server's copy of a group after a change
+    let replace_group = {
+        let groups = groups.clone();
+        Callback::from(move |updated: UserGroup| {
+            let list = (*groups)
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|g| if g.id == updated.id { updated.clone() } else { g })
+                .collect();
+            groups.set(Some(list));
+        })
+    };
+    
+    let on_create = {
+        let groups = groups.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = get_input_value("group-name");
+            if name.trim().is_empty() {
+                return;
+            }
+            let role = match get_input_value("group-role").as_str() {
+                "SuperAdmin" => UserRole::SuperAdmin,
+                "Admin" => UserRole::Admin,
+                _ => UserRole::Viewer,
+            };
+            let group = UserGroup { id: String::new(), name, role, members: Vec::new() };
+            
+            let groups = groups.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_user_group(&group).await {
+                    Ok(created) => {
+                        let mut list = (*groups).clone().unwrap_or_default();
+                        list.push(created);
+                        groups.set(Some(list));
+                        set_input_value("group-name", "");
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let user_name = |id: &String| {
+        props.users.iter().find(|u| &u.id == id).map_or(id.clone(), |u| u.name.clone())
+    };
+    
+    html! {
+        <div class="groups-panel">
+            <h3>{"Groups"}</h3>
+            {
+                match &*groups {
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Name"}</th>
+                                    <th>{"Role"}</th>
+                                    <th>{"Members"}</th>
+                                    <th>{"Actions"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|group| {
+                                        let select_id = format!("group-member-{}", group.id);
+                                        
+                                        let on_add = {
+                                            let replace_group = replace_group.clone();
+                                            let group_id = group.id.clone();
+                                            let select_id = select_id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let user_id = get_input_value(&select_id);
+                                                if user_id.is_empty() {
+                                                    return;
+                                                }
+                                                let replace_group = replace_group.clone();
+                                                let group_id = group_id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match add_group_member(&group_id, &user_id).await {
+                                                        Ok(updated) => replace_group.emit(updated),
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        
+                                        let on_delete = {
+                                            let groups = groups.clone();
+                                            let id = group.id.clone();
+                                            let name = group.name.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                if !gloo::dialogs::confirm(&format!("Delete group {}? Its members lose what it granted.", name)) {
+                                                    return;
+                                                }
+                                                let groups = groups.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match delete_user_group(&id).await {
+                                                        Ok(()) => {
+                                                            let list = (*groups).clone().unwrap_or_default().into_iter().filter(|g| g.id != id).collect();
+                                                            groups.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        
+                                        html! {
+                                            <tr key={group.id.clone()}>
+                                                <td>{&group.name}</td>
+                                                <td>{role_label(&group.role)}</td>
+                                                <td>
+                                                    <div class="group-members">
+                                                        {
+                                                            group.members.iter().map(|member| {
+                                                                let on_remove = {
+                                                                    let replace_group = replace_group.clone();
+                                                                    let group_id = group.id.clone();
+                                                                    let member = member.clone();
+                                                                    Callback::from(move |_: MouseEvent| {
+                                                                        let replace_group = replace_group.clone();
+                                                                        let group_id = group_id.clone();
+                                                                        let member = member.clone();
+                                                                        wasm_bindgen_futures::spawn_local(async move {
+                                                                            match remove_group_member(&group_id, &member).await {
+                                                                                Ok(updated) => replace_group.emit(updated),
+                                                                                Err(e) => gloo::dialogs::alert(&e),
+                                                                            }
+                                                                        });
+                                                                    })
+                                                                };
+                                                                html! {
+                                                                    <span key={member.clone()} class="member-chip">
+                                                                        {user_name(member)}
+                                                                        <button title="Remove" onclick={on_remove}>{"×"}</button>
+                                                                    </span>
+                                                                }
+                                                            }).collect::<Html>()
+                                                        }
+                                                    </div>
+                                                    <select id={select_id}>
+                                                        {
+                                                            props.users.iter().filter(|u| !group.members.contains(&u.id)).map(|u| html! {
+                                                                <option key={u.id.clone()} value={u.id.clone()}>{&u.name}</option>
+                                                            }).collect::<Html>()
+                                                        }
+                                                    </select>
+                                                    <button class="action-button" onclick={on_add}>{"Add"}</button>
+                                                </td>
+                                                <td>
+                                                    <PermissionEditor
+                                                        subject={GrantSubject::Group(group.id.clone())}
+                                                        name={group.name.clone()}
+                                                        role={group.role.clone()}
+                                                        cameras={props.cameras.clone()}
+                                                        sites={props.sites.clone()}
+                                                        zones={props.zones.clone()}
+                                                    />
+                                                    <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading groups..."}</div> },
+                }
+            }
+            <div class="form-group">
+                <input type="text" id="group-name" placeholder="Group name" />
+                <select id="group-role">
+                    <option value="Viewer">{"Viewer"}</option>
+                    <option value="Admin">{"Admin"}</option>
+                    <option value="SuperAdmin">{"Super Admin"}</option>
+                </select>
+                <button class="secondary-button" onclick={on_create}>{"Add Group"}</button>
+            </div>
+        </div>
+    }
+}
+
+// How often the header checks for new alerts
+const ALERT_POLL_SECONDS: u32 = 30;
+
+fn severity_class(severity: &AlertSeverity) -> &'static str {
+    match severity {
+        AlertSeverity::Info => "alert-info",
+        AlertSeverity::Warning => "alert-warning",
+        AlertSeverity::Critical => "alert-critical",
+    }
+}
+
+// Bell in the header with the number of open alerts, opening a list of
+// everything not yet resolved
+#[function_component(AlertsPanel)]
+fn alerts_panel() -> Html {
+    let alerts = use_state(Vec::<Alert>::new);
+    let is_open = use_state(|| false);
+    let is_admin = current_user().is_some_and(|user| user.role != UserRole::Viewer);
+    
+    {
+        let alerts = alerts.clone();
+        use_effect_with_deps(move |_| {
+            let load = move || {
+                let alerts = alerts.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match get_alerts().await {
+                        Ok(data) => alerts.set(data.into_iter().filter(|a| a.state != AlertState::Resolved).collect()),
+                        Err(e) => log::error!("Failed to refresh alerts: {:?}", e),
+                    }
+                });
+            };
+            load();
+            let interval = Interval::new(ALERT_POLL_SECONDS * 1_000, load);
+            move || drop(interval)
+        }, ());
+    }
+    
+    let toggle = {
+        let is_open = is_open.clone();
+        Callback::from(move |_: MouseEvent| is_open.set(!*is_open))
+    };
+    
+    let open_count = alerts.iter().filter(|a| a.state == AlertState::Open).count();
+    
+    html! {
+        <div class="alerts-container">
+            <button class="icon-button" title="Alerts" onclick={toggle}>
+                <i class="fas fa-bell"></i>
+                {
+                    if open_count > 0 {
+                        html! { <span class="alert-badge">{open_count}</span> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </button>
+            {
+                if *is_open {
+                    html! {
+                        <div class="alerts-dropdown">
+                            {
+                                if alerts.is_empty() {
+                                    html! { <p>{"No active alerts."}</p> }
+                                } else {
+                                    alerts.iter().map(|alert| {
+                                        let on_acknowledge = {
+                                            let alerts = alerts.clone();
+                                            let id = alert.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let alerts = alerts.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match acknowledge_alert(&id).await {
+                                                        Ok(updated) => {
+                                                            let list = (*alerts).iter().map(|a| if a.id == id { updated.clone() } else { a.clone() }).collect();
+                                                            alerts.set(list);
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let on_resolve = {
+                                            let alerts = alerts.clone();
+                                            let id = alert.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let alerts = alerts.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match resolve_alert(&id).await {
+                                                        Ok(_) => {
+                                                            let list = (*alerts).iter().filter(|a| a.id != id).cloned().collect();
+                                                            alerts.set(list);
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        html! {
+                                            <div key={alert.id.clone()} class={classes!("alert-item", severity_class(&alert.severity))}>
+                                                <div class="alert-message">{&alert.message}</div>
+                                                <div class="alert-meta">
+                                                    {format!("{:?} · raised {}", alert.severity, local_time(&alert.raised_at))}
+                                                    {
+                                                        if alert.state == AlertState::Acknowledged {
+                                                            html! { <span>{" · acknowledged"}</span> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </div>
+                                                <div class="alert-actions">
+                                                    {
+                                                        if alert.state == AlertState::Open {
+                                                            html! { <button class="action-button" onclick={on_acknowledge}>{"Acknowledge"}</button> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                    {
+                                                        if is_admin {
+                                                            html! { <button class="action-button" onclick={on_resolve}>{"Resolve"}</button> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </div>
+                                            </div>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
+
+fn describe_condition(condition: &AlertCondition) -> String {
+    match condition {
+        AlertCondition::CameraOffline { minutes } => format!("Camera offline for {} min", minutes),
+        AlertCondition::FailedLogins { count, minutes } => format!("More than {} failed sign-ins in {} min", count, minutes),
+        AlertCondition::LicenseExpiring { days } => format!("License expires within {} days", days),
+    }
+}
+
+// Settings section listing the alert rules, for admins to add, switch off
+// and remove rules
+#[function_component(AlertRulesEditor)]
+fn alert_rules_editor() -> Html {
+    let rules = use_state(|| None::<Vec<AlertRule>>);
+    // Which condition the form builds: "offline", "logins" or "license"
+    let condition_kind = use_state(|| "offline".to_string());
+    
+    {
+        let rules = rules.clone();
+        use_effect_with_deps(move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_alert_rules().await {
+                    Ok(data) => rules.set(Some(data)),
+                    // Viewers can't manage rules, so the section stays empty
+                    Err(_) => rules.set(Some(Vec::new())),
+                }
+            });
+            || ()
+        }, ());
+    }
+    
+    let on_kind_change = {
+        let condition_kind = condition_kind.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                condition_kind.set(select.value());
+            }
+        })
+    };
+    
+    let on_create = {
+        let rules = rules.clone();
+        let condition_kind = condition_kind.clone();
+        Callback::from(move |_: MouseEvent| {
+            let number = |id: &str| get_input_value(id).parse::<u32>().unwrap_or(0);
+            let condition = match condition_kind.as_str() {
+                "logins" => AlertCondition::FailedLogins { count: number("rule-value"), minutes: number("rule-minutes") },
+                "license" => AlertCondition::LicenseExpiring { days: number("rule-value") },
+                _ => AlertCondition::CameraOffline { minutes: number("rule-value") },
+            };
+            let severity = match get_input_value("rule-severity").as_str() {
+                "Critical" => AlertSeverity::Critical,
+                "Info" => AlertSeverity::Info,
+                _ => AlertSeverity::Warning,
+            };
+            let rule = AlertRule {
+                id: String::new(),
+                name: get_input_value("rule-name"),
+                condition,
+                severity,
+                cooldown_minutes: number("rule-cooldown"),
+                enabled: true,
+            };
+            
+            let rules = rules.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_alert_rule(&rule).await {
+                    Ok(created) => {
+                        let mut list = (*rules).clone().unwrap_or_default();
+                        list.push(created);
+                        rules.set(Some(list));
+                        set_input_value("rule-name", "");
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let value_label = match condition_kind.as_str() {
+        "logins" => "Attempts",
+        "license" => "Days",
+        _ => "Minutes",
+    };
+    
+    html! {
+        <div class="settings-section">
+            <h3>{"Alert Rules"}</h3>
+            {
+                match &*rules {
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Name"}</th>
+                                    <th>{"Condition"}</th>
+                                    <th>{"Severity"}</th>
+                                    <th>{"Cooldown"}</th>
+                                    <th>{"Enabled"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|rule| {
+                                        let on_toggle = {
+                                            let rules = rules.clone();
+                                            let rule = rule.clone();
+                                            Callback::from(move |_: Event| {
+                                                let rules = rules.clone();
+                                                let updated = AlertRule { enabled: !rule.enabled, ..rule.clone() };
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match update_alert_rule(&updated).await {
+                                                        Ok(saved) => {
+                                                            let list = (*rules).clone().unwrap_or_default().into_iter().map(|r| if r.id == saved.id { saved.clone() } else { r }).collect();
+                                                            rules.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let on_delete = {
+                                            let rules = rules.clone();
+                                            let id = rule.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let rules = rules.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match delete_alert_rule(&id).await {
+                                                        Ok(()) => {
+                                                            let list = (*rules).clone().unwrap_or_default().into_iter().filter(|r| r.id != id).collect();
+                                                            rules.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        html! {
+                                            <tr key={rule.id.clone()}>
+                                                <td>{&rule.name}</td>
+                                                <td>{describe_condition(&rule.condition)}</td>
+                                                <td><span class={classes!("severity", severity_class(&rule.severity))}>{format!("{:?}", rule.severity)}</span></td>
+                                                <td>{format!("{} min", rule.cooldown_minutes)}</td>
+                                                <td><input type="checkbox" checked={rule.enabled} onchange={on_toggle} /></td>
+                                                <td><button class="action-button danger" onclick={on_delete}>{"Delete"}</button></td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading alert rules..."}</div> },
+                }
+            }
+            <div class="form-group rule-form">
+                <input type="text" id="rule-name" placeholder="Rule name" />
+                <select onchange={on_kind_change}>
+                    <option value="offline" selected={*condition_kind == "offline"}>{"Camera offline"}</option>
+                    <option value="logins" selected={*condition_kind == "logins"}>{"Failed sign-ins"}</option>
+                    <option value="license" selected={*condition_kind == "license"}>{"License expiring"}</option>
+                </select>
+                <input type="number" id="rule-value" min="1" placeholder={value_label} />
+                {
+                    if *condition_kind == "logins" {
+                        html! { <input type="number" id="rule-minutes" min="1" placeholder="Within minutes" /> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <select id="rule-severity">
+                    <option value="Info">{"Info"}</option>
+                    <option value="Warning" selected=true>{"Warning"}</option>
+                    <option value="Critical">{"Critical"}</option>
+                </select>
+                <input type="number" id="rule-cooldown" min="0" placeholder="Cooldown (min)" />
+                <button class="secondary-button" onclick={on_create}>{"Add Rule"}</button>
+            </div>
+        </div>
+    }
+}
+
+// Settings sections each save only their own part, on top of the latest
+// settings from the server so they don't undo each other's changes
+async fn save_settings_section(change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+    let mut updated = get_settings().await?;
+    change(&mut updated);
+    update_settings(&updated).await?;
+    get_settings().await
+}
+
+fn outbox_status_class(status: &OutboxStatus) -> &'static str {
+    match status {
+        OutboxStatus::Pending => "status-maintenance",
+        OutboxStatus::Sent => "status-online",
+        OutboxStatus::Failed => "status-offline",
+    }
+}
+
+// Settings section for the outgoing mail server, with a test send and the
+// outbox of queued notifications. Only admins see it.
+#[function_component(EmailSettings)]
+fn email_settings() -> Html {
+    let is_admin = current_user().is_some_and(|user| user.role != UserRole::Viewer);
+    let settings = use_state(|| None::<Settings>);
+    let outbox = use_state(|| None::<Vec<OutboxEmail>>);
+    // Result of the last save or test send
+    let status = use_state(|| None::<String>);
+    
+    let load_outbox = {
+        let outbox = outbox.clone();
+        Callback::from(move |_: ()| {
+            let outbox = outbox.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(data) = get_outbox().await {
+                    outbox.set(Some(data));
+                }
+            });
+        })
+    };
+    
+    {
+        let settings = settings.clone();
+        let load_outbox = load_outbox.clone();
+        use_effect_with_deps(move |_| {
+            if is_admin {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(data) = get_settings().await {
+                        settings.set(Some(data));
+                    }
+                });
+                load_outbox.emit(());
+            }
+            || ()
+        }, ());
+    }
+    
+    let on_save = {
+        let settings = settings.clone();
+        let status = status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(current) = settings.as_ref() else { return };
+            let security = match get_input_value("smtp-security").as_str() {
+                "None" => SmtpSecurity::None,
+                "Tls" => SmtpSecurity::Tls,
+                _ => SmtpSecurity::StartTls,
+            };
+            let smtp = SmtpSettings {
+                host: get_input_value("smtp-host").trim().to_string(),
+                port: get_input_value("smtp-port").parse().unwrap_or(587),
+                security,
+                username: get_input_value("smtp-username").trim().to_string(),
+                // Left empty, the server keeps the stored password
+                password: get_input_value("smtp-password"),
+                has_password: current.smtp.has_password,
+                from_address: get_input_value("smtp-from").trim().to_string(),
+            };
+            
+            let settings = settings.clone();
+            let status = status.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match save_settings_section(|updated| updated.smtp = smtp).await {
+                    Ok(data) => {
+                        set_input_value("smtp-password", "");
+                        settings.set(Some(data));
+                        status.set(Some("Email settings saved".to_string()));
+                    }
+                    Err(e) => status.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    let on_test = {
+        let status = status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let to = get_input_value("smtp-test-to");
+            if to.trim().is_empty() {
+                status.set(Some("Enter an address to send the test email to".to_string()));
+                return;
+            }
+            
+            let status = status.clone();
+            status.set(Some(format!("Sending a test email to {}...", to.trim())));
+            wasm_bindgen_futures::spawn_local(async move {
+                match send_test_email(to.trim()).await {
+                    Ok(()) => status.set(Some(format!("Test email sent to {}", to.trim()))),
+                    Err(e) => status.set(Some(format!("Test email failed: {}", e))),
+                }
+            });
+        })
+    };
+    
+    if !is_admin {
+        return html! {};
+    }
+    let Some(app_settings) = settings.as_ref() else {
+        return html! { <div class="loading-container">{"Loading email settings..."}</div> };
+    };
+    let smtp = &app_settings.smtp;
+    
+    html! {
+        <div class="settings-section">
+            <h3>{"Email"}</h3>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"SMTP Server"}</div>
+                <div class="settings-value">
+                    <input type="text" id="smtp-host" placeholder="smtp.example.com" value={smtp.host.clone()} />
+                    <input type="number" id="smtp-port" min="1" max="65535" value={smtp.port.to_string()} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Security"}</div>
+                <div class="settings-value">
+                    <select id="smtp-security">
+                        <option value="StartTls" selected={smtp.security == SmtpSecurity::StartTls}>{"STARTTLS"}</option>
+                        <option value="Tls" selected={smtp.security == SmtpSecurity::Tls}>{"TLS"}</option>
+                        <option value="None" selected={smtp.security == SmtpSecurity::None}>{"None"}</option>
+                    </select>
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Username"}</div>
+                <div class="settings-value">
+                    <input type="text" id="smtp-username" autocomplete="off" value={smtp.username.clone()} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Password"}</div>
+                <div class="settings-value">
+                    <input type="password" id="smtp-password" autocomplete="new-password"
+                        placeholder={if smtp.has_password { "Stored; leave empty to keep" } else { "Not set" }} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"From Address"}</div>
+                <div class="settings-value">
+                    <input type="email" id="smtp-from" value={smtp.from_address.clone()} />
+                </div>
+            </div>
+            
+            <div class="settings-actions">
+                <button class="primary-button" onclick={on_save}>{"Save Email Settings"}</button>
+                <input type="email" id="smtp-test-to" placeholder="Send a test to..." />
+                <button class="secondary-button" onclick={on_test}>{"Send Test Email"}</button>
+            </div>
+            {
+                match &*status {
+                    Some(message) => html! { <div class="settings-status">{message}</div> },
+                    None => html! {},
+                }
+            }
+            
+            <h4>{"Outbox"}</h4>
+            {
+                match &*outbox {
+                    Some(list) if list.is_empty() => html! { <p>{"No emails have been queued"}</p> },
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Queued"}</th>
+                                    <th>{"Subject"}</th>
+                                    <th>{"To"}</th>
+                                    <th>{"Status"}</th>
+                                    <th>{"Attempts"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().rev().map(|email| {
+                                        let on_retry = {
+                                            let outbox = outbox.clone();
+                                            let id = email.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let outbox = outbox.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match retry_outbox_email(&id).await {
+                                                        Ok(saved) => {
+                                                            let list = (*outbox).clone().unwrap_or_default().into_iter().map(|e| if e.id == saved.id { saved.clone() } else { e }).collect();
+                                                            outbox.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        html! {
+                                            <tr key={email.id.clone()}>
+                                                <td>{local_time(&email.created_at)}</td>
+                                                <td>{&email.subject}</td>
+                                                <td>{email.to.join(", ")}</td>
+                                                <td title={email.last_error.clone().unwrap_or_default()}>
+                                                    <span class={outbox_status_class(&email.status)}>{format!("{:?}", email.status)}</span>
+                                                </td>
+                                                <td>{email.attempts}</td>
+                                                <td>
+                                                    {
+                                                        if email.status == OutboxStatus::Failed {
+                                                            html! { <button class="action-button" onclick={on_retry}>{"Retry"}</button> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading outbox..."}</div> },
+                }
+            }
+            <button class="secondary-button" onclick={load_outbox.reform(|_: MouseEvent| ())}>{"Refresh Outbox"}</button>
+        </div>
+    }
+}
+
+fn sms_status_class(status: &SmsStatus) -> &'static str {
+    match status {
+        SmsStatus::Pending => "status-maintenance",
+        SmsStatus::Sent => "status-online",
+        SmsStatus::Failed | SmsStatus::RateLimited => "status-offline",
+    }
+}
+
+// Settings section for the HTTP gateway SMS alerts go through, with a test
+// send and the log of messages. Only admins see it.
+#[function_component(SmsSettings)]
+fn sms_settings() -> Html {
+    let is_admin = current_user().is_some_and(|user| user.role != UserRole::Viewer);
+    let settings = use_state(|| None::<Settings>);
+    let messages = use_state(|| None::<Vec<SmsMessage>>);
+    // Result of the last save or test send
+    let status = use_state(|| None::<String>);
+    
+    let load_messages = {
+        let messages = messages.clone();
+        Callback::from(move |_: ()| {
+            let messages = messages.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(data) = get_sms_messages().await {
+                    messages.set(Some(data));
+                }
+            });
+        })
+    };
+    
+    {
+        let settings = settings.clone();
+        let load_messages = load_messages.clone();
+        use_effect_with_deps(move |_| {
+            if is_admin {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(data) = get_settings().await {
+                        settings.set(Some(data));
+                    }
+                });
+                load_messages.emit(());
+            }
+            || ()
+        }, ());
+    }
+    
+    let on_save = {
+        let settings = settings.clone();
+        let status = status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(current) = settings.as_ref() else { return };
+            let enabled = is_checked("sms-enabled");
+            let body_format = match get_input_value("sms-format").as_str() {
+                "Form" => SmsBodyFormat::Form,
+                _ => SmsBodyFormat::Json,
+            };
+            let sms = SmsGatewaySettings {
+                url: get_input_value("sms-url").trim().to_string(),
+                body_format,
+                body_template: get_input_value("sms-template"),
+                auth_header: get_input_value("sms-auth-header").trim().to_string(),
+                // Left empty, the server keeps the stored value
+                auth_value: get_input_value("sms-auth-value"),
+                has_auth_value: current.sms.has_auth_value,
+                max_per_hour: get_input_value("sms-limit").parse().unwrap_or(0),
+            };
+            
+            let settings = settings.clone();
+            let status = status.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match save_settings_section(|updated| {
+                    updated.sms = sms;
+                    updated.sms_alerts = enabled;
+                }).await {
+                    Ok(data) => {
+                        set_input_value("sms-auth-value", "");
+                        settings.set(Some(data));
+                        status.set(Some("SMS settings saved".to_string()));
+                    }
+                    Err(e) => status.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    let on_test = {
+        let status = status.clone();
+        Callback::from(move |_: MouseEvent| {
+            let to = get_input_value("sms-test-to").trim().replace(' ', "");
+            if to.is_empty() {
+                status.set(Some("Enter a number to send the test message to".to_string()));
+                return;
+            }
+            
+            let status = status.clone();
+            status.set(Some(format!("Sending a test message to {}...", to)));
+            wasm_bindgen_futures::spawn_local(async move {
+                match send_test_sms(&to).await {
+                    Ok(()) => status.set(Some(format!("Test message sent to {}", to))),
+                    Err(e) => status.set(Some(format!("Test message failed: {}", e))),
+                }
+            });
+        })
+    };
+    
+    if !is_admin {
+        return html! {};
+    }
+    let Some(app_settings) = settings.as_ref() else {
+        return html! { <div class="loading-container">{"Loading SMS settings..."}</div> };
+    };
+    let sms = &app_settings.sms;
+    
+    html! {
+        <div class="settings-section">
+            <h3>{"SMS"}</h3>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Send Alerts by SMS"}</div>
+                <div class="settings-value">
+                    <input type="checkbox" id="sms-enabled" checked={app_settings.sms_alerts} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Gateway URL"}</div>
+                <div class="settings-value">
+                    <input type="url" id="sms-url" placeholder="https://sms.example.com/send" value={sms.url.clone()} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Request Body"}</div>
+                <div class="settings-value">
+                    <select id="sms-format">
+                        <option value="Json" selected={sms.body_format == SmsBodyFormat::Json}>{"JSON"}</option>
+                        <option value="Form" selected={sms.body_format == SmsBodyFormat::Form}>{"Form"}</option>
+                    </select>
+                    <input type="text" id="sms-template" class="template-input" value={sms.body_template.clone()}
+                        title="{{to}} and {{message}} are replaced with the number and the text" />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Auth Header"}</div>
+                <div class="settings-value">
+                    <input type="text" id="sms-auth-header" placeholder="Authorization" value={sms.auth_header.clone()} />
+                    <input type="password" id="sms-auth-value" autocomplete="new-password"
+                        placeholder={if sms.has_auth_value { "Stored; leave empty to keep" } else { "Not set" }} />
+                </div>
+            </div>
+            
+            <div class="settings-item">
+                <div class="settings-label">{"Rate Limit"}</div>
+                <div class="settings-value">
+                    <input type="number" id="sms-limit" min="0" value={sms.max_per_hour.to_string()} />
+                    <span>{" messages per number per hour (0 for no limit)"}</span>
+                </div>
+            </div>
+            
+            <div class="settings-actions">
+                <button class="primary-button" onclick={on_save}>{"Save SMS Settings"}</button>
+                <input type="tel" id="sms-test-to" placeholder="+15550100" />
+                <button class="secondary-button" onclick={on_test}>{"Send Test SMS"}</button>
+            </div>
+            {
+                match &*status {
+                    Some(message) => html! { <div class="settings-status">{message}</div> },
+                    None => html! {},
+                }
+            }
+            
+            <h4>{"Messages"}</h4>
+            {
+                match &*messages {
+                    Some(list) if list.is_empty() => html! { <p>{"No messages have been sent"}</p> },
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Queued"}</th>
+                                    <th>{"To"}</th>
+                                    <th>{"Message"}</th>
+                                    <th>{"Status"}</th>
+                                    <th>{"Attempts"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|message| {
+                                        let on_retry = {
+                                            let messages = messages.clone();
+                                            let id = message.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let messages = messages.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match retry_sms_message(&id).await {
+                                                        Ok(saved) => {
+                                                            let list = (*messages).clone().unwrap_or_default().into_iter().map(|m| if m.id == saved.id { saved.clone() } else { m }).collect();
+                                                            messages.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let status_text = match (&message.status, message.gateway_status) {
+                                            (SmsStatus::RateLimited, _) => "Rate limited".to_string(),
+                                            (status, Some(code)) => format!("{:?} (HTTP {})", status, code),
+                                            (status, None) => format!("{:?}", status),
+                                        };
+                                        html! {
+                                            <tr key={message.id.clone()}>
+                                                <td>{local_time(&message.created_at)}</td>
+                                                <td>{&message.to}</td>
+                                                <td>{&message.body}</td>
+                                                <td title={message.last_error.clone().unwrap_or_default()}>
+                                                    <span class={sms_status_class(&message.status)}>{status_text}</span>
+                                                </td>
+                                                <td>{message.attempts}</td>
+                                                <td>
+                                                    {
+                                                        if message.status == SmsStatus::Failed {
+                                                            html! { <button class="action-button" onclick={on_retry}>{"Retry"}</button> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading messages..."}</div> },
+                }
+            }
+            <button class="secondary-button" onclick={load_messages.reform(|_: MouseEvent| ())}>{"Refresh Messages"}</button>
+        </div>
+    }
+}
+
+fn delivery_status_class(status: &WebhookDeliveryStatus) -> &'static str {
+    match status {
+        WebhookDeliveryStatus::Pending => "status-maintenance",
+        WebhookDeliveryStatus::Delivered => "status-online",
+        WebhookDeliveryStatus::DeadLetter => "status-offline",
+    }
+}
+
+fn delivery_rows(deliveries: &[WebhookDelivery], on_retry: Callback<String>) -> Html {
+    deliveries.iter().map(|delivery| {
+        let retry = {
+            let on_retry = on_retry.clone();
+            let id = delivery.id.clone();
+            Callback::from(move |_: MouseEvent| on_retry.emit(id.clone()))
+        };
+        html! {
+            <tr key={delivery.id.clone()}>
+                <td>{local_time(&delivery.created_at)}</td>
+                <td>{delivery.event.name()}</td>
+                <td title={delivery.last_error.clone().unwrap_or_default()}>
+                    <span class={delivery_status_class(&delivery.status)}>{format!("{:?}", delivery.status)}</span>
+                </td>
+                <td>{delivery.attempts}</td>
+                <td>{delivery.response_status.map(|status| status.to_string()).unwrap_or_else(|| "-".to_string())}</td>
+                <td>
+                    {
+                        if delivery.status == WebhookDeliveryStatus::DeadLetter {
+                            html! { <button class="action-button" onclick={retry}>{"Retry"}</button> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </td>
+            </tr>
+        }
+    }).collect::<Html>()
+}
+
+// Settings section for outgoing webhooks: subscriptions, the delivery log of
+// the selected one, and deliveries that ran out of retries. Only admins see it.
+#[function_component(WebhooksPanel)]
+fn webhooks_panel() -> Html {
+    let is_admin = current_user().is_some_and(|user| user.role != UserRole::Viewer);
+    let webhooks = use_state(|| None::<Vec<WebhookSubscription>>);
+    let selected = use_state(|| None::<String>);
+    let deliveries = use_state(Vec::<WebhookDelivery>::new);
+    let dead_letters = use_state(Vec::<WebhookDelivery>::new);
+    // Secret of the subscription just created, shown once
+    let new_secret = use_state(|| None::<String>);
+    
+    let load_deliveries = {
+        let selected = selected.clone();
+        let deliveries = deliveries.clone();
+        let dead_letters = dead_letters.clone();
+        Callback::from(move |id: Option<String>| {
+            selected.set(id.clone());
+            let deliveries = deliveries.clone();
+            let dead_letters = dead_letters.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match id {
+                    Some(id) => deliveries.set(get_webhook_deliveries(&id).await.unwrap_or_default()),
+                    None => deliveries.set(Vec::new()),
+                }
+                if let Ok(data) = get_webhook_dead_letters().await {
+                    dead_letters.set(data);
+                }
+            });
+        })
+    };
+    
+    {
+        let webhooks = webhooks.clone();
+        let load_deliveries = load_deliveries.clone();
+        use_effect_with_deps(move |_| {
+            if is_admin {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(data) = get_webhooks().await {
+                        webhooks.set(Some(data));
+                    }
+                });
+                load_deliveries.emit(None);
+            }
+            || ()
+        }, ());
+    }
+    
+    let on_retry = {
+        let selected = selected.clone();
+        let load_deliveries = load_deliveries.clone();
+        Callback::from(move |id: String| {
+            let selected = (*selected).clone();
+            let load_deliveries = load_deliveries.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match retry_webhook_delivery(&id).await {
+                    Ok(_) => load_deliveries.emit(selected),
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let on_create = {
+        let webhooks = webhooks.clone();
+        let new_secret = new_secret.clone();
+        Callback::from(move |_: MouseEvent| {
+            let webhook = WebhookSubscription {
+                id: String::new(),
+                name: get_input_value("webhook-name").trim().to_string(),
+                url: get_input_value("webhook-url").trim().to_string(),
+                events: WebhookEvent::ALL
+                    .into_iter()
+                    .filter(|event| is_checked(&format!("webhook-event-{}", event.name())))
+                    .collect(),
+                active: true,
+                created_at: String::new(),
+                // Left empty, the server generates one
+                secret: get_input_value("webhook-secret"),
+            };
+            
+            let webhooks = webhooks.clone();
+            let new_secret = new_secret.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_webhook(&webhook).await {
+                    Ok(mut created) => {
+                        new_secret.set(Some(std::mem::take(&mut created.secret)));
+                        let mut list = (*webhooks).clone().unwrap_or_default();
+                        list.push(created);
+                        webhooks.set(Some(list));
+                        set_input_value("webhook-name", "");
+                        set_input_value("webhook-url", "");
+                        set_input_value("webhook-secret", "");
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    if !is_admin {
+        return html! {};
+    }
+    
+    html! {
+        <div class="settings-section">
+            <h3>{"Webhooks"}</h3>
+            <p class="settings-hint">
+                {"Events are POSTed as JSON. X-Webhook-Signature holds sha256= and the hex HMAC-SHA256 of the X-Webhook-Timestamp value, a dot and the body, keyed with the subscription's secret."}
+            </p>
+            {
+                match &*new_secret {
+                    Some(secret) => html! {
+                        <div class="settings-status">
+                            {"Signing secret for the new webhook, shown only this once: "}
+                            <code>{secret}</code>
+                        </div>
+                    },
+                    None => html! {},
+                }
+            }
+            {
+                match &*webhooks {
+                    Some(list) if list.is_empty() => html! { <p>{"No webhooks yet"}</p> },
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Name"}</th>
+                                    <th>{"URL"}</th>
+                                    <th>{"Events"}</th>
+                                    <th>{"Active"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|webhook| {
+                                        let on_toggle = {
+                                            let webhooks = webhooks.clone();
+                                            let updated = WebhookSubscription { active: !webhook.active, ..webhook.clone() };
+                                            Callback::from(move |_: Event| {
+                                                let webhooks = webhooks.clone();
+                                                let updated = updated.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match update_webhook(&updated).await {
+                                                        Ok(saved) => {
+                                                            let list = (*webhooks).clone().unwrap_or_default().into_iter().map(|w| if w.id == saved.id { saved.clone() } else { w }).collect();
+                                                            webhooks.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let on_show = {
+                                            let load_deliveries = load_deliveries.clone();
+                                            let id = webhook.id.clone();
+                                            Callback::from(move |_: MouseEvent| load_deliveries.emit(Some(id.clone())))
+                                        };
+                                        let on_delete = {
+                                            let webhooks = webhooks.clone();
+                                            let selected = selected.clone();
+                                            let id = webhook.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let webhooks = webhooks.clone();
+                                                let selected = selected.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match delete_webhook(&id).await {
+                                                        Ok(()) => {
+                                                            let list = (*webhooks).clone().unwrap_or_default().into_iter().filter(|w| w.id != id).collect();
+                                                            webhooks.set(Some(list));
+                                                            if selected.as_deref() == Some(id.as_str()) {
+                                                                selected.set(None);
+                                                            }
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let events = webhook.events.iter().map(|event| event.name()).collect::<Vec<_>>().join(", ");
+                                        html! {
+                                            <tr key={webhook.id.clone()}>
+                                                <td>{&webhook.name}</td>
+                                                <td>{&webhook.url}</td>
+                                                <td>{events}</td>
+                                                <td><input type="checkbox" checked={webhook.active} onchange={on_toggle} /></td>
+                                                <td>
+                                                    <button class="action-button" onclick={on_show}>{"Deliveries"}</button>
+                                                    <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading webhooks..."}</div> },
+                }
+            }
+            
+            <div class="form-group rule-form">
+                <input type="text" id="webhook-name" placeholder="Name" />
+                <input type="url" id="webhook-url" placeholder="https://hooks.example.com/cameras" />
+                <input type="text" id="webhook-secret" placeholder="Secret (generated if empty)" />
+                {
+                    WebhookEvent::ALL.iter().map(|event| html! {
+                        <label key={event.name()}>
+                            <input type="checkbox" id={format!("webhook-event-{}", event.name())} />
+                            {event.name()}
+                        </label>
+                    }).collect::<Html>()
+                }
+                <button class="secondary-button" onclick={on_create}>{"Add Webhook"}</button>
+            </div>
+            
+            {
+                match (*selected).clone().and_then(|id| webhooks.as_ref()?.iter().find(|w| w.id == id).cloned()) {
+                    Some(webhook) => {
+                        let refresh = {
+                            let load_deliveries = load_deliveries.clone();
+                            let id = webhook.id.clone();
+                            Callback::from(move |_: MouseEvent| load_deliveries.emit(Some(id.clone())))
+                        };
+                        html! {
+                            <>
+                                <h4>{format!("Deliveries to {}", webhook.name)}</h4>
+                                {
+                                    if deliveries.is_empty() {
+                                        html! { <p>{"Nothing has been sent to this webhook yet"}</p> }
+                                    } else {
+                                        html! {
+                                            <table class="data-table">
+                                                <thead>
+                                                    <tr>
+                                                        <th>{"Time"}</th>
+                                                        <th>{"Event"}</th>
+                                                        <th>{"Status"}</th>
+                                                        <th>{"Attempts"}</th>
+                                                        <th>{"HTTP"}</th>
+                                                        <th></th>
+                                                    </tr>
+                                                </thead>
+                                                <tbody>{delivery_rows(&deliveries, on_retry.clone())}</tbody>
+                                            </table>
+                                        }
+                                    }
+                                }
+                                <button class="secondary-button" onclick={refresh}>{"Refresh Deliveries"}</button>
+                            </>
+                        }
+                    }
+                    None => html! {},
+                }
+            }
+            
+            {
+                if dead_letters.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <>
+                            <h4>{"Dead Letters"}</h4>
+                            <table class="data-table">
+                                <thead>
+                                    <tr>
+                                        <th>{"Time"}</th>
+                                        <th>{"Event"}</th>
+                                        <th>{"Status"}</th>
+                                        <th>{"Attempts"}</th>
+                                        <th>{"HTTP"}</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>{delivery_rows(&dead_letters, on_retry)}</tbody>
+                            </table>
+                        </>
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+// Turns a datetime-local input value, which is in the browser's time zone,
+// into the UTC timestamp the API expects
+fn local_input_to_utc(value: &str) -> Option<String> {
+    if value.is_empty() {
+        return None;
+    }
+    let iso = Date::new(&JsValue::from_str(value)).to_iso_string().as_string()?;
+    DateTime::parse_from_rfc3339(&iso)
+        .ok()
+        .map(|time| time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+fn local_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MaintenancePanelProps {
+    pub cameras: Vec<Camera>,
+    pub sites: Vec<Site>,
+    pub zones: Vec<Zone>,
+}
+
+// Maintenance windows that are open or still to come, with a form for
+// admins to schedule new ones
+#[function_component(MaintenancePanel)]
+fn maintenance_panel(props: &MaintenancePanelProps) -> Html {
+    let windows = use_state(|| None::<Vec<MaintenanceWindow>>);
+    // Which kind of target the form offers: "camera" or "zone"
+    let target_kind = use_state(|| "camera".to_string());
+    let is_admin = current_user().is_some_and(|user| user.role != UserRole::Viewer);
+    
+    {
+        let windows = windows.clone();
+        use_effect_with_deps(move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_upcoming_maintenance().await {
+                    Ok(data) => windows.set(Some(data)),
+                    Err(_) => windows.set(Some(Vec::new())),
+                }
+            });
+            || ()
+        }, ());
+    }
+    
+    let on_kind_change = {
+        let target_kind = target_kind.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                target_kind.set(select.value());
+            }
+        })
+    };
+    
+    let on_schedule = {
+        let windows = windows.clone();
+        let target_kind = target_kind.clone();
+        Callback::from(move |_: MouseEvent| {
+            let target_id = get_input_value("maintenance-target");
+            let (Some(start), Some(end)) = (
+                local_input_to_utc(&get_input_value("maintenance-start")),
+                local_input_to_utc(&get_input_value("maintenance-end")),
+            ) else {
+                gloo::dialogs::alert("Pick a start and end time");
+                return;
+            };
+            if target_id.is_empty() {
+                return;
+            }
+            let target = match target_kind.as_str() {
+                "zone" => MaintenanceTarget::Zone(target_id),
+                _ => MaintenanceTarget::Camera(target_id),
+            };
+            let window = MaintenanceWindow {
+                id: String::new(),
+                target,
+                start,
+                end,
+                reason: get_input_value("maintenance-reason"),
+                created_by: String::new(),
+                state: MaintenanceState::Scheduled,
+                affected: Vec::new(),
+            };
+            
+            let windows = windows.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match create_maintenance_window(&window).await {
+                    Ok(created) => {
+                        let mut list = (*windows).clone().unwrap_or_default();
+                        list.push(created);
+                        list.sort_by(|a, b| a.start.cmp(&b.start));
+                        windows.set(Some(list));
+                        set_input_value("maintenance-reason", "");
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let target_label = |target: &MaintenanceTarget| match target {
+        MaintenanceTarget::Camera(id) => props.cameras.iter().find(|c| &c.id == id).map_or(id.clone(), |c| c.name.clone()),
+        MaintenanceTarget::Zone(id) => zone_label(&Some(id.clone()), &props.sites, &props.zones),
+    };
+    
+    let target_options: Vec<(String, String)> = match target_kind.as_str() {
+        "zone" => props
+            .zones
+            .iter()
+            .map(|z| (z.id.clone(), zone_label(&Some(z.id.clone()), &props.sites, &props.zones)))
+            .collect(),
+        _ => props.cameras.iter().map(|c| (c.id.clone(), c.name.clone())).collect(),
+    };
+    
+    html! {
+        <>
+            {
+                match &*windows {
+                    Some(list) if list.is_empty() => html! { <p>{"No maintenance planned."}</p> },
+                    Some(list) => html! {
+                        <table class="dashboard-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Target"}</th>
+                                    <th>{"When"}</th>
+                                    <th>{"Reason"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|window| {
+                                        let on_cancel = {
+                                            let windows = windows.clone();
+                                            let id = window.id.clone();
+                                            Callback::from(move |_: MouseEvent| {
+                                                let windows = windows.clone();
+                                                let id = id.clone();
+                                                wasm_bindgen_futures::spawn_local(async move {
+                                                    match delete_maintenance_window(&id).await {
+                                                        Ok(()) => {
+                                                            let list = (*windows).clone().unwrap_or_default().into_iter().filter(|w| w.id != id).collect();
+                                                            windows.set(Some(list));
+                                                        }
+                                                        Err(e) => gloo::dialogs::alert(&e),
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        let in_progress = window.state == MaintenanceState::Active;
+                                        html! {
+                                            <tr key={window.id.clone()}>
+                                                <td>{target_label(&window.target)}</td>
+                                                <td>
+                                                    {format!("{} - {}", local_time(&window.start), local_time(&window.end))}
+                                                    {
+                                                        if in_progress {
+                                                            html! { <span class="status-indicator status-maintenance">{"In progress"}</span> }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </td>
+                                                <td>{&window.reason}</td>
+                                                <td>
+                                                    {
+                                                        if is_admin {
+                                                            html! {
+                                                                <button class="action-button danger" onclick={on_cancel}>
+                                                                    {if in_progress { "End" } else { "Cancel" }}
+                                                                </button>
+                                                            }
+                                                        } else {
+                                                            html! {}
+                                                        }
+                                                    }
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                    None => html! { <div class="loading-container">{"Loading maintenance windows..."}</div> },
+                }
+            }
+            {
+                if is_admin {
+                    html! {
+                        <div class="maintenance-form">
+                            <select onchange={on_kind_change}>
+                                <option value="camera" selected={*target_kind == "camera"}>{"Camera"}</option>
+                                <option value="zone" selected={*target_kind == "zone"}>{"Zone"}</option>
+                            </select>
+                            <select id="maintenance-target">
+                                {
+                                    target_options.into_iter().map(|(id, label)| html! {
+                                        <option key={id.clone()} value={id}>{label}</option>
+                                    }).collect::<Html>()
+                                }
+                            </select>
+                            <input type="datetime-local" id="maintenance-start" />
+                            <input type="datetime-local" id="maintenance-end" />
+                            <input type="text" id="maintenance-reason" placeholder="Reason" />
+                            <button class="secondary-button" onclick={on_schedule}>{"Schedule"}</button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DiscoverDialogProps {
+    pub on_imported: Callback<Vec<Camera>>,
+}
+
+// "Discover" button and dialog that finds ONVIF cameras on the LAN and
+// imports the selected ones
+#[function_component(DiscoverDialog)]
+fn discover_dialog(props: &DiscoverDialogProps) -> Html {
+    let is_open = use_state(|| false);
+    let devices = use_state(|| None::<Vec<DiscoveredDevice>>);
+    let selected = use_state(Vec::<String>::new);
+    
+    let on_open = {
+        let is_open = is_open.clone();
+        let devices = devices.clone();
+        let selected = selected.clone();
+        Callback::from(move |_: MouseEvent| {
+            is_open.set(true);
+            devices.set(None);
+            selected.set(Vec::new());
+            
+            let devices = devices.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match discover_cameras().await {
+                    Ok(data) => devices.set(Some(data)),
+                    Err(e) => {
+                        devices.set(Some(Vec::new()));
+                        gloo::dialogs::alert(&e);
+                    }
+                }
+            });
+        })
+    };
+    
+    let on_close = {
+        let is_open = is_open.clone();
+        Callback::from(move |_: MouseEvent| is_open.set(false))
+    };
+    
+    let on_import = {
+        let is_open = is_open.clone();
+        let devices = devices.clone();
+        let selected = selected.clone();
+        let on_imported = props.on_imported.clone();
+        Callback::from(move |_: MouseEvent| {
+            let chosen: Vec<DiscoveredDevice> = (*devices)
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|d| selected.contains(&d.endpoint))
+                .collect();
+            if chosen.is_empty() {
+                gloo::dialogs::alert("Select at least one device to import");
+                return;
+            }
+            
+            let is_open = is_open.clone();
+            let on_imported = on_imported.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match import_cameras(&chosen).await {
+                    Ok(cameras) => {
+                        on_imported.emit(cameras);
+                        is_open.set(false);
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let content = match devices.as_ref() {
+        None => html! { <div class="loading-container">{"Searching the network..."}</div> },
+        Some(list) if list.is_empty() => html! { <p>{"No ONVIF devices answered."}</p> },
+        Some(list) => html! {
+            <table class="data-table">
+                <thead>
+                    <tr>
+                        <th></th>
+                        <th>{"Name"}</th>
+                        <th>{"Address"}</th>
+                        <th>{"Hardware"}</th>
+                        <th>{"Location"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        list.iter().map(|device| {
+                            let endpoint = device.endpoint.clone();
+                            let on_toggle = {
+                                let selected = selected.clone();
+                                let endpoint = endpoint.clone();
+                                Callback::from(move |_: Event| {
+                                    let mut list = (*selected).clone();
+                                    match list.iter().position(|e| *e == endpoint) {
+                                        Some(index) => {
+                                            list.remove(index);
+                                        }
+                                        None => list.push(endpoint.clone()),
+                                    }
+                                    selected.set(list);
+                                })
+                            };
+                            
+                            html! {
+                                <tr key={endpoint.clone()}>
+                                    <td>
+                                        {
+                                            if device.registered_camera_id.is_some() {
+                                                html! { <span class="status-indicator status-online">{"Registered"}</span> }
+                                            } else {
+                                                html! {
+                                                    <input
+                                                        type="checkbox"
+                                                        checked={selected.contains(&endpoint)}
+                                                        onchange={on_toggle}
+                                                    />
+                                                }
+                                            }
+                                        }
+                                    </td>
+                                    <td>{device.name.clone().unwrap_or_default()}</td>
+                                    <td>{&device.ip_address}</td>
+                                    <td>{device.hardware.clone().unwrap_or_default()}</td>
+                                    <td>{device.location.clone().unwrap_or_default()}</td>
+                                </tr>
+                            }
+                        }).collect::<Html>()
+                    }
+                </tbody>
+            </table>
+        },
+    };
+    
+    html! {
+        <>
+            <button class="secondary-button" onclick={on_open}>{"Discover"}</button>
+            <Modal title="Discover Cameras" is_open={*is_open} on_close={on_close}>
+                {content}
+                <div class="settings-actions">
+                    <button class="primary-button" onclick={on_import}>{"Import Selected"}</button>
+                </div>
+            </Modal>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PtzPadProps {
+    pub camera_id: String,
+}
+
+// Send a PTZ command, remembering why it was refused if it was
+fn send_ptz(camera_id: String, command: PtzCommand, error: UseStateHandle<Option<PtzError>>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match ptz_command(&camera_id, &command).await {
+            Ok(()) => error.set(None),
+            Err(e) => error.set(Some(e)),
+        }
+    });
+}
+
+// Pan/tilt/zoom controls: holding a button moves the camera, releasing it stops
+#[function_component(PtzPad)]
+fn ptz_pad(props: &PtzPadProps) -> Html {
+    let error = use_state(|| None::<PtzError>);
+    let presets = use_state(|| None::<Vec<PtzPreset>>);
+    
+    let load_presets = {
+        let camera_id = props.camera_id.clone();
+        let error = error.clone();
+        let presets = presets.clone();
+        Callback::from(move |_: ()| {
+            let camera_id = camera_id.clone();
+            let error = error.clone();
+            let presets = presets.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_ptz_presets(&camera_id).await {
+                    Ok(list) => {
+                        error.set(None);
+                        presets.set(Some(list));
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    {
+        let load_presets = load_presets.clone();
+        use_effect_with_deps(move |_| {
+            load_presets.emit(());
+            || ()
+        }, props.camera_id.clone());
+    }
+    
+    // Moves are bounded by a timeout so a lost mouseup can't leave the camera spinning
+    let move_button = |label: &str, pan: f64, tilt: f64, zoom: f64| {
+        let on_press = {
+            let camera_id = props.camera_id.clone();
+            let error = error.clone();
+            Callback::from(move |_: MouseEvent| {
+                let command = if pan == 0.0 && tilt == 0.0 {
+                    PtzCommand::Zoom { speed: zoom, timeout_ms: Some(5000) }
+                } else {
+                    PtzCommand::ContinuousMove { pan, tilt, zoom, timeout_ms: Some(5000) }
+                };
+                send_ptz(camera_id.clone(), command, error.clone());
+            })
+        };
+        let on_release = {
+            let camera_id = props.camera_id.clone();
+            let error = error.clone();
+            Callback::from(move |_: MouseEvent| send_ptz(camera_id.clone(), PtzCommand::Stop, error.clone()))
+        };
+        
+        html! {
+            <button class="action-button" onmousedown={on_press} onmouseup={on_release}>{label.to_string()}</button>
+        }
+    };
+    
+    let on_save_preset = {
+        let camera_id = props.camera_id.clone();
+        let error = error.clone();
+        let load_presets = load_presets.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = get_input_value("ptz-preset-name");
+            if name.trim().is_empty() {
+                gloo::dialogs::alert("Please enter a preset name");
+                return;
+            }
+            
+            let camera_id = camera_id.clone();
+            let error = error.clone();
+            let load_presets = load_presets.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_ptz_preset(&camera_id, &name).await {
+                    Ok(_) => load_presets.emit(()),
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    let on_sign_in = {
+        let load_presets = load_presets.clone();
+        Callback::from(move |_: MouseEvent| {
+            let username = get_input_value("ptz-username");
+            let password = get_input_value("ptz-password");
+            let load_presets = load_presets.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match login(&username, &password).await {
+                    Ok(_) => load_presets.emit(()),
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    match (*error).clone() {
+        Some(PtzError::NotSignedIn) => {
+            return html! {
+                <div class="ptz-pad">
+                    <p>{"Sign in to control this camera."}</p>
+                    <div class="form-group">
+                        <label for="ptz-username">{"Username"}</label>
+                        <input type="text" id="ptz-username" />
+                    </div>
+                    <div class="form-group">
+                        <label for="ptz-password">{"Password"}</label>
+                        <input type="password" id="ptz-password" />
+                    </div>
+                    <button class="primary-button" onclick={on_sign_in}>{"Sign In"}</button>
+                </div>
+            };
+        }
+        Some(PtzError::Forbidden) => {
+            return html! {
+                <div class="ptz-pad">
+                    <p>{"Viewers can't control PTZ cameras."}</p>
+                </div>
+            };
+        }
+        _ => {}
+    }
+    
+    html! {
+        <div class="ptz-pad">
+            <div class="ptz-directions">
+                <div>{move_button("▲", 0.0, 0.5, 0.0)}</div>
+                <div>
+                    {move_button("◀", -0.5, 0.0, 0.0)}
+                    {move_button("▶", 0.5, 0.0, 0.0)}
+                </div>
+                <div>{move_button("▼", 0.0, -0.5, 0.0)}</div>
+            </div>
+            <div class="ptz-zoom">
+                {move_button("Zoom +", 0.0, 0.0, 0.5)}
+                {move_button("Zoom −", 0.0, 0.0, -0.5)}
+            </div>
+            {
+                if let Some(PtzError::Failed(message)) = (*error).as_ref() {
+                    html! { <p class="error-message">{message}</p> }
+                } else {
+                    html! {}
+                }
+            }
+            <h4>{"Presets"}</h4>
+            {
+                match presets.as_ref() {
+                    None => html! { <p>{"Loading presets..."}</p> },
+                    Some(list) if list.is_empty() => html! { <p>{"No presets saved."}</p> },
+                    Some(list) => html! {
+                        <ul class="ptz-presets">
+                            {
+                                list.iter().map(|preset| {
+                                    let on_goto = {
+                                        let camera_id = props.camera_id.clone();
+                                        let token = preset.token.clone();
+                                        let error = error.clone();
+                                        Callback::from(move |_: MouseEvent| {
+                                            send_ptz(camera_id.clone(), PtzCommand::GotoPreset { token: token.clone() }, error.clone())
+                                        })
+                                    };
+                                    html! {
+                                        <li key={preset.token.clone()}>
+                                            {&preset.name}
+                                            <button class="action-button" onclick={on_goto}>{"Go"}</button>
+                                        </li>
+                                    }
+                                }).collect::<Html>()
+                            }
+                        </ul>
+                    },
+                }
+            }
+            <div class="form-group">
+                <input type="text" id="ptz-preset-name" placeholder="Preset name" />
+                <button class="secondary-button" onclick={on_save_preset}>{"Save Current Position"}</button>
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CameraDetailProps {
+    pub camera: Camera,
+    pub sites: Vec<Site>,
+    pub zones: Vec<Zone>,
+    pub on_updated: Callback<Camera>,
+}
+
+// "View" button and dialog with what ONVIF told us about a camera, plus PTZ controls
+#[function_component(CameraDetail)]
+fn camera_detail(props: &CameraDetailProps) -> Html {
+    let is_open = use_state(|| false);
+    // Event token just issued, shown until the dialog is closed
+    let new_token = use_state(|| None::<String>);
+    let camera = &props.camera;
+    
+    let on_open = {
+        let is_open = is_open.clone();
+        Callback::from(move |_: MouseEvent| is_open.set(true))
+    };
+    
+    let on_close = {
+        let is_open = is_open.clone();
+        let new_token = new_token.clone();
+        Callback::from(move |_: MouseEvent| {
+            new_token.set(None);
+            is_open.set(false);
+        })
+    };
+    
+    let on_save_credentials = {
+        let id = camera.id.clone();
+        let on_updated = props.on_updated.clone();
+        Callback::from(move |_: MouseEvent| {
+            let username = get_input_value("camera-username");
+            let password = get_input_value("camera-password");
+            let id = id.clone();
+            let on_updated = on_updated.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_camera_credentials(&id, &username, &password).await {
+                    Ok(updated) => {
+                        set_input_value("camera-password", "");
+                        on_updated.emit(updated);
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let on_issue_token = {
+        let camera = camera.clone();
+        let on_updated = props.on_updated.clone();
+        let new_token = new_token.clone();
+        Callback::from(move |_: MouseEvent| {
+            if camera.has_event_token
+                && !gloo::dialogs::confirm("Issue a new token? The camera's current token will stop working.")
+            {
+                return;
+            }
+            let camera = camera.clone();
+            let on_updated = on_updated.clone();
+            let new_token = new_token.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match issue_event_token(&camera.id).await {
+                    Ok(token) => {
+                        new_token.set(Some(token));
+                        on_updated.emit(Camera { has_event_token: true, ..camera });
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let on_revoke_token = {
+        let camera = camera.clone();
+        let on_updated = props.on_updated.clone();
+        let new_token = new_token.clone();
+        Callback::from(move |_: MouseEvent| {
+            if !gloo::dialogs::confirm("Revoke the event token? The camera won't be able to push events until a new one is issued.") {
+                return;
+            }
+            let camera = camera.clone();
+            let on_updated = on_updated.clone();
+            let new_token = new_token.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match revoke_event_token(&camera.id).await {
+                    Ok(()) => {
+                        new_token.set(None);
+                        on_updated.emit(Camera { has_event_token: false, ..camera });
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let on_zone_change = {
+        let camera = camera.clone();
+        let on_updated = props.on_updated.clone();
+        Callback::from(move |e: Event| {
+            let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else { return };
+            let mut camera = camera.clone();
+            camera.zone_id = optional_value(select.value());
+            let on_updated = on_updated.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match update_camera(&camera.id, &camera).await {
+                    Ok(updated) => on_updated.emit(updated),
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let device = match &camera.device_info {
+        Some(info) => html! {
+            <div class="settings-info">
+                <p><strong>{"Manufacturer: "}</strong>{&info.manufacturer}</p>
+                <p><strong>{"Model: "}</strong>{&info.model}</p>
+                <p><strong>{"Firmware: "}</strong>{&info.firmware_version}</p>
+                <p><strong>{"Serial: "}</strong>{&info.serial_number}</p>
+            </div>
+        },
+        None => html! { <p>{"No device information yet. Use Refresh Info to fetch it."}</p> },
+    };
+    
+    html! {
+        <>
+            <button class="action-button" onclick={on_open}>{"View"}</button>
+            <Modal title={camera.name.clone()} is_open={*is_open} on_close={on_close}>
+                <h4>{"Zone"}</h4>
+                <select onchange={on_zone_change}>
+                    <option value="" selected={camera.zone_id.is_none()}>{"Unassigned"}</option>
+                    {
+                        props.sites.iter().map(|site| html! {
+                            <optgroup key={site.id.clone()} label={site.name.clone()}>
+                                {
+                                    props.zones.iter().filter(|z| z.site_id == site.id).map(|zone| html! {
+                                        <option
+                                            key={zone.id.clone()}
+                                            value={zone.id.clone()}
+                                            selected={camera.zone_id.as_ref() == Some(&zone.id)}
+                                        >
+                                            {&zone.name}
+                                        </option>
+                                    }).collect::<Html>()
+                                }
+                            </optgroup>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <h4>{"Device"}</h4>
+                {device}
+                <h4>{"Profiles"}</h4>
+                <ul>
+                    {
+                        camera.media_profiles.iter().map(|profile| {
+                            let resolution = match (profile.width, profile.height) {
+                                (Some(width), Some(height)) => format!(" {}x{}", width, height),
+                                _ => String::new(),
+                            };
+                            html! {
+                                <li key={profile.token.clone()}>
+                                    {format!("{} ({}{})", profile.name, profile.encoding.clone().unwrap_or_default(), resolution)}
+                                </li>
+                            }
+                        }).collect::<Html>()
+                    }
+                </ul>
+                <h4>{"Network"}</h4>
+                <ul>
+                    {
+                        camera.network_interfaces.iter().map(|interface| {
+                            let address = match (&interface.ipv4_address, interface.prefix_length) {
+                                (Some(ip), Some(prefix)) => format!("{}/{}", ip, prefix),
+                                (Some(ip), None) => ip.clone(),
+                                _ => "-".to_string(),
+                            };
+                            html! {
+                                <li key={interface.token.clone()}>
+                                    {format!("{} {}{}", interface.hw_address, address, if interface.dhcp { " (DHCP)" } else { "" })}
+                                </li>
+                            }
+                        }).collect::<Html>()
+                    }
+                </ul>
+                <h4>{"Credentials"}</h4>
+                <p>
+                    {
+                        if camera.has_credentials {
+                            "Stored encrypted. Enter new ones to replace them, or leave the username empty to remove them."
+                        } else {
+                            "No credentials stored."
+                        }
+                    }
+                </p>
+                <div class="form-group">
+                    <input type="text" id="camera-username" placeholder="Username" autocomplete="off" />
+                    <input type="password" id="camera-password" placeholder="Password" autocomplete="new-password" />
+                    <button class="secondary-button" onclick={on_save_credentials}>{"Save Credentials"}</button>
+                </div>
+                <h4>{"Event Push"}</h4>
+                <p>
+                    {
+                        if camera.has_event_token {
+                            format!("The camera can POST motion, tamper and I/O alarm events to /api/cameras/{}/events with its token.", camera.id)
+                        } else {
+                            "No event token issued, so the camera can't push events.".to_string()
+                        }
+                    }
+                </p>
+                {
+                    if let Some(token) = (*new_token).clone() {
+                        html! {
+                            <p class="settings-hint">
+                                {"Copy this token into the camera now; it won't be shown again: "}
+                                <code>{token}</code>
+                            </p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <div class="form-group">
+                    <button class="secondary-button" onclick={on_issue_token}>
+                        { if camera.has_event_token { "Issue New Token" } else { "Issue Token" } }
+                    </button>
+                    {
+                        if camera.has_event_token {
+                            html! { <button class="action-button danger" onclick={on_revoke_token}>{"Revoke"}</button> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <h4>{"PTZ"}</h4>
+                <PtzPad camera_id={camera.id.clone()} />
+            </Modal>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EventTimelineProps {
+    pub cameras: Vec<Camera>,
+}
+
+// Motion, tamper and I/O alarm events pushed by cameras, newest first
+#[function_component(EventTimeline)]
+fn event_timeline(props: &EventTimelineProps) -> Html {
+    let events = use_state(|| None::<Vec<CameraEvent>>);
+    let error = use_state(|| None::<String>);
+    
+    let load = {
+        let events = events.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let camera_id = get_input_value("events-camera");
+            let event_type = get_input_value("events-type");
+            let from = get_input_value("events-from");
+            let to = get_input_value("events-to");
+            let events = events.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_events(&camera_id, &event_type, &from, &to).await {
+                    Ok(data) => {
+                        error.set(None);
+                        events.set(Some(data));
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+    
+    {
+        let load = load.clone();
+        use_effect_with_deps(move |_| {
+            load.emit(());
+            || ()
+        }, ());
+    }
+    
+    let on_filter = {
+        let load = load.clone();
+        Callback::from(move |_: MouseEvent| load.emit(()))
+    };
+    
+    let camera_name = |id: &str| {
+        props.cameras.iter().find(|camera| camera.id == id).map_or_else(|| id.to_string(), |camera| camera.name.clone())
+    };
+    
+    html! {
+        <div class="events-page">
+            <h2>{"Camera Events"}</h2>
+            <div class="form-group">
+                <select id="events-camera">
+                    <option value="">{"All cameras"}</option>
+                    {
+                        props.cameras.iter().map(|camera| html! {
+                            <option key={camera.id.clone()} value={camera.id.clone()}>{&camera.name}</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <select id="events-type">
+                    <option value="">{"All types"}</option>
+                    {
+                        CameraEventType::ALL.iter().map(|event_type| html! {
+                            <option value={format!("{:?}", event_type)}>{event_type.label()}</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <label for="events-from">{"From"}</label>
+                <input type="date" id="events-from" />
+                <label for="events-to">{"To"}</label>
+                <input type="date" id="events-to" />
+                <button class="secondary-button" onclick={on_filter}>{"Filter"}</button>
+            </div>
+            {
+                if let Some(message) = (*error).clone() {
+                    html! { <p class="error-message">{message}</p> }
+                } else {
+                    html! {}
+                }
+            }
+            {
+                match (*events).as_ref() {
+                    None => html! { <div class="loading-container">{"Loading events..."}</div> },
+                    Some(list) if list.is_empty() => html! { <p>{"No events match."}</p> },
+                    Some(list) => html! {
+                        <table class="data-table">
+                            <thead>
+                                <tr>
+                                    <th>{"Time"}</th>
+                                    <th>{"Camera"}</th>
+                                    <th>{"Type"}</th>
+                                    <th>{"Details"}</th>
+                                    <th>{"Snapshot"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {
+                                    list.iter().map(|event| {
+                                        let snapshot = authorized_url(&format!("/api/events/{}/snapshot", event.id));
+                                        html! {
+                                            <tr key={event.id.clone()}>
+                                                <td>{local_time(&event.timestamp)}</td>
+                                                <td>{camera_name(&event.camera_id)}</td>
+                                                <td>{event.event_type.label()}</td>
+                                                <td>{&event.details}</td>
+                                                <td>
+                                                    {
+                                                        if event.snapshot_type.is_some() {
+                                                            html! {
+                                                                <a href={snapshot.clone()} target="_blank">
+                                                                    <img class="event-snapshot" src={snapshot} alt="Snapshot" loading="lazy" />
+                                                                </a>
+                                                            }
+                                                        } else {
+                                                            html! { {"-"} }
+                                                        }
+                                                    }
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                            </tbody>
+                        </table>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LiveViewProps {
+    pub cameras: Vec<Camera>,
+}
+
+const MAX_GRID_SIZE: u8 = 4;
+
+// Grid of MJPEG tiles that can be filled with cameras and saved as named layouts
+#[function_component(LiveView)]
+fn live_view(props: &LiveViewProps) -> Html {
+    let grid_size = use_state(|| 2u8);
+    let tiles = use_state(|| vec![None::<String>; usize::from(MAX_GRID_SIZE * MAX_GRID_SIZE)]);
+    let layouts = use_state(|| None::<Vec<LiveLayout>>);
+    let selected_layout = use_state(|| None::<String>);
+    
+    {
+        let layouts = layouts.clone();
+        use_effect_with_deps(move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_live_layouts().await {
+                    Ok(data) => layouts.set(Some(data)),
+                    Err(e) => {
+                        layouts.set(Some(Vec::new()));
+                        gloo::dialogs::alert(&e);
+                    }
+                }
+            });
+            || ()
+        }, ());
+    }
+    
+    let on_select_layout = {
+        let layouts = layouts.clone();
+        let selected_layout = selected_layout.clone();
+        let grid_size = grid_size.clone();
+        let tiles = tiles.clone();
+        Callback::from(move |e: Event| {
+            let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else { return };
+            let layout = (*layouts)
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|l| l.id == select.value());
+            
+            match layout {
+                Some(layout) => {
+                    let mut cameras = layout.cameras.clone();
+                    cameras.resize(usize::from(MAX_GRID_SIZE * MAX_GRID_SIZE), None);
+                    grid_size.set(layout.grid_size);
+                    tiles.set(cameras);
+                    set_input_value("live-layout-name", &layout.name);
+                    selected_layout.set(Some(layout.id));
+                }
+                None => selected_layout.set(None),
+            }
+        })
+    };
+    
+    let on_save = {
+        let layouts = layouts.clone();
+        let selected_layout = selected_layout.clone();
+        let grid_size = grid_size.clone();
+        let tiles = tiles.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = get_input_value("live-layout-name");
+            if name.trim().is_empty() {
+                gloo::dialogs::alert("Please enter a layout name");
+                return;
+            }
+            
+            let tile_count = usize::from(*grid_size * *grid_size);
+            let layout = LiveLayout {
+                id: String::new(),
+                name,
+                grid_size: *grid_size,
+                cameras: tiles.iter().take(tile_count).cloned().collect(),
+                created_by: String::new(),
+            };
+            let existing = (*selected_layout).clone();
+            let layouts = layouts.clone();
+            let selected_layout = selected_layout.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let saved = match &existing {
+                    Some(id) => update_live_layout(id, &layout).await,
+                    None => create_live_layout(&layout).await,
+                };
+                match saved {
+                    Ok(saved) => {
+                        let mut list: Vec<LiveLayout> = (*layouts)
+                            .clone()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|l| l.id != saved.id)
+                            .collect();
+                        selected_layout.set(Some(saved.id.clone()));
+                        list.push(saved);
+                        layouts.set(Some(list));
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let on_delete = {
+        let layouts = layouts.clone();
+        let selected_layout = selected_layout.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(id) = (*selected_layout).clone() else { return };
+            let layouts = layouts.clone();
+            let selected_layout = selected_layout.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match delete_live_layout(&id).await {
+                    Ok(()) => {
+                        let list = (*layouts)
+                            .clone()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|l| l.id != id)
+                            .collect();
+                        layouts.set(Some(list));
+                        selected_layout.set(None);
+                        set_input_value("live-layout-name", "");
+                    }
+                    Err(e) => gloo::dialogs::alert(&e),
+                }
+            });
+        })
+    };
+    
+    let size = *grid_size;
+    let tile_count = usize::from(size * size);
+    
+    html! {
+        <div class="live-view-page">
+            <div class="page-header">
+                <h2>{"Live View"}</h2>
+                <div class="live-view-toolbar">
+                    {
+                        (1..=MAX_GRID_SIZE).map(|n| {
+                            let grid_size = grid_size.clone();
+                            let class = if n == size { "action-button active" } else { "action-button" };
+                            html! {
+                                <button class={class} onclick={Callback::from(move |_: MouseEvent| grid_size.set(n))}>
+                                    {format!("{}x{}", n, n)}
+                                </button>
+                            }
+                        }).collect::<Html>()
+                    }
+                    <select id="live-layout" onchange={on_select_layout}>
+                        <option value="" selected={selected_layout.is_none()}>{"Unsaved layout"}</option>
+                        {
+                            layouts.as_ref().map(|list| list.iter().map(|layout| html! {
+                                <option
+                                    value={layout.id.clone()}
+                                    selected={selected_layout.as_ref() == Some(&layout.id)}
+                                >
+                                    {&layout.name}
+                                </option>
+                            }).collect::<Html>()).unwrap_or_default()
+                        }
+                    </select>
+                    <input type="text" id="live-layout-name" placeholder="Layout name" />
+                    <button class="primary-button" onclick={on_save}>{"Save Layout"}</button>
+                    if selected_layout.is_some() {
+                        <button class="action-button danger" onclick={on_delete}>{"Delete"}</button>
+                    }
+                </div>
+            </div>
+            <div class="live-grid" style={format!("grid-template-columns: repeat({}, 1fr);", size)}>
+                {
+                    (0..tile_count).map(|index| {
+                        let camera = tiles[index]
+                            .as_ref()
+                            .and_then(|id| props.cameras.iter().find(|c| &c.id == id));
+                        let on_choose = {
+                            let tiles = tiles.clone();
+                            Callback::from(move |e: Event| {
+                                let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else { return };
+                                let mut list = (*tiles).clone();
+                                list[index] = Some(select.value()).filter(|id| !id.is_empty());
+                                tiles.set(list);
+                            })
+                        };
+                        
+                        let video = match camera {
+                            Some(camera) if camera.mjpeg_url.is_some() => html! {
+                                <img class="live-tile-video" src={authorized_url(&format!("/api/cameras/{}/mjpeg", camera.id))} alt={camera.name.clone()} />
+                            },
+                            Some(_) => html! { <div class="live-tile-empty">{"No MJPEG stream configured"}</div> },
+                            None => html! { <div class="live-tile-empty">{"Empty"}</div> },
+                        };
+                        
+                        html! {
+                            <div class="live-tile" key={index}>
+                                {video}
+                                <select onchange={on_choose}>
+                                    <option value="" selected={camera.is_none()}>{"No camera"}</option>
+                                    {
+                                        props.cameras.iter().map(|c| html! {
+                                            <option
+                                                value={c.id.clone()}
+                                                selected={camera.is_some_and(|chosen| chosen.id == c.id)}
+                                            >
+                                                {&c.name}
+                                            </option>
+                                        }).collect::<Html>()
+                                    }
+                                </select>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+        </div>
+    }
+}
+
+// Modal Component
+#[derive(Properties, PartialEq)]
+pub struct ModalProps {
+    #[prop_or_default]
+    pub title: String,
+    #[prop_or_default]
+    pub is_open: bool,
+    #[prop_or(Callback::noop())]
+    pub on_close: Callback<MouseEvent>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+#[function_component(Modal)]
+fn modal(props: &ModalProps) -> Html {
+    if !props.is_open {
+        return html! {};
+    }
+
+    let onclick = props.on_close.clone();
+    
+    let modal_onclick = Callback::from(move |e: MouseEvent| {
+        e.stop_propagation();
+    });
+    
+    html! {
+        <div class="modal-overlay" onclick={onclick.clone()}>
+            <div class="modal-container" onclick={modal_onclick}>
+                <div class="modal-header">
+                    <h3 class="modal-title">{&props.title}</h3>
+                    <button class="close-button" onclick={onclick}>{"×"}</button>
+                </div>
+                <div class="modal-content">
+                    { for props.children.iter() }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+// Mock data for development
 // Mock data for other entities
 fn mock_users() -> Vec<User> {
     vec![
@@ -1091,6 +5236,7 @@ fn mock_users() -> Vec<User> {
             username: "admin".to_string(),
             name: "Admin User".to_string(),
             email: "admin@example.com".to_string(),
+            phone: String::new(),
             role: UserRole::SuperAdmin,
             active: true,
             last_login: "2025-02-25 08:15".to_string(),
@@ -1100,6 +5246,7 @@ fn mock_users() -> Vec<User> {
             username: "jane".to_string(),
             name: "Jane Smith".to_string(),
             email: "jane@example.com".to_string(),
+            phone: String::new(),
             role: UserRole::Admin,
             active: true,
             last_login: "2025-02-24 14:22".to_string(),
@@ -1109,6 +5256,7 @@ fn mock_users() -> Vec<User> {
             username: "john".to_string(),
             name: "John Doe".to_string(),
             email: "john@example.com".to_string(),
+            phone: String::new(),
             role: UserRole::Viewer,
             active: true,
             last_login: "2025-02-25 09:03".to_string(),
@@ -1118,6 +5266,7 @@ fn mock_users() -> Vec<User> {
             username: "alice".to_string(),
             name: "Alice Brown".to_string(),
             email: "alice@example.com".to_string(),
+            phone: String::new(),
             role: UserRole::Viewer,
             active: false,
             last_login: "2025-01-15 10:30".to_string(),
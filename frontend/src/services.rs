@@ -1,7 +1,13 @@
-use gloo_net::http::Request;
+use gloo::storage::{LocalStorage, Storage};
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+use futures::StreamExt;
+use gloo_net::http::{Request, Response};
+use gloo_net::websocket::{futures::WebSocket, Message};
+use gloo_timers::future::TimeoutFuture;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen_futures::spawn_local;
-use yew::UseStateHandle;
+use yew::{Callback, UseStateHandle};
 
 // Response type for errors
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -10,12 +16,14 @@ pub struct ApiError {
 }
 
 // User Models
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct User {
     pub id: String,
     pub username: String,
     pub name: String,
     pub email: String,
+    #[serde(default)]
+    pub phone: String,
     pub role: UserRole,
     pub active: bool,
     pub last_login: String,
@@ -39,6 +47,77 @@ pub struct Camera {
     pub active: bool,
     pub status: CameraStatus,
     pub last_update: String,
+    #[serde(default)]
+    pub stream_path: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub stream_info: Option<StreamInfo>,
+    #[serde(default)]
+    pub probe_error: Option<ProbeError>,
+    #[serde(default)]
+    pub onvif_url: Option<String>,
+    #[serde(default)]
+    pub device_info: Option<DeviceInfo>,
+    #[serde(default)]
+    pub network_interfaces: Vec<NetworkInterface>,
+    #[serde(default)]
+    pub media_profiles: Vec<MediaProfile>,
+    #[serde(default)]
+    pub info_updated_at: Option<String>,
+    #[serde(default)]
+    pub mjpeg_url: Option<String>,
+    #[serde(default)]
+    pub has_credentials: bool,
+    #[serde(default)]
+    pub zone_id: Option<String>,
+    #[serde(default)]
+    pub has_event_token: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DeviceInfo {
+    pub manufacturer: String,
+    pub model: String,
+    pub firmware_version: String,
+    pub serial_number: String,
+    pub hardware_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NetworkInterface {
+    pub token: String,
+    pub hw_address: String,
+    pub ipv4_address: Option<String>,
+    pub prefix_length: Option<u8>,
+    pub dhcp: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MediaProfile {
+    pub token: String,
+    pub name: String,
+    pub encoding: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub stream_uri: Option<String>,
+    #[serde(default)]
+    pub snapshot_uri: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DiscoveredDevice {
+    pub endpoint: String,
+    pub ip_address: String,
+    pub onvif_url: String,
+    pub scopes: Vec<String>,
+    pub name: Option<String>,
+    pub hardware: Option<String>,
+    pub location: Option<String>,
+    pub registered_camera_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -48,78 +127,2087 @@ pub enum CameraStatus {
     Maintenance,
 }
 
-// Activity Log Model
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ActivityLog {
-    pub id: String,
-    pub timestamp: String,
-    pub user_id: String,
-    pub action: String,
-    pub target: String,
-    pub details: String,
-}
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StreamInfo {
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ProbeError {
+    Unreachable(String),
+    AuthFailed,
+    StreamNotFound,
+    Protocol(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatusInterval {
+    pub status: CameraStatus,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UptimePoint {
+    pub start: String,
+    pub availability_percent: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatusHistory {
+    pub camera_id: String,
+    pub from: String,
+    pub to: String,
+    pub availability_percent: Option<f64>,
+    pub intervals: Vec<StatusInterval>,
+    pub daily: Vec<UptimePoint>,
+    pub weekly: Vec<UptimePoint>,
+}
+
+// Activity Log Model
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActivityLog {
+    pub id: String,
+    pub timestamp: String,
+    pub user_id: String,
+    pub action: String,
+    pub target: String,
+    pub details: String,
+}
+
+// Report Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Report {
+    pub id: String,
+    pub name: String,
+    pub type_: ReportType,
+    pub created_at: String,
+    pub created_by: String,
+    pub period: String,
+    pub format: ReportFormat,
+    pub url: String,
+    pub status: ReportStatus,
+    pub progress: u8,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub site_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ReportType {
+    UsageSummary,
+    CameraStatus,
+    UserActivity,
+    UptimeSla,
+    OfflineIncidents,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ReportFormat {
+    PDF,
+    CSV,
+    XLSX,
+    JSON,
+    HTML,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ReportStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl ReportStatus {
+    // Whether the report job is still waiting for or using a worker
+    pub fn is_pending(&self) -> bool {
+        matches!(self, ReportStatus::Queued | ReportStatus::Running)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateReportRequest {
+    pub name: Option<String>,
+    pub type_: ReportType,
+    pub format: ReportFormat,
+    pub from: String,
+    pub to: String,
+    pub site_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateReportResponse {
+    pub job_id: String,
+    pub report: Report,
+}
+
+// Report Schedule Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReportSchedule {
+    pub id: String,
+    pub name: String,
+    pub cron: String,
+    pub type_: ReportType,
+    pub format: ReportFormat,
+    pub period_rule: PeriodRule,
+    pub recipients: Vec<String>,
+    pub enabled: bool,
+    pub created_by: String,
+    #[serde(default)]
+    pub site_id: Option<String>,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+    pub last_report_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PeriodRule {
+    PreviousDay,
+    PreviousWeek,
+    PreviousMonth,
+    PreviousQuarter,
+    Last7Days,
+    Last30Days,
+}
+
+// Site Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Site {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Zone {
+    pub id: String,
+    pub site_id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatusCounts {
+    pub total: usize,
+    pub online: usize,
+    pub offline: usize,
+    pub maintenance: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ZoneSummary {
+    pub zone: Zone,
+    pub counts: StatusCounts,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SiteSummary {
+    pub site: Site,
+    pub counts: StatusCounts,
+    pub zones: Vec<ZoneSummary>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SitesSummary {
+    pub sites: Vec<SiteSummary>,
+    pub unassigned: StatusCounts,
+}
+
+// Camera permission Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum GrantSubject {
+    User(String),
+    Group(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum GrantTarget {
+    Camera(String),
+    Zone(String),
+    Site(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CameraGrant {
+    pub id: String,
+    pub subject: GrantSubject,
+    pub target: GrantTarget,
+    pub created_by: String,
+}
+
+// User group Model
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UserGroup {
+    pub id: String,
+    pub name: String,
+    pub role: UserRole,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+// Effective permission Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum PermissionSource {
+    Direct,
+    Group { id: String, name: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GrantSource {
+    pub grant_id: String,
+    pub target: GrantTarget,
+    pub source: PermissionSource,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EffectiveCamera {
+    pub camera_id: String,
+    pub camera_name: String,
+    pub sources: Vec<GrantSource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EffectivePermissions {
+    pub user_id: String,
+    pub role: UserRole,
+    pub role_source: PermissionSource,
+    pub all_cameras: bool,
+    pub cameras: Vec<EffectiveCamera>,
+}
+
+// Maintenance window Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum MaintenanceTarget {
+    Camera(String),
+    Zone(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum MaintenanceState {
+    Scheduled,
+    Active,
+    Completed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    pub target: MaintenanceTarget,
+    pub start: String,
+    pub end: String,
+    pub reason: String,
+    pub created_by: String,
+    pub state: MaintenanceState,
+    #[serde(default)]
+    pub affected: Vec<String>,
+}
+
+// Alert Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AlertCondition {
+    CameraOffline { minutes: u32 },
+    FailedLogins { count: u32, minutes: u32 },
+    LicenseExpiring { days: u32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    pub condition: AlertCondition,
+    pub severity: AlertSeverity,
+    pub cooldown_minutes: u32,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AlertState {
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Alert {
+    pub id: String,
+    pub rule_id: String,
+    pub severity: AlertSeverity,
+    pub subject: String,
+    pub camera_id: Option<String>,
+    pub message: String,
+    pub state: AlertState,
+    pub raised_at: String,
+    pub last_seen: String,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+}
+
+// Live View layout Model
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LiveLayout {
+    pub id: String,
+    pub name: String,
+    pub grid_size: u8,
+    pub cameras: Vec<Option<String>>,
+    pub created_by: String,
+}
+
+// Settings Model
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Settings {
+    pub registered_to: String,
+    pub server_status: bool,
+    pub api_url: String,
+    pub license_expiry: String,
+    pub theme: String,
+    pub email_alerts: bool,
+    pub sms_alerts: bool,
+    pub refresh_interval: u32,
+    pub app_version: String,
+    #[serde(default)]
+    pub sla_target_percent: f64,
+    #[serde(default)]
+    pub report_retention_days: u32,
+    #[serde(default)]
+    pub report_storage_quota_mb: u64,
+    #[serde(default)]
+    pub smtp: SmtpSettings,
+    #[serde(default)]
+    pub sms: SmsGatewaySettings,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SmtpSecurity {
+    None,
+    #[default]
+    StartTls,
+    Tls,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: String,
+    // Never returned by the server; only sent when changing the password
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub has_password: bool,
+    pub from_address: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SmsBodyFormat {
+    #[default]
+    Json,
+    Form,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SmsGatewaySettings {
+    pub url: String,
+    #[serde(default)]
+    pub body_format: SmsBodyFormat,
+    pub body_template: String,
+    #[serde(default)]
+    pub auth_header: String,
+    // Never returned by the server; only sent when changing the credentials
+    #[serde(default)]
+    pub auth_value: String,
+    #[serde(default)]
+    pub has_auth_value: bool,
+    #[serde(default)]
+    pub max_per_hour: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum SmsStatus {
+    Pending,
+    Sent,
+    Failed,
+    RateLimited,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SmsMessage {
+    pub id: String,
+    pub user_id: String,
+    pub to: String,
+    pub body: String,
+    pub status: SmsStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub sent_at: Option<String>,
+    pub gateway_status: Option<u16>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TestSmsRequest {
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct TestSmsResponse {
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum CameraEventType {
+    Motion,
+    Tamper,
+    IoAlarm,
+}
+
+impl CameraEventType {
+    pub const ALL: [CameraEventType; 3] = [CameraEventType::Motion, CameraEventType::Tamper, CameraEventType::IoAlarm];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraEventType::Motion => "Motion",
+            CameraEventType::Tamper => "Tamper",
+            CameraEventType::IoAlarm => "I/O alarm",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CameraEvent {
+    pub id: String,
+    pub camera_id: String,
+    #[serde(rename = "type")]
+    pub event_type: CameraEventType,
+    pub timestamp: String,
+    pub received_at: String,
+    #[serde(default)]
+    pub details: String,
+    pub snapshot_type: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+struct EventToken {
+    token: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum WebhookEvent {
+    #[serde(rename = "camera.status_changed")]
+    CameraStatusChanged,
+    #[serde(rename = "user.created")]
+    UserCreated,
+    #[serde(rename = "user.updated")]
+    UserUpdated,
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+    #[serde(rename = "report.ready")]
+    ReportReady,
+    #[serde(rename = "alert.raised")]
+    AlertRaised,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 6] = [
+        WebhookEvent::CameraStatusChanged,
+        WebhookEvent::UserCreated,
+        WebhookEvent::UserUpdated,
+        WebhookEvent::UserDeleted,
+        WebhookEvent::ReportReady,
+        WebhookEvent::AlertRaised,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::CameraStatusChanged => "camera.status_changed",
+            WebhookEvent::UserCreated => "user.created",
+            WebhookEvent::UserUpdated => "user.updated",
+            WebhookEvent::UserDeleted => "user.deleted",
+            WebhookEvent::ReportReady => "report.ready",
+            WebhookEvent::AlertRaised => "alert.raised",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    #[serde(default)]
+    pub created_at: String,
+    // Only sent when creating a subscription or replacing its secret
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    DeadLetter,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub delivered_at: Option<String>,
+    pub response_status: Option<u16>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum EmailKind {
+    Alert,
+    ScheduledReport,
+    PasswordReset,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OutboxEmail {
+    pub id: String,
+    pub kind: EmailKind,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub sent_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TestEmailRequest {
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct TestEmailResponse {
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PasswordResetRequest {
+    email: String,
+}
+
+#[derive(Serialize)]
+struct PasswordResetConfirm {
+    token: String,
+    password: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReportStorage {
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub reports: usize,
+    pub stored_files: usize,
+}
+
+// Authentication types
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub user: User,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// PTZ types
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PtzCommand {
+    ContinuousMove { pan: f64, tilt: f64, zoom: f64, timeout_ms: Option<u64> },
+    Stop,
+    AbsoluteMove { pan: f64, tilt: f64, zoom: f64 },
+    RelativeMove { pan: f64, tilt: f64, zoom: f64 },
+    Zoom { speed: f64, timeout_ms: Option<u64> },
+    ListPresets,
+    GotoPreset { token: String },
+    SetPreset { name: String, token: Option<String> },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PtzPreset {
+    pub token: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PresetSavedResponse {
+    token: String,
+}
+
+// Why a PTZ request was refused; the pad reacts differently to each
+#[derive(Clone, Debug, PartialEq)]
+pub enum PtzError {
+    NotSignedIn,
+    Forbidden,
+    Failed(String),
+}
+
+const AUTH_TOKEN_KEY: &str = "auth_token";
+const AUTH_USER_KEY: &str = "auth_user";
+
+fn auth_token() -> Option<String> {
+    LocalStorage::get(AUTH_TOKEN_KEY).ok()
+}
+
+// The user who signed in on this browser, if anyone has
+pub fn current_user() -> Option<User> {
+    auth_token()?;
+    LocalStorage::get(AUTH_USER_KEY).ok()
+}
+
+pub fn sign_out() {
+    LocalStorage::delete(AUTH_TOKEN_KEY);
+    LocalStorage::delete(AUTH_USER_KEY);
+}
+
+// Sign out here and end the session on the server
+pub async fn logout() {
+    let request = with_auth(Request::post("/api/auth/logout"));
+    sign_out();
+    let _ = request.send().await;
+}
+
+// The signed-in user as the server sees them now. Sessions don't survive a
+// server restart, so a stored token the server no longer knows is dropped.
+pub async fn check_session() -> Option<User> {
+    auth_token()?;
+    match with_auth(Request::get("/api/auth/session")).send().await {
+        Ok(response) if response.status() == 401 => {
+            sign_out();
+            None
+        }
+        Ok(response) if response.status() == 200 => match response.json::<User>().await {
+            Ok(user) => {
+                let _ = LocalStorage::set(AUTH_USER_KEY, &user);
+                Some(user)
+            }
+            Err(_) => current_user(),
+        },
+        // Unreachable or failing for now; keep what we have
+        _ => current_user(),
+    }
+}
+
+// URL with the token as a query parameter, for images and links that can't
+// send an Authorization header
+pub fn authorized_url(path: &str) -> String {
+    match auth_token() {
+        Some(token) => {
+            let separator = if path.contains('?') { '&' } else { '?' };
+            format!("{}{}token={}", path, separator, token)
+        }
+        None => path.to_string(),
+    }
+}
+
+// Attach the signed-in user's token for endpoints that check roles
+fn with_auth(request: Request) -> Request {
+    match auth_token() {
+        Some(token) => request.header("Authorization", &format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+// API Service methods
+pub async fn login(username: &str, password: &str) -> Result<LoginResponse, String> {
+    let request = LoginRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    };
+
+    let response = Request::post("/api/auth/login")
+        .json(&request)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<LoginResponse>().await {
+                    Ok(data) => {
+                        let _ = LocalStorage::set(AUTH_TOKEN_KEY, &data.token);
+                        let _ = LocalStorage::set(AUTH_USER_KEY, &data.user);
+                        Ok(data)
+                    }
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Invalid credentials".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// User Service methods
+pub async fn get_users() -> Result<Vec<User>, String> {
+    let response = with_auth(Request::get("/api/users"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<User>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get users".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_user(id: &str) -> Result<User, String> {
+    let response = with_auth(Request::get(&format!("/api/users/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<User>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("User not found".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_user(user: &User) -> Result<User, String> {
+    let response = with_auth(Request::post("/api/users"))
+        .json(user)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<User>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can add users".to_string())
+            } else {
+                Err("Failed to create user".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn update_user(id: &str, user: &User) -> Result<User, String> {
+    let response = with_auth(Request::put(&format!("/api/users/{}", id)))
+        .json(user)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<User>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 400 {
+                Err("Phone numbers need the international format, like +15550100".to_string())
+            } else if response.status() == 403 {
+                Err("Only administrators can change other users, roles or account status".to_string())
+            } else {
+                Err("Failed to update user".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_user(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/users/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 403 {
+                Err("Only administrators can delete users".to_string())
+            } else {
+                Err("Failed to delete user".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Camera Service methods
+pub async fn get_cameras() -> Result<Vec<Camera>, String> {
+    let response = with_auth(Request::get("/api/cameras"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<Camera>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get cameras".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_camera(id: &str) -> Result<Camera, String> {
+    let response = with_auth(Request::get(&format!("/api/cameras/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Camera>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Camera not found".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Create a new camera
+pub async fn create_camera(camera: &Camera) -> Result<Camera, String> {
+    let response = with_auth(Request::post("/api/cameras"))
+        .json(camera)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<Camera>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can add cameras".to_string())
+            } else {
+                Err("Failed to create camera".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Update an existing camera
+pub async fn update_camera(id: &str, camera: &Camera) -> Result<Camera, String> {
+    let response = with_auth(Request::put(&format!("/api/cameras/{}", id)))
+        .json(camera)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Camera>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can change cameras".to_string())
+            } else {
+                Err("Failed to update camera".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Delete a camera
+pub async fn delete_camera(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/cameras/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 403 {
+                Err("Only administrators can delete cameras".to_string())
+            } else {
+                Err("Failed to delete camera".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn set_camera_credentials(id: &str, username: &str, password: &str) -> Result<Camera, String> {
+    let credentials = CameraCredentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    };
+
+    let response = with_auth(Request::put(&format!("/api/cameras/{}/credentials", id)))
+        .json(&credentials)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Camera>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 401 {
+                Err("Sign in to change camera credentials".to_string())
+            } else if response.status() == 403 {
+                Err("Only administrators can change camera credentials".to_string())
+            } else {
+                Err("Failed to save camera credentials".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn refresh_camera_info(id: &str) -> Result<Camera, String> {
+    let response = with_auth(Request::post(&format!("/api/cameras/{}/refresh-info", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Camera>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can refresh camera info".to_string())
+            } else if response.status() == 502 {
+                Err("The camera did not answer ONVIF requests".to_string())
+            } else {
+                Err("Failed to refresh camera info".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn discover_cameras() -> Result<Vec<DiscoveredDevice>, String> {
+    let response = with_auth(Request::get("/api/cameras/discover"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<DiscoveredDevice>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can discover cameras".to_string())
+            } else {
+                Err("Camera discovery failed".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn import_cameras(devices: &[DiscoveredDevice]) -> Result<Vec<Camera>, String> {
+    let response = with_auth(Request::post("/api/cameras/import"))
+        .json(&devices)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<Vec<Camera>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can import cameras".to_string())
+            } else {
+                Err("Failed to import cameras".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// `from`/`to` are inclusive YYYY-MM-DD dates; empty means unbounded
+pub async fn get_camera_status_history(id: &str, from: &str, to: &str) -> Result<StatusHistory, String> {
+    let response = with_auth(Request::get(&format!("/api/cameras/{}/status-history", id)))
+        .query([("from", from), ("to", to)])
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<StatusHistory>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get camera status history".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Activity Log Service methods
+pub async fn get_logs() -> Result<Vec<ActivityLog>, String> {
+    let response = with_auth(Request::get("/api/logs"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<ActivityLog>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                // Only admins see the activity log
+                Ok(Vec::new())
+            } else {
+                Err("Failed to get logs".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Report Service methods
+pub async fn get_reports() -> Result<Vec<Report>, String> {
+    let response = with_auth(Request::get("/api/reports"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<Report>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get reports".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_report(id: &str) -> Result<Report, String> {
+    let response = with_auth(Request::get(&format!("/api/reports/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Report>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Report not found".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_report(request: &CreateReportRequest) -> Result<CreateReportResponse, String> {
+    let response = with_auth(Request::post("/api/reports"))
+        .json(request)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 202 {
+                match response.json::<CreateReportResponse>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 503 {
+                Err("Report queue is full, try again later".to_string())
+            } else {
+                Err("Failed to create report".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn cancel_report(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::post(&format!("/api/reports/{}/cancel", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 202 {
+                Ok(())
+            } else {
+                Err("Failed to cancel report".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_report(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/reports/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to delete report".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_report_storage() -> Result<ReportStorage, String> {
+    let response = with_auth(Request::get("/api/reports/storage"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<ReportStorage>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get report storage usage".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Report Schedule Service methods
+pub async fn get_report_schedules() -> Result<Vec<ReportSchedule>, String> {
+    let response = with_auth(Request::get("/api/report-schedules"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<ReportSchedule>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get report schedules".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_report_schedule(schedule: &ReportSchedule) -> Result<ReportSchedule, String> {
+    let response = with_auth(Request::post("/api/report-schedules"))
+        .json(schedule)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<ReportSchedule>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 400 {
+                Err("Invalid schedule expression".to_string())
+            } else {
+                Err("Failed to create report schedule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn update_report_schedule(id: &str, schedule: &ReportSchedule) -> Result<ReportSchedule, String> {
+    let response = with_auth(Request::put(&format!("/api/report-schedules/{}", id)))
+        .json(schedule)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<ReportSchedule>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to update report schedule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_report_schedule(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/report-schedules/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to delete report schedule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Live View layout Service methods
+pub async fn get_live_layouts() -> Result<Vec<LiveLayout>, String> {
+    let response = with_auth(Request::get("/api/layouts"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<LiveLayout>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get layouts".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_live_layout(layout: &LiveLayout) -> Result<LiveLayout, String> {
+    let response = with_auth(Request::post("/api/layouts"))
+        .json(layout)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<LiveLayout>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 400 {
+                Err("Invalid layout".to_string())
+            } else {
+                Err("Failed to save layout".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn update_live_layout(id: &str, layout: &LiveLayout) -> Result<LiveLayout, String> {
+    let response = with_auth(Request::put(&format!("/api/layouts/{}", id)))
+        .json(layout)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<LiveLayout>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to update layout".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_live_layout(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/layouts/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to delete layout".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Site Service methods
+pub async fn get_sites() -> Result<Vec<Site>, String> {
+    let response = with_auth(Request::get("/api/sites"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<Site>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get sites".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_site(site: &Site) -> Result<Site, String> {
+    let response = with_auth(Request::post("/api/sites"))
+        .json(site)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<Site>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can create sites".to_string())
+            } else {
+                Err("Failed to create site".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_site(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/sites/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 409 {
+                Err("Delete the site's zones first".to_string())
+            } else if response.status() == 403 {
+                Err("Only administrators can delete sites".to_string())
+            } else {
+                Err("Failed to delete site".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_sites_summary() -> Result<SitesSummary, String> {
+    let response = with_auth(Request::get("/api/sites/summary"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<SitesSummary>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get site summary".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Zone Service methods
+pub async fn get_zones() -> Result<Vec<Zone>, String> {
+    let response = with_auth(Request::get("/api/zones"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<Zone>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get zones".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_zone(zone: &Zone) -> Result<Zone, String> {
+    let response = with_auth(Request::post("/api/zones"))
+        .json(zone)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<Zone>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can create zones".to_string())
+            } else {
+                Err("Failed to create zone".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_zone(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/zones/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 403 {
+                Err("Only administrators can delete zones".to_string())
+            } else {
+                Err("Failed to delete zone".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Camera permission Service methods
+pub async fn get_camera_grants(subject: &GrantSubject) -> Result<Vec<CameraGrant>, String> {
+    let query = match subject {
+        GrantSubject::User(id) => format!("user_id={}", id),
+        GrantSubject::Group(id) => format!("group_id={}", id),
+    };
+    let response = with_auth(Request::get(&format!("/api/permissions?{}", query)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<CameraGrant>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can manage permissions".to_string())
+            } else {
+                Err("Failed to get permissions".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_camera_grant(grant: &CameraGrant) -> Result<CameraGrant, String> {
+    let response = with_auth(Request::post("/api/permissions"))
+        .json(grant)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<CameraGrant>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can manage permissions".to_string())
+            } else {
+                Err("Failed to grant access".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_camera_grant(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/permissions/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to revoke access".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn get_effective_permissions(user_id: &str) -> Result<EffectivePermissions, String> {
+    let response = with_auth(Request::get(&format!("/api/users/{}/permissions", user_id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<EffectivePermissions>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get effective permissions".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// User group Service methods
+pub async fn get_user_groups() -> Result<Vec<UserGroup>, String> {
+    let response = with_auth(Request::get("/api/groups"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<UserGroup>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can manage groups".to_string())
+            } else {
+                Err("Failed to get groups".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_user_group(group: &UserGroup) -> Result<UserGroup, String> {
+    let response = with_auth(Request::post("/api/groups"))
+        .json(group)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<UserGroup>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("You can't create a group with a role above your own".to_string())
+            } else {
+                Err("Failed to create group".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_user_group(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/groups/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to delete group".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn add_group_member(group_id: &str, user_id: &str) -> Result<UserGroup, String> {
+    let response = with_auth(Request::put(&format!("/api/groups/{}/members/{}", group_id, user_id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<UserGroup>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("You can't add members to a group with a role above your own".to_string())
+            } else {
+                Err("Failed to add member".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn remove_group_member(group_id: &str, user_id: &str) -> Result<UserGroup, String> {
+    let response = with_auth(Request::delete(&format!("/api/groups/{}/members/{}", group_id, user_id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<UserGroup>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to remove member".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Maintenance window Service methods
+pub async fn get_upcoming_maintenance() -> Result<Vec<MaintenanceWindow>, String> {
+    let response = with_auth(Request::get("/api/maintenance?upcoming=true"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<MaintenanceWindow>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get maintenance windows".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_maintenance_window(window: &MaintenanceWindow) -> Result<MaintenanceWindow, String> {
+    let response = with_auth(Request::post("/api/maintenance"))
+        .json(window)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<MaintenanceWindow>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 400 {
+                Err("The window needs a reason and must end after it starts, in the future".to_string())
+            } else if response.status() == 403 {
+                Err("Only administrators can schedule maintenance".to_string())
+            } else {
+                Err("Failed to schedule maintenance".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_maintenance_window(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/maintenance/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to cancel maintenance".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Alert Service methods
+pub async fn get_alerts() -> Result<Vec<Alert>, String> {
+    let response = with_auth(Request::get("/api/alerts"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<Alert>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get alerts".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Shared by acknowledge and resolve, which only differ in the action path
+async fn update_alert_state(id: &str, action: &str) -> Result<Alert, String> {
+    let response = with_auth(Request::post(&format!("/api/alerts/{}/{}", id, action)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Alert>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can resolve alerts".to_string())
+            } else if response.status() == 409 {
+                Err("The alert has already been resolved".to_string())
+            } else {
+                Err("Failed to update alert".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn acknowledge_alert(id: &str) -> Result<Alert, String> {
+    update_alert_state(id, "acknowledge").await
+}
+
+pub async fn resolve_alert(id: &str) -> Result<Alert, String> {
+    update_alert_state(id, "resolve").await
+}
+
+pub async fn get_alert_rules() -> Result<Vec<AlertRule>, String> {
+    let response = with_auth(Request::get("/api/alert-rules"))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Vec<AlertRule>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can manage alert rules".to_string())
+            } else {
+                Err("Failed to get alert rules".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn create_alert_rule(rule: &AlertRule) -> Result<AlertRule, String> {
+    let response = with_auth(Request::post("/api/alert-rules"))
+        .json(rule)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 201 {
+                match response.json::<AlertRule>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 400 {
+                Err("The rule needs a name and thresholds above zero".to_string())
+            } else {
+                Err("Failed to create alert rule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn update_alert_rule(rule: &AlertRule) -> Result<AlertRule, String> {
+    let response = with_auth(Request::put(&format!("/api/alert-rules/{}", rule.id)))
+        .json(rule)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<AlertRule>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to update alert rule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn delete_alert_rule(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/alert-rules/{}", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else {
+                Err("Failed to delete alert rule".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Settings Service methods
+pub async fn get_settings() -> Result<Settings, String> {
+    let response = Request::get("/api/settings")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                match response.json::<Settings>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else {
+                Err("Failed to get settings".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn update_settings(settings: &Settings) -> Result<(), String> {
+    let response = with_auth(Request::put("/api/settings"))
+        .json(settings)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
 
-// Report Models
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Report {
-    pub id: String,
-    pub name: String,
-    pub type_: ReportType,
-    pub created_at: String,
-    pub created_by: String,
-    pub period: String,
-    pub format: ReportFormat,
-    pub url: String,
+    match response {
+        Ok(response) => {
+            if response.status() == 200 {
+                Ok(())
+            } else if response.status() == 403 {
+                Err("Only administrators can change settings".to_string())
+            } else {
+                Err("Failed to update settings".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub enum ReportType {
-    UsageSummary,
-    CameraStatus,
-    UserActivity,
-}
+// Helper function to fetch data and update state
+pub fn fetch_data<T, F, S>(
+    fetch_fn: F,
+    state: UseStateHandle<Option<T>>,
+    error_state: UseStateHandle<Option<String>>,
+) where
+    F: FnOnce() -> S + 'static,
+    S: std::future::Future<Output = Result<T, String>> + 'static,
+    T: Clone + 'static,
+{
+    let state = state.clone();
+    let error_state = error_state.clone();
+    
+    // Clear previous error
+    error_state.set(None);
+    
+    spawn_local(async move {
+        match fetch_fn().await {
+            Ok(data) => {
+                state.set(Some(data));
+            }
+            Err(err) => {
+                error_state.set(Some(err));
+            }
+        }
+    });
+} 
+async fn send_ptz_command(id: &str, command: &PtzCommand) -> Result<Response, PtzError> {
+    let response = with_auth(Request::post(&format!("/api/cameras/{}/ptz", id)))
+        .json(command)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await
+        .map_err(|err| PtzError::Failed(format!("Request failed: {}", err)))?;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub enum ReportFormat {
-    PDF,
-    CSV,
+    match response.status() {
+        200 | 204 => Ok(response),
+        401 => Err(PtzError::NotSignedIn),
+        403 => Err(PtzError::Forbidden),
+        400 => Err(PtzError::Failed("Invalid PTZ command".to_string())),
+        502 => Err(PtzError::Failed("The camera did not accept the PTZ command".to_string())),
+        _ => Err(PtzError::Failed("PTZ command failed".to_string())),
+    }
 }
 
-// Settings Model
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Settings {
-    pub registered_to: String,
-    pub server_status: bool,
-    pub api_url: String,
-    pub license_expiry: String,
-    pub theme: String,
-    pub email_alerts: bool,
-    pub sms_alerts: bool,
-    pub refresh_interval: u32,
-    pub app_version: String,
+pub async fn ptz_command(id: &str, command: &PtzCommand) -> Result<(), PtzError> {
+    send_ptz_command(id, command).await.map(|_| ())
 }
 
-// Authentication types
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LoginRequest {
-    pub username: String,
-    pub password: String,
+pub async fn get_ptz_presets(id: &str) -> Result<Vec<PtzPreset>, PtzError> {
+    let response = send_ptz_command(id, &PtzCommand::ListPresets).await?;
+    response
+        .json::<Vec<PtzPreset>>()
+        .await
+        .map_err(|err| PtzError::Failed(format!("Failed to parse response: {}", err)))
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LoginResponse {
-    pub token: String,
-    pub user: User,
+pub async fn set_ptz_preset(id: &str, name: &str) -> Result<String, PtzError> {
+    let command = PtzCommand::SetPreset { name: name.to_string(), token: None };
+    let response = send_ptz_command(id, &command).await?;
+    response
+        .json::<PresetSavedResponse>()
+        .await
+        .map(|saved| saved.token)
+        .map_err(|err| PtzError::Failed(format!("Failed to parse response: {}", err)))
 }
 
-// API Service methods
-pub async fn login(username: &str, password: &str) -> Result<LoginResponse, String> {
-    let request = LoginRequest {
-        username: username.to_string(),
-        password: password.to_string(),
-    };
-
-    let response = Request::post("/api/auth/login")
+pub async fn send_test_email(to: &str) -> Result<(), String> {
+    let request = TestEmailRequest { to: to.to_string() };
+    let response = with_auth(Request::post("/api/settings/test-email"))
         .json(&request)
         .expect("Failed to serialize JSON")
         .send()
@@ -128,84 +2216,124 @@ pub async fn login(username: &str, password: &str) -> Result<LoginResponse, Stri
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<LoginResponse>().await {
-                    Ok(data) => Ok(data),
+                Ok(())
+            } else if response.status() == 502 {
+                // The server tried and the mail server refused or was unreachable
+                match response.json::<TestEmailResponse>().await {
+                    Ok(data) => Err(data.error.unwrap_or_else(|| "Failed to send test email".to_string())),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 403 {
+                Err("Only administrators can send test emails".to_string())
             } else {
-                Err("Invalid credentials".to_string())
+                Err("Failed to send test email".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// User Service methods
-pub async fn get_users() -> Result<Vec<User>, String> {
-    let response = Request::get("/api/users")
+pub async fn get_outbox() -> Result<Vec<OutboxEmail>, String> {
+    let response = with_auth(Request::get("/api/outbox"))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Vec<User>>().await {
+                match response.json::<Vec<OutboxEmail>>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 403 {
+                Err("Only administrators can view the outbox".to_string())
             } else {
-                Err("Failed to get users".to_string())
+                Err("Failed to get outbox".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn get_user(id: &str) -> Result<User, String> {
-    let response = Request::get(&format!("/api/users/{}", id))
+pub async fn retry_outbox_email(id: &str) -> Result<OutboxEmail, String> {
+    let response = with_auth(Request::post(&format!("/api/outbox/{}/retry", id)))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<User>().await {
+                match response.json::<OutboxEmail>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 404 {
+                Err("Email not found".to_string())
             } else {
-                Err("User not found".to_string())
+                Err("Failed to retry email".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn create_user(user: &User) -> Result<User, String> {
-    let response = Request::post("/api/users")
-        .json(user)
+pub async fn request_password_reset(email: &str) -> Result<(), String> {
+    let request = PasswordResetRequest { email: email.to_string() };
+    let response = Request::post("/api/auth/password-reset")
+        .json(&request)
         .expect("Failed to serialize JSON")
         .send()
         .await;
 
     match response {
         Ok(response) => {
-            if response.status() == 200 {
-                match response.json::<User>().await {
-                    Ok(data) => Ok(data),
-                    Err(err) => Err(format!("Failed to parse response: {}", err)),
-                }
+            if response.status() == 202 {
+                Ok(())
             } else {
-                Err("Failed to create user".to_string())
+                Err("Failed to request a password reset".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn update_user(id: &str, user: &User) -> Result<User, String> {
-    let response = Request::put(&format!("/api/users/{}", id))
-        .json(user)
+// The token from an emailed reset link (/?reset_token=...), if the page was opened with one
+pub fn reset_token_from_url() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("reset_token="))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+}
+
+pub async fn confirm_password_reset(token: &str, password: &str) -> Result<(), String> {
+    let request = PasswordResetConfirm { token: token.to_string(), password: password.to_string() };
+    let response = Request::post("/api/auth/password-reset/confirm")
+        .json(&request)
+        .expect("Failed to serialize JSON")
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 400 {
+                Err("This reset link is invalid, expired or already used, or the password is too short".to_string())
+            } else {
+                Err("Failed to reset the password".to_string())
+            }
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+pub async fn send_test_sms(to: &str) -> Result<(), String> {
+    let request = TestSmsRequest { to: to.to_string() };
+    let response = with_auth(Request::post("/api/settings/test-sms"))
+        .json(&request)
         .expect("Failed to serialize JSON")
         .send()
         .await;
@@ -213,80 +2341,94 @@ pub async fn update_user(id: &str, user: &User) -> Result<User, String> {
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<User>().await {
-                    Ok(data) => Ok(data),
+                Ok(())
+            } else if response.status() == 502 {
+                match response.json::<TestSmsResponse>().await {
+                    Ok(data) => Err(data.error.unwrap_or_else(|| "Failed to send test SMS".to_string())),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 400 {
+                Err("Phone numbers need the international format, like +15550100".to_string())
+            } else if response.status() == 403 {
+                Err("Only administrators can send test messages".to_string())
             } else {
-                Err("Failed to update user".to_string())
+                Err("Failed to send test SMS".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn delete_user(id: &str) -> Result<(), String> {
-    let response = Request::delete(&format!("/api/users/{}", id))
+pub async fn get_sms_messages() -> Result<Vec<SmsMessage>, String> {
+    let response = with_auth(Request::get("/api/sms"))
         .send()
         .await;
 
     match response {
         Ok(response) => {
-            if response.status() == 204 {
-                Ok(())
+            if response.status() == 200 {
+                match response.json::<Vec<SmsMessage>>().await {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can view sent messages".to_string())
             } else {
-                Err("Failed to delete user".to_string())
+                Err("Failed to get SMS messages".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Camera Service methods
-pub async fn get_cameras() -> Result<Vec<Camera>, String> {
-    let response = Request::get("/api/cameras")
+pub async fn retry_sms_message(id: &str) -> Result<SmsMessage, String> {
+    let response = with_auth(Request::post(&format!("/api/sms/{}/retry", id)))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Vec<Camera>>().await {
+                match response.json::<SmsMessage>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 404 {
+                Err("Message not found".to_string())
             } else {
-                Err("Failed to get cameras".to_string())
+                Err("Failed to retry message".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn get_camera(id: &str) -> Result<Camera, String> {
-    let response = Request::get(&format!("/api/cameras/{}", id))
+pub async fn get_webhooks() -> Result<Vec<WebhookSubscription>, String> {
+    let response = with_auth(Request::get("/api/webhooks"))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Camera>().await {
+                match response.json::<Vec<WebhookSubscription>>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 403 {
+                Err("Only administrators can manage webhooks".to_string())
             } else {
-                Err("Camera not found".to_string())
+                Err("Failed to get webhooks".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Create a new camera
-pub async fn create_camera(camera: &Camera) -> Result<Camera, String> {
-    let response = Request::post("/api/cameras")
-        .json(camera)
+// The created subscription comes back with its secret, which is never shown again
+pub async fn create_webhook(webhook: &WebhookSubscription) -> Result<WebhookSubscription, String> {
+    let response = with_auth(Request::post("/api/webhooks"))
+        .json(webhook)
         .expect("Failed to serialize JSON")
         .send()
         .await;
@@ -294,22 +2436,23 @@ pub async fn create_camera(camera: &Camera) -> Result<Camera, String> {
     match response {
         Ok(response) => {
             if response.status() == 201 {
-                match response.json::<Camera>().await {
+                match response.json::<WebhookSubscription>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 400 {
+                Err("A webhook needs a name, an http(s) URL and at least one event".to_string())
             } else {
-                Err("Failed to create camera".to_string())
+                Err("Failed to create webhook".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Update an existing camera
-pub async fn update_camera(id: &str, camera: &Camera) -> Result<Camera, String> {
-    let response = Request::put(&format!("/api/cameras/{}", id))
-        .json(camera)
+pub async fn update_webhook(webhook: &WebhookSubscription) -> Result<WebhookSubscription, String> {
+    let response = with_auth(Request::put(&format!("/api/webhooks/{}", webhook.id)))
+        .json(webhook)
         .expect("Failed to serialize JSON")
         .send()
         .await;
@@ -317,21 +2460,22 @@ pub async fn update_camera(id: &str, camera: &Camera) -> Result<Camera, String>
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Camera>().await {
+                match response.json::<WebhookSubscription>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 400 {
+                Err("A webhook needs a name, an http(s) URL and at least one event".to_string())
             } else {
-                Err("Failed to update camera".to_string())
+                Err("Failed to update webhook".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Delete a camera
-pub async fn delete_camera(id: &str) -> Result<(), String> {
-    let response = Request::delete(&format!("/api/cameras/{}", id))
+pub async fn delete_webhook(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/webhooks/{}", id)))
         .send()
         .await;
 
@@ -340,139 +2484,243 @@ pub async fn delete_camera(id: &str) -> Result<(), String> {
             if response.status() == 204 {
                 Ok(())
             } else {
-                Err("Failed to delete camera".to_string())
+                Err("Failed to delete webhook".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Activity Log Service methods
-pub async fn get_logs() -> Result<Vec<ActivityLog>, String> {
-    let response = Request::get("/api/logs")
+pub async fn get_webhook_deliveries(id: &str) -> Result<Vec<WebhookDelivery>, String> {
+    let response = with_auth(Request::get(&format!("/api/webhooks/{}/deliveries", id)))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Vec<ActivityLog>>().await {
+                match response.json::<Vec<WebhookDelivery>>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
             } else {
-                Err("Failed to get logs".to_string())
+                Err("Failed to get webhook deliveries".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Report Service methods
-pub async fn get_reports() -> Result<Vec<Report>, String> {
-    let response = Request::get("/api/reports")
+pub async fn get_webhook_dead_letters() -> Result<Vec<WebhookDelivery>, String> {
+    let response = with_auth(Request::get("/api/webhooks/dead-letters"))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Vec<Report>>().await {
+                match response.json::<Vec<WebhookDelivery>>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
             } else {
-                Err("Failed to get reports".to_string())
+                Err("Failed to get dead-lettered deliveries".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn get_report(id: &str) -> Result<Report, String> {
-    let response = Request::get(&format!("/api/reports/{}", id))
+pub async fn retry_webhook_delivery(id: &str) -> Result<WebhookDelivery, String> {
+    let response = with_auth(Request::post(&format!("/api/webhooks/deliveries/{}/retry", id)))
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Report>().await {
+                match response.json::<WebhookDelivery>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 404 {
+                Err("Delivery not found".to_string())
             } else {
-                Err("Report not found".to_string())
+                Err("Failed to retry delivery".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Settings Service methods
-pub async fn get_settings() -> Result<Settings, String> {
-    let response = Request::get("/api/settings")
+// Camera Event Service methods
+// `camera_id` and `event_type` are empty for any; `from`/`to` are inclusive
+// YYYY-MM-DD dates, empty for unbounded
+pub async fn get_events(camera_id: &str, event_type: &str, from: &str, to: &str) -> Result<Vec<CameraEvent>, String> {
+    let from = (!from.is_empty()).then(|| format!("{}T00:00:00Z", from));
+    let to = (!to.is_empty()).then(|| format!("{}T23:59:59Z", to));
+    let params: Vec<(&str, &str)> = [
+        ("camera_id", Some(camera_id)),
+        ("type", Some(event_type)),
+        ("from", from.as_deref()),
+        ("to", to.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.filter(|v| !v.is_empty()).map(|v| (name, v)))
+    .collect();
+
+    let response = with_auth(Request::get("/api/events"))
+        .query(params)
         .send()
         .await;
 
     match response {
         Ok(response) => {
             if response.status() == 200 {
-                match response.json::<Settings>().await {
+                match response.json::<Vec<CameraEvent>>().await {
                     Ok(data) => Ok(data),
                     Err(err) => Err(format!("Failed to parse response: {}", err)),
                 }
+            } else if response.status() == 400 {
+                Err("Invalid event filter".to_string())
             } else {
-                Err("Failed to get settings".to_string())
+                Err("Failed to get camera events".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-pub async fn update_settings(settings: &Settings) -> Result<(), String> {
-    let response = Request::put("/api/settings")
-        .json(settings)
-        .expect("Failed to serialize JSON")
+// Returns the new token; it is only ever shown this once
+pub async fn issue_event_token(id: &str) -> Result<String, String> {
+    let response = with_auth(Request::post(&format!("/api/cameras/{}/event-token", id)))
         .send()
         .await;
 
     match response {
         Ok(response) => {
-            if response.status() == 200 {
-                Ok(())
+            if response.status() == 201 {
+                match response.json::<EventToken>().await {
+                    Ok(data) => Ok(data.token),
+                    Err(err) => Err(format!("Failed to parse response: {}", err)),
+                }
+            } else if response.status() == 403 {
+                Err("Only administrators can issue event tokens".to_string())
             } else {
-                Err("Failed to update settings".to_string())
+                Err("Failed to issue event token".to_string())
             }
         }
         Err(err) => Err(format!("Request failed: {}", err)),
     }
 }
 
-// Helper function to fetch data and update state
-pub fn fetch_data<T, F, S>(
-    fetch_fn: F,
-    state: UseStateHandle<Option<T>>,
-    error_state: UseStateHandle<Option<String>>,
-) where
-    F: FnOnce() -> S + 'static,
-    S: std::future::Future<Output = Result<T, String>> + 'static,
-    T: Clone + 'static,
-{
-    let state = state.clone();
-    let error_state = error_state.clone();
-    
-    // Clear previous error
-    error_state.set(None);
-    
-    spawn_local(async move {
-        match fetch_fn().await {
-            Ok(data) => {
-                state.set(Some(data));
+pub async fn revoke_event_token(id: &str) -> Result<(), String> {
+    let response = with_auth(Request::delete(&format!("/api/cameras/{}/event-token", id)))
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            if response.status() == 204 {
+                Ok(())
+            } else if response.status() == 403 {
+                Err("Only administrators can revoke event tokens".to_string())
+            } else {
+                Err("Failed to revoke event token".to_string())
             }
-            Err(err) => {
-                error_state.set(Some(err));
+        }
+        Err(err) => Err(format!("Request failed: {}", err)),
+    }
+}
+
+// Live change feed
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "entity", content = "data", rename_all = "snake_case")]
+pub enum FeedEntity {
+    User(User),
+    Camera(Camera),
+    ActivityLog(ActivityLog),
+    Report(Report),
+    Settings(Settings),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedMessage {
+    Change {
+        action: ChangeAction,
+        #[serde(flatten)]
+        entity: FeedEntity,
+    },
+    CameraStatus {
+        camera_id: String,
+        status: CameraStatus,
+        timestamp: String,
+    },
+    // Changes were missed, so everything should be fetched again
+    Resync,
+}
+
+// Wait after a dropped connection, doubled up to the maximum while it keeps failing
+const FEED_RETRY_MS: u32 = 1_000;
+const FEED_MAX_RETRY_MS: u32 = 30_000;
+
+fn change_feed_url() -> String {
+    let location = web_sys::window().expect("no window").location();
+    let scheme = if location.protocol().ok().as_deref() == Some("https:") { "wss" } else { "ws" };
+    format!("{}://{}{}", scheme, location.host().unwrap_or_default(), authorized_url("/api/ws"))
+}
+
+// Pass every change feed message to `on_message` until `stop` fires or is
+// dropped. Dropped connections are reopened, followed by a Resync since
+// changes may have been missed in between.
+pub async fn follow_changes(on_message: Callback<FeedMessage>, mut stop: oneshot::Receiver<()>) {
+    let mut retry_ms = FEED_RETRY_MS;
+    let mut reconnecting = false;
+
+    loop {
+        match WebSocket::open(&change_feed_url()) {
+            Ok(mut socket) => {
+                if reconnecting {
+                    on_message.emit(FeedMessage::Resync);
+                }
+                loop {
+                    match future::select(socket.next(), &mut stop).await {
+                        Either::Left((Some(Ok(Message::Text(text))), _)) => {
+                            retry_ms = FEED_RETRY_MS;
+                            match serde_json::from_str::<FeedMessage>(&text) {
+                                Ok(message) => on_message.emit(message),
+                                Err(e) => log::warn!("Ignoring change feed message: {}", e),
+                            }
+                        }
+                        Either::Left((Some(Ok(Message::Bytes(_))), _)) => {}
+                        Either::Left((Some(Err(e)), _)) => {
+                            log::warn!("Change feed connection lost: {}", e);
+                            break;
+                        }
+                        Either::Left((None, _)) => break,
+                        Either::Right(_) => {
+                            let _ = socket.close(None, None);
+                            return;
+                        }
+                    }
+                }
             }
+            Err(e) => log::error!("Failed to open change feed: {}", e),
         }
-    });
-} 
\ No newline at end of file
+
+        reconnecting = true;
+        if let Either::Right(_) = future::select(TimeoutFuture::new(retry_ms), &mut stop).await {
+            return;
+        }
+        retry_ms = (retry_ms * 2).min(FEED_MAX_RETRY_MS);
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::email;
+use crate::mock_data::{
+    ActivityLog, Alert, AlertCondition, AlertRule, CameraStatus, User, UserRole, WebhookEvent,
+    get_alert_rules, get_cameras, get_settings, get_users, offline_since, failed_logins_since,
+    raise_alert, resolve_cleared_alerts, add_activity_log,
+    next_id, now_timestamp, parse_timestamp,
+};
+use crate::permissions::{camera_access, effective_role};
+use crate::sms;
+use crate::webhooks;
+
+// How often the rules are evaluated
+const TICK_SECONDS: u64 = 30;
+
+// One subject a rule's condition currently holds for
+struct Firing {
+    subject: String,
+    camera_id: Option<String>,
+    message: String,
+}
+
+// Spawn the background task that evaluates alert rules
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            evaluate(Utc::now());
+        }
+    });
+}
+
+fn minutes_between(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    (to - from).num_minutes()
+}
+
+// Cameras in maintenance aren't offline as far as their status goes, so
+// maintenance windows keep them out of offline alerts
+fn offline_cameras(minutes: u32, now: DateTime<Utc>) -> Vec<Firing> {
+    get_cameras()
+        .into_iter()
+        .filter(|camera| camera.active && camera.status == CameraStatus::Offline)
+        .filter_map(|camera| {
+            let since = offline_since(&camera.id)
+                .as_deref()
+                .and_then(parse_timestamp)
+                .or_else(|| parse_timestamp(&camera.last_update))?;
+            let offline_for = minutes_between(since, now);
+            (offline_for >= i64::from(minutes)).then(|| Firing {
+                subject: format!("camera:{}", camera.id),
+                camera_id: Some(camera.id.clone()),
+                message: format!("{} has been offline for {} minutes", camera.name, offline_for),
+            })
+        })
+        .collect()
+}
+
+fn failed_logins(count: u32, minutes: u32, now: DateTime<Utc>) -> Vec<Firing> {
+    let attempts = failed_logins_since(now - chrono::Duration::minutes(i64::from(minutes)));
+    if attempts.len() <= count as usize {
+        return Vec::new();
+    }
+
+    let mut usernames: Vec<String> = attempts.into_iter().map(|attempt| attempt.username).collect();
+    let total = usernames.len();
+    usernames.sort();
+    usernames.dedup();
+    vec![Firing {
+        subject: "failed-logins".to_string(),
+        camera_id: None,
+        message: format!(
+            "{} failed sign-ins in the last {} minutes (usernames: {})",
+            total, minutes, usernames.join(", ")
+        ),
+    }]
+}
+
+fn license_expiring(days: u32, now: DateTime<Utc>) -> Vec<Firing> {
+    let expiry = get_settings().license_expiry;
+    let Ok(date) = NaiveDate::parse_from_str(&expiry, "%Y-%m-%d") else {
+        return Vec::new();
+    };
+
+    let days_left = (date - now.date_naive()).num_days();
+    if days_left > i64::from(days) {
+        return Vec::new();
+    }
+    let message = if days_left < 0 {
+        format!("The license expired on {}", expiry)
+    } else {
+        format!("The license expires on {}, in {} days", expiry, days_left)
+    };
+    vec![Firing { subject: "license".to_string(), camera_id: None, message }]
+}
+
+fn firing(condition: &AlertCondition, now: DateTime<Utc>) -> Vec<Firing> {
+    match condition {
+        AlertCondition::CameraOffline { minutes } => offline_cameras(*minutes, now),
+        AlertCondition::FailedLogins { count, minutes } => failed_logins(*count, *minutes, now),
+        AlertCondition::LicenseExpiring { days } => license_expiring(*days, now),
+    }
+}
+
+// Thresholds of zero would fire on everything, so each needs to be positive
+pub fn valid_rule(rule: &AlertRule) -> bool {
+    let condition_valid = match rule.condition {
+        AlertCondition::CameraOffline { minutes } => minutes > 0,
+        AlertCondition::FailedLogins { minutes, .. } => minutes > 0,
+        AlertCondition::LicenseExpiring { days } => days > 0,
+    };
+    condition_valid && !rule.name.trim().is_empty()
+}
+
+// Active users who would see the alert in the app, and so get notified of it:
+// admins for everything, viewers only for cameras they have access to
+pub fn audience(alert: &Alert) -> Vec<User> {
+    get_users()
+        .into_iter()
+        .filter(|user| user.active)
+        .filter(|user| {
+            effective_role(user) != UserRole::Viewer
+                || alert.camera_id.as_deref().is_some_and(|id| camera_access(user).allows(id))
+        })
+        .collect()
+}
+
+fn log(action: &str, alert: &Alert) {
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: "system".to_string(),
+        action: action.to_string(),
+        target: alert.id.clone(),
+        details: format!("{:?}: {}", alert.severity, alert.message),
+    });
+}
+
+// Run every rule once. Conditions that hold raise or refresh an alert, and
+// alerts whose condition has cleared are resolved. Returns the alerts raised.
+pub fn evaluate(now: DateTime<Utc>) -> Vec<Alert> {
+    let mut raised = Vec::new();
+
+    for rule in get_alert_rules() {
+        let firings = if rule.enabled { firing(&rule.condition, now) } else { Vec::new() };
+        let subjects: Vec<String> = firings.iter().map(|f| f.subject.clone()).collect();
+
+        for firing in firings {
+            if let Some(alert) = raise_alert(&rule, &firing.subject, firing.camera_id, firing.message) {
+                println!("Alert raised by rule '{}': {}", rule.name, alert.message);
+                log("ALERT_RAISED", &alert);
+                email::notify_alert(&alert, &rule);
+                sms::notify_alert(&alert, &rule);
+                webhooks::emit(WebhookEvent::AlertRaised, &alert);
+                raised.push(alert);
+            }
+        }
+        for alert in resolve_cleared_alerts(&rule.id, &subjects) {
+            log("ALERT_RESOLVED", &alert);
+        }
+    }
+
+    raised
+}
@@ -0,0 +1,345 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::mock_data::{
+    Camera, PasswordReset, User, UserRole,
+    get_camera, get_user, get_password_hash, set_password_hash, add_password_reset, take_password_reset,
+    add_session, get_session_user_id, remove_session, remove_user_sessions,
+};
+use crate::permissions::{self, CameraAccess};
+
+// What users sign in with until they set a password of their own
+const MOCK_PASSWORD: &str = "password";
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+// How long an emailed reset link works
+const RESET_LINK_MINUTES: i64 = 60;
+
+// Tokens are random and only their hash is kept, like reset tokens
+fn random_token() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Start a session for `user` and return the token the login endpoint hands out
+pub fn start_session(user: &User) -> String {
+    let token = random_token();
+    add_session(token_hash(&token), &user.id);
+    token
+}
+
+pub fn end_session(token: &str) -> bool {
+    remove_session(&token_hash(token))
+}
+
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Argon2 hashes any password")
+        .to_string()
+}
+
+pub fn password_matches(user: &User, password: &str) -> bool {
+    match get_password_hash(&user.id) {
+        Some(hash) => PasswordHash::new(&hash)
+            .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok()),
+        None => password == MOCK_PASSWORD,
+    }
+}
+
+// Start a password reset for `user` and return the token for the emailed link
+pub fn issue_password_reset(user: &User, now: DateTime<Utc>) -> String {
+    let token = random_token();
+    add_password_reset(PasswordReset {
+        token_hash: token_hash(&token),
+        user_id: user.id.clone(),
+        expires_at: now + chrono::Duration::minutes(RESET_LINK_MINUTES),
+    });
+    token
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ResetError {
+    // Unknown, already used or expired
+    InvalidToken,
+    TooShort,
+}
+
+// Set a new password with an emailed token. The token is used up, unless the
+// password is refused before it is looked at. Returns the user.
+pub fn reset_password(token: &str, password: &str, now: DateTime<Utc>) -> Result<User, ResetError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ResetError::TooShort);
+    }
+    let reset = take_password_reset(&token_hash(token))
+        .filter(|reset| reset.expires_at > now)
+        .ok_or(ResetError::InvalidToken)?;
+    let user = get_user(&reset.user_id).filter(|user| user.active).ok_or(ResetError::InvalidToken)?;
+    set_password_hash(&user.id, hash_password(password));
+    // Whoever knew the old password shouldn't stay signed in
+    remove_user_sessions(&user.id);
+    Ok(user)
+}
+
+// The signed-in user, taken from the session token in an `Authorization: Bearer` header
+pub struct CurrentUser(pub User);
+
+impl CurrentUser {
+    // The user's own role or one inherited from a group, whichever is higher
+    pub fn role(&self) -> UserRole {
+        permissions::effective_role(&self.0)
+    }
+
+    // Admins and super admins may operate cameras; viewers only watch
+    pub fn require_admin(&self) -> Result<(), StatusCode> {
+        match self.role() {
+            UserRole::SuperAdmin | UserRole::Admin => Ok(()),
+            UserRole::Viewer => Err(StatusCode::FORBIDDEN),
+        }
+    }
+
+    pub fn camera_access(&self) -> CameraAccess {
+        permissions::camera_access(&self.0)
+    }
+
+    // A camera this user may see. Cameras outside their grants look the same
+    // as ones that don't exist.
+    pub fn camera(&self, id: &str) -> Result<Camera, StatusCode> {
+        get_camera(id)
+            .filter(|camera| self.camera_access().allows(&camera.id))
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
+fn bearer_token(parts: &Parts) -> Option<String> {
+    parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+fn query_token(parts: &Parts) -> Option<String> {
+    parts
+        .uri
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .map(str::to_string)
+}
+
+fn session_user(token: &str) -> Result<CurrentUser, StatusCode> {
+    let user_id = get_session_user_id(&token_hash(token)).ok_or(StatusCode::UNAUTHORIZED)?;
+    match get_user(&user_id) {
+        Some(user) if user.active => Ok(CurrentUser(user)),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CurrentUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        session_user(&bearer_token(parts).ok_or(StatusCode::UNAUTHORIZED)?)
+    }
+}
+
+// The signed-in user on routes browsers open without headers: img sources,
+// download links and WebSockets. These also take the token as a `token` query
+// parameter; other routes don't, so tokens stay out of access logs.
+pub struct UrlAuthorized(pub CurrentUser);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for UrlAuthorized {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).or_else(|| query_token(parts)).ok_or(StatusCode::UNAUTHORIZED)?;
+        session_user(&token).map(UrlAuthorized)
+    }
+}
+
+// The bearer token as sent, for handlers that check it themselves, such as
+// the one a camera pushes events with or a session being signed out.
+pub struct BearerToken(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for BearerToken {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        bearer_token(parts).map(BearerToken).ok_or(StatusCode::UNAUTHORIZED)
+    }
+}
+
+// Signing in from tests of other modules
+#[cfg(test)]
+pub mod testing {
+    use axum::{http::header, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    // A session token from the real login route
+    pub async fn login(username: &str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/api/auth/login", post(crate::login_handler))
+            .with_state(Arc::new(Mutex::new(())));
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/api/auth/login", address))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "username": username, "password": super::MOCK_PASSWORD }).to_string())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        body["token"].as_str().unwrap().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::{get, post}, Router};
+    use std::sync::{Arc, Mutex};
+
+    // Serve the real sign-in, session and reset routes, plus one that takes
+    // the token from the URL
+    fn server() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/api/auth/login", post(crate::login_handler))
+            .route("/api/auth/session", get(crate::get_session_handler))
+            .route("/api/auth/logout", post(crate::logout_handler))
+            .route("/api/auth/password-reset", post(crate::password_reset_handler))
+            .route("/api/auth/password-reset/confirm", post(crate::confirm_password_reset_handler))
+            .route("/api/auth/url", get(|UrlAuthorized(user): UrlAuthorized| async move { user.0.id }))
+            .with_state(Arc::new(Mutex::new(())));
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        format!("http://{}/api/auth", address)
+    }
+
+    async fn get_status(url: String, token: Option<&str>) -> u16 {
+        let mut request = reqwest::Client::new().get(url);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await.unwrap().status().as_u16()
+    }
+
+    fn user(id: &str) -> User {
+        crate::mock_data::create_user(User {
+            id: id.to_string(),
+            username: id.to_string(),
+            name: "Reset Test".to_string(),
+            email: format!("{}@example.com", id),
+            phone: String::new(),
+            role: UserRole::Viewer,
+            active: true,
+            last_login: String::new(),
+        })
+    }
+
+    async fn send(url: String, body: serde_json::Value) -> u16 {
+        reqwest::Client::new()
+            .post(url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    async fn login(url: &str, username: &str, password: &str) -> u16 {
+        send(format!("{}/login", url), serde_json::json!({ "username": username, "password": password })).await
+    }
+
+    #[tokio::test]
+    async fn emailed_token_sets_the_password_once() {
+        let url = server();
+        let user = user("reset-flow");
+
+        let status = send(format!("{}/password-reset", url), serde_json::json!({ "email": "reset-flow@example.com" })).await;
+        assert_eq!(status, 202);
+        let email = crate::mock_data::get_outbox().into_iter().find(|email| email.to == [user.email.clone()]).unwrap();
+        let token = email.body.split("reset_token=").nth(1).unwrap()[..32].to_string();
+
+        let confirm = |password: &str| serde_json::json!({ "token": token, "password": password });
+        assert_eq!(send(format!("{}/password-reset/confirm", url), confirm("short")).await, 400);
+        assert_eq!(send(format!("{}/password-reset/confirm", url), confirm("correct horse")).await, 204);
+        assert_eq!(send(format!("{}/password-reset/confirm", url), confirm("another one")).await, 400);
+
+        assert_eq!(login(&url, "reset-flow", "correct horse").await, 200);
+        assert_eq!(login(&url, "reset-flow", "password").await, 401);
+        assert_eq!(login(&url, "reset-flow", "another one").await, 401);
+    }
+
+    #[tokio::test]
+    async fn only_issued_session_tokens_are_accepted() {
+        let url = server();
+        let token = testing::login("asmith").await;
+
+        assert_eq!(get_status(format!("{}/session", url), Some(&token)).await, 200);
+        assert_eq!(get_status(format!("{}/session", url), Some("mock-token-1")).await, 401);
+        assert_eq!(get_status(format!("{}/session", url), None).await, 401);
+        // Only the routes browsers open without headers read the query
+        assert_eq!(get_status(format!("{}/session?token={}", url, token), None).await, 401);
+        assert_eq!(get_status(format!("{}/url?token={}", url, token), None).await, 200);
+        assert_eq!(get_status(format!("{}/url?token=mock-token-1", url), None).await, 401);
+    }
+
+    #[tokio::test]
+    async fn logout_and_password_reset_end_sessions() {
+        let url = server();
+        let user = user("reset-sessions");
+        let client = reqwest::Client::new();
+
+        let first = start_session(&user);
+        let second = start_session(&user);
+        let status = client.post(format!("{}/logout", url)).bearer_auth(&first).send().await.unwrap().status();
+        assert_eq!(status, 204);
+        assert_eq!(get_status(format!("{}/session", url), Some(&first)).await, 401);
+        assert_eq!(get_status(format!("{}/session", url), Some(&second)).await, 200);
+
+        let token = issue_password_reset(&user, Utc::now());
+        reset_password(&token, "long enough", Utc::now()).unwrap();
+        assert_eq!(get_status(format!("{}/session", url), Some(&second)).await, 401);
+    }
+
+    #[test]
+    fn tokens_expire_and_are_replaced() {
+        let user = user("reset-expiry");
+        let now = Utc::now();
+
+        let token = issue_password_reset(&user, now);
+        let later = now + chrono::Duration::minutes(RESET_LINK_MINUTES + 1);
+        assert_eq!(reset_password(&token, "long enough", later).map(|user| user.id), Err(ResetError::InvalidToken));
+
+        let first = issue_password_reset(&user, now);
+        let second = issue_password_reset(&user, now);
+        assert_eq!(reset_password(&first, "long enough", now).map(|user| user.id), Err(ResetError::InvalidToken));
+        assert_eq!(reset_password(&second, "long enough", now).map(|user| user.id), Ok(user.id));
+        assert_eq!(get_password_hash("reset-expiry").map(|hash| hash.starts_with("$argon2")), Some(true));
+    }
+}
@@ -0,0 +1,105 @@
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::mock_data::{Camera, format_timestamp, parse_timestamp};
+
+// Decoded size allowed for a snapshot pushed with an event
+pub const MAX_SNAPSHOT_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotError {
+    NotBase64,
+    TooLarge,
+    // Only JPEG and PNG images are kept
+    UnknownFormat,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    hex(&bytes)
+}
+
+// Only the hash is stored, so a leaked camera record doesn't leak the token
+pub fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+// Compares every byte so the time taken doesn't hint at how much matched
+pub fn token_matches(camera: &Camera, token: &str) -> bool {
+    let Some(expected) = &camera.event_token_hash else { return false };
+    let actual = hash_token(token);
+    expected.len() == actual.len()
+        && expected.bytes().zip(actual.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// The time the camera gave, in the server's format; now when it gave none.
+// None when it gave one that can't be read.
+pub fn event_time(timestamp: Option<&str>) -> Option<String> {
+    match timestamp {
+        Some(timestamp) => parse_timestamp(timestamp).map(format_timestamp),
+        None => Some(format_timestamp(chrono::Utc::now())),
+    }
+}
+
+// A base64 snapshot, decoded, with its content type
+pub fn decode_snapshot(encoded: &str) -> Result<(&'static str, Vec<u8>), SnapshotError> {
+    // Roughly 4 characters per 3 bytes; saves decoding something far too big
+    if encoded.len() > MAX_SNAPSHOT_BYTES / 3 * 4 + 4 {
+        return Err(SnapshotError::TooLarge);
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| SnapshotError::NotBase64)?;
+    if bytes.len() > MAX_SNAPSHOT_BYTES {
+        return Err(SnapshotError::TooLarge);
+    }
+
+    let content_type = if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else {
+        return Err(SnapshotError::UnknownFormat);
+    };
+    Ok((content_type, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_issued_token_matches() {
+        let token = generate_token();
+        let camera = Camera { event_token_hash: Some(hash_token(&token)), ..Camera::default() };
+
+        assert!(token_matches(&camera, &token));
+        assert!(!token_matches(&camera, &generate_token()));
+        assert!(!token_matches(&camera, ""));
+        assert!(!token_matches(&Camera::default(), &token));
+    }
+
+    #[test]
+    fn snapshots_must_be_small_jpeg_or_png() {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let jpeg = engine.encode([0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10]);
+        assert_eq!(decode_snapshot(&jpeg).unwrap().0, "image/jpeg");
+        assert_eq!(decode_snapshot(&engine.encode(b"GIF89a")), Err(SnapshotError::UnknownFormat));
+        assert_eq!(decode_snapshot("not base64!"), Err(SnapshotError::NotBase64));
+
+        let mut big = vec![0xFF, 0xD8, 0xFF];
+        big.resize(MAX_SNAPSHOT_BYTES + 1, 0);
+        assert_eq!(decode_snapshot(&engine.encode(big)), Err(SnapshotError::TooLarge));
+    }
+
+    #[test]
+    fn event_times_are_normalised_to_utc() {
+        assert_eq!(event_time(Some("2025-03-01T10:00:00+02:00")), Some("2025-03-01T08:00:00Z".to_string()));
+        assert_eq!(event_time(Some("yesterday")), None);
+        assert!(event_time(None).is_some());
+    }
+}
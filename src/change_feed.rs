@@ -0,0 +1,304 @@
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::Response,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashSet;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::mock_data::{
+    ActivityLog, Camera, CameraStatus, Report, Settings, User, UserRole,
+    get_cameras, get_user,
+};
+use crate::permissions::{camera_access, effective_role};
+
+// Messages a slow client may fall behind by before it is told to reload
+const BACKLOG: usize = 256;
+
+// The records the dashboard keeps in memory, with the value as the REST API returns it
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "entity", content = "data", rename_all = "snake_case")]
+pub enum Entity {
+    User(User),
+    Camera(Box<Camera>),
+    ActivityLog(ActivityLog),
+    Report(Report),
+    Settings(Box<Settings>),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+// What the server pushes over /api/ws
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedMessage {
+    // Deletes carry the record as it was just before it went
+    Change {
+        action: ChangeAction,
+        #[serde(flatten)]
+        entity: Entity,
+    },
+    CameraStatus {
+        camera_id: String,
+        previous_status: Option<CameraStatus>,
+        status: CameraStatus,
+        timestamp: String,
+    },
+    // Sent instead of the messages a client missed; it should fetch everything again
+    Resync,
+}
+
+static FEED: Lazy<broadcast::Sender<FeedMessage>> = Lazy::new(|| broadcast::channel(BACKLOG).0);
+
+pub fn publish(message: FeedMessage) {
+    // Sending only fails when nobody is connected
+    let _ = FEED.send(message);
+}
+
+pub fn created(entity: Entity) {
+    publish(FeedMessage::Change { action: ChangeAction::Created, entity });
+}
+
+pub fn updated(entity: Entity) {
+    publish(FeedMessage::Change { action: ChangeAction::Updated, entity });
+}
+
+pub fn deleted(entity: Entity) {
+    publish(FeedMessage::Change { action: ChangeAction::Deleted, entity });
+}
+
+// One connection's view of the feed. `cameras` holds the cameras the client
+// has been allowed to see, so it can be told when one goes out of reach.
+struct Viewer {
+    user: User,
+    cameras: HashSet<String>,
+}
+
+impl Viewer {
+    fn new(user: User) -> Self {
+        let access = camera_access(&user);
+        let cameras = access.filter(get_cameras()).into_iter().map(|camera| camera.id).collect();
+        Viewer { user, cameras }
+    }
+
+    fn is_admin(&self) -> bool {
+        effective_role(&self.user) != UserRole::Viewer
+    }
+
+    // The message as this user should get it, or None if it isn't theirs to see.
+    // Mirrors the read rules of the REST endpoints.
+    fn view(&mut self, message: FeedMessage) -> Option<FeedMessage> {
+        match &message {
+            FeedMessage::Change { action, entity: Entity::Camera(camera) } => {
+                let allowed = *action != ChangeAction::Deleted && camera_access(&self.user).allows(&camera.id);
+                if allowed {
+                    self.cameras.insert(camera.id.clone());
+                    Some(message)
+                } else if self.cameras.remove(&camera.id) {
+                    // Deleted, or moved outside the user's grants
+                    Some(FeedMessage::Change { action: ChangeAction::Deleted, entity: Entity::Camera(camera.clone()) })
+                } else {
+                    None
+                }
+            }
+            FeedMessage::Change { entity: Entity::Report(report), .. } => {
+                (self.is_admin() || report.created_by == self.user.id).then_some(message)
+            }
+            // Users carry contact details; only admins see other people's
+            FeedMessage::Change { entity: Entity::User(user), .. } => {
+                (self.is_admin() || user.id == self.user.id).then_some(message)
+            }
+            FeedMessage::Change { entity: Entity::ActivityLog(_), .. } => self.is_admin().then_some(message),
+            FeedMessage::CameraStatus { camera_id, .. } => camera_access(&self.user).allows(camera_id).then_some(message),
+            FeedMessage::Change { .. } | FeedMessage::Resync => Some(message),
+        }
+    }
+}
+
+// Upgrade to a change feed socket for `user`
+pub fn accept(ws: WebSocketUpgrade, user: User) -> Response {
+    // Subscribe before the upgrade so nothing published in between is lost
+    let receiver = FEED.subscribe();
+    ws.on_upgrade(move |socket| run(socket, user, receiver))
+}
+
+async fn send(socket: &mut WebSocket, message: &FeedMessage) -> bool {
+    let text = serde_json::to_string(message).expect("feed messages serialize");
+    socket.send(Message::Text(text)).await.is_ok()
+}
+
+async fn run(mut socket: WebSocket, user: User, mut receiver: broadcast::Receiver<FeedMessage>) {
+    let user_id = user.id.clone();
+    let mut viewer = Viewer::new(user);
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Clients only listen; pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+            received = receiver.recv() => {
+                let message = match received {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(_)) => FeedMessage::Resync,
+                    Err(RecvError::Closed) => break,
+                };
+
+                // Pick up role changes, and drop the connection once the
+                // account is deleted or deactivated
+                match get_user(&user_id).filter(|user| user.active) {
+                    Some(user) => viewer.user = user,
+                    None => break,
+                }
+
+                if let Some(message) = viewer.view(message) {
+                    if !send(&mut socket, &message).await {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::testing::login;
+    use axum::{routing::get, Router};
+    use futures_util::StreamExt;
+    use std::sync::{Arc, Mutex};
+    use tokio_tungstenite::tungstenite;
+
+    type Client = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+    // Serve the real feed route
+    async fn server() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/api/ws", get(crate::change_feed_handler))
+            .with_state(Arc::new(Mutex::new(())));
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        format!("ws://{}/api/ws", address)
+    }
+
+    // The next message matching `wanted`; other tests publish on the same feed
+    async fn receive(client: &mut Client, wanted: impl Fn(&serde_json::Value) -> bool) -> serde_json::Value {
+        let next = async {
+            loop {
+                match client.next().await.unwrap().unwrap() {
+                    tungstenite::Message::Text(text) => {
+                        let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        if wanted(&message) {
+                            return message;
+                        }
+                    }
+                    tungstenite::Message::Close(_) => panic!("socket closed"),
+                    _ => continue,
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), next).await.expect("no matching message")
+    }
+
+    fn camera(id: &str) -> Camera {
+        let mut camera = crate::mock_data::get_camera("101").unwrap();
+        camera.id = id.to_string();
+        camera.name = format!("Feed test {}", id);
+        camera.zone_id = None;
+        camera
+    }
+
+    fn log(target: &str) -> ActivityLog {
+        ActivityLog {
+            id: crate::mock_data::next_id(),
+            timestamp: crate::mock_data::now_timestamp(),
+            user_id: "system".to_string(),
+            action: "FEED_TEST".to_string(),
+            target: target.to_string(),
+            details: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pushes_typed_changes_and_status() {
+        let url = server().await;
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("{}?token={}", url, login("admin").await)).await.unwrap();
+
+        crate::mock_data::create_camera(camera("feed-1"));
+        let created = receive(&mut client, |m| m["type"] == "change" && m["data"]["id"] == "feed-1").await;
+        assert_eq!(created["action"], "created");
+        assert_eq!(created["entity"], "camera");
+        assert_eq!(created["data"]["name"], "Feed test feed-1");
+
+        let status = receive(&mut client, |m| m["type"] == "camera_status" && m["camera_id"] == "feed-1").await;
+        assert_eq!(status["previous_status"], serde_json::Value::Null);
+
+        crate::mock_data::delete_camera("feed-1");
+        let deleted = receive(&mut client, |m| m["type"] == "change" && m["data"]["id"] == "feed-1").await;
+        assert_eq!(deleted["action"], "deleted");
+    }
+
+    #[tokio::test]
+    async fn viewers_only_get_what_they_may_read() {
+        let url = server().await;
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("{}?token={}", url, login("asmith").await)).await.unwrap();
+
+        // The viewer has no grant on an unassigned camera, and doesn't see the
+        // audit trail or other users, so all but the last change are skipped
+        crate::mock_data::create_camera(camera("feed-2"));
+        crate::mock_data::add_activity_log(log("feed-2"));
+        let other = crate::mock_data::get_user("2").unwrap();
+        crate::mock_data::update_user("2", other);
+        let own = crate::mock_data::get_user("3").unwrap();
+        crate::mock_data::update_user("3", own);
+
+        let next = receive(&mut client, |m| {
+            m["data"]["id"] == "feed-2" || m["camera_id"] == "feed-2" || m["data"]["target"] == "feed-2"
+                || (m["entity"] == "user" && (m["data"]["id"] == "2" || m["data"]["id"] == "3"))
+        })
+        .await;
+        assert_eq!(next["entity"], "user");
+        assert_eq!(next["data"]["id"], "3");
+        crate::mock_data::delete_camera("feed-2");
+    }
+
+    #[tokio::test]
+    async fn admins_get_users_and_the_audit_trail() {
+        let url = server().await;
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("{}?token={}", url, login("admin").await)).await.unwrap();
+
+        crate::mock_data::add_activity_log(log("feed-3"));
+        let logged = receive(&mut client, |m| m["entity"] == "activity_log" && m["data"]["target"] == "feed-3").await;
+        assert_eq!(logged["action"], "created");
+
+        let other = crate::mock_data::get_user("2").unwrap();
+        crate::mock_data::update_user("2", other);
+        let updated = receive(&mut client, |m| m["entity"] == "user" && m["data"]["id"] == "2").await;
+        assert_eq!(updated["action"], "updated");
+    }
+
+    #[tokio::test]
+    async fn connecting_needs_a_signed_in_user() {
+        let url = server().await;
+
+        let status = |result: Result<_, tungstenite::Error>| match result {
+            Err(tungstenite::Error::Http(response)) => response.status().as_u16(),
+            _ => 0,
+        };
+        assert_eq!(status(tokio_tungstenite::connect_async(url.clone()).await), 401);
+        assert_eq!(status(tokio_tungstenite::connect_async(format!("{}?token=mock-token-999", url)).await), 401);
+    }
+}
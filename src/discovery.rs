@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+use crate::mock_data::get_cameras;
+use crate::xml;
+
+const MULTICAST_ADDRESS: &str = "239.255.255.250:3702";
+
+// An ONVIF device that answered a WS-Discovery probe
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiscoveredDevice {
+    // WS-Addressing endpoint reference, stable across IP changes
+    pub endpoint: String,
+    pub ip_address: String,
+    // ONVIF device service URL
+    pub onvif_url: String,
+    pub scopes: Vec<String>,
+    pub name: Option<String>,
+    pub hardware: Option<String>,
+    pub location: Option<String>,
+    // Set when a camera with this IP address is already registered
+    #[serde(default)]
+    pub registered_camera_id: Option<String>,
+}
+
+// A random-looking urn:uuid for the probe's MessageID
+fn message_id() -> String {
+    let seed = format!("{:?}:{}", std::time::SystemTime::now(), std::process::id());
+    let hex = format!("{:x}", md5::compute(seed));
+    format!("urn:uuid:{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+fn probe_message(message_id: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:a="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:d="http://schemas.xmlsoap.org/ws/2005/04/discovery" xmlns:dn="http://www.onvif.org/ver10/network/wsdl">
+  <s:Header>
+    <a:Action s:mustUnderstand="1">http://schemas.xmlsoap.org/ws/2005/04/discovery/Probe</a:Action>
+    <a:MessageID>{}</a:MessageID>
+    <a:To s:mustUnderstand="1">urn:schemas-xmlsoap-org:ws:2005:04:discovery</a:To>
+  </s:Header>
+  <s:Body>
+    <d:Probe>
+      <d:Types>dn:NetworkVideoTransmitter</d:Types>
+    </d:Probe>
+  </s:Body>
+</s:Envelope>"#,
+        message_id
+    )
+}
+
+fn decode_scope_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        // Read the hex digits as bytes; slicing the str could split a multibyte character
+        let hex = |b: u8| (b as char).to_digit(16);
+        let escaped = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| Some((hex(bytes[i + 1])? * 16 + hex(bytes[i + 2])?) as u8))
+            .flatten();
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Value of an ONVIF scope such as onvif://www.onvif.org/name/Front_Gate
+fn scope_value(scopes: &[String], key: &str) -> Option<String> {
+    let prefix = format!("onvif://www.onvif.org/{}/", key);
+    scopes
+        .iter()
+        .find_map(|scope| scope.strip_prefix(&prefix))
+        .map(decode_scope_value)
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let authority = url.split("://").nth(1)?.split('/').next()?;
+    let host = match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next()?,
+        None => authority.split(':').next()?,
+    };
+    Some(host.to_string())
+}
+
+// Turn one ProbeMatches reply into devices
+pub fn parse_probe_matches(reply: &str) -> Vec<DiscoveredDevice> {
+    xml::elements(reply, "ProbeMatch")
+        .into_iter()
+        .filter_map(|probe_match| {
+            let endpoint = xml::text(probe_match.text, "Address")?.to_string();
+            // Prefer an IPv4 service address when the device lists several
+            let xaddrs: Vec<&str> = xml::text(probe_match.text, "XAddrs")?.split_whitespace().collect();
+            let onvif_url = xaddrs
+                .iter()
+                .find(|url| !url.contains('['))
+                .or(xaddrs.first())?
+                .to_string();
+            let ip_address = host_of(&onvif_url)?;
+
+            let scopes: Vec<String> = xml::text(probe_match.text, "Scopes")
+                .map(|s| s.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default();
+
+            Some(DiscoveredDevice {
+                endpoint,
+                ip_address,
+                onvif_url,
+                name: scope_value(&scopes, "name"),
+                hardware: scope_value(&scopes, "hardware"),
+                location: scope_value(&scopes, "location"),
+                scopes,
+                registered_camera_id: None,
+            })
+        })
+        .collect()
+}
+
+// Devices in a reply to the probe `message_id` whose endpoints aren't in
+// `seen` yet. Devices often answer more than once, and stray traffic that
+// isn't an answer to our probe is ignored.
+fn new_devices(reply: &str, message_id: &str, seen: &mut HashSet<String>) -> Vec<DiscoveredDevice> {
+    if xml::text(reply, "RelatesTo").is_some_and(|id| id != message_id) {
+        return Vec::new();
+    }
+
+    parse_probe_matches(reply)
+        .into_iter()
+        .filter(|device| seen.insert(device.endpoint.clone()))
+        .collect()
+}
+
+// Multicast a WS-Discovery probe and collect the ONVIF devices that answer
+// within `timeout`. Devices already registered as cameras are marked.
+pub async fn discover(timeout: Duration) -> std::io::Result<Vec<DiscoveredDevice>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.set_multicast_ttl_v4(1)?;
+
+    let message_id = message_id();
+    socket.send_to(probe_message(&message_id).as_bytes(), MULTICAST_ADDRESS).await?;
+
+    let mut devices: Vec<DiscoveredDevice> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut buffer = vec![0u8; 65536];
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await {
+        let Ok((length, from)) = received else { continue };
+        let reply = String::from_utf8_lossy(&buffer[..length]);
+
+        for device in new_devices(&reply, &message_id, &mut seen) {
+            println!("Discovered ONVIF device {} at {} (from {})", device.endpoint, device.onvif_url, from);
+            devices.push(device);
+        }
+    }
+
+    let cameras = get_cameras();
+    for device in &mut devices {
+        device.registered_camera_id = cameras
+            .iter()
+            .find(|c| c.ip_address == device.ip_address)
+            .map(|c| c.id.clone());
+    }
+    devices.sort_by(|a, b| a.ip_address.cmp(&b.ip_address));
+
+    Ok(devices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_percent_escapes_in_scopes() {
+        assert_eq!(decode_scope_value("Front%20Gate"), "Front Gate");
+        assert_eq!(decode_scope_value("Caf%C3%A9"), "Café");
+        assert_eq!(decode_scope_value("100%"), "100%");
+        assert_eq!(decode_scope_value("%zz"), "%zz");
+    }
+
+    #[test]
+    fn multibyte_text_after_a_percent_sign_is_kept() {
+        assert_eq!(decode_scope_value("%é1"), "%é1");
+        assert_eq!(decode_scope_value("a%1é"), "a%1é");
+        assert_eq!(decode_scope_value("Gate_%日本"), "Gate_%日本");
+    }
+
+    const MESSAGE_ID: &str = "urn:uuid:5e1c0b5a-0000-4000-8000-000000000001";
+
+    // Two cameras answering in one reply, one of them on IPv6 and IPv4, and a
+    // match without service addresses
+    fn probe_matches(relates_to: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<SOAP-ENV:Envelope xmlns:SOAP-ENV="http://www.w3.org/2003/05/soap-envelope" xmlns:wsa="http://schemas.xmlsoap.org/ws/2004/08/addressing" xmlns:d="http://schemas.xmlsoap.org/ws/2005/04/discovery" xmlns:dn="http://www.onvif.org/ver10/network/wsdl">
+  <SOAP-ENV:Header>
+    <wsa:MessageID>urn:uuid:9a4c2f10-0000-4000-8000-000000000002</wsa:MessageID>
+    <wsa:RelatesTo>{}</wsa:RelatesTo>
+    <wsa:Action>http://schemas.xmlsoap.org/ws/2005/04/discovery/ProbeMatches</wsa:Action>
+  </SOAP-ENV:Header>
+  <SOAP-ENV:Body>
+    <d:ProbeMatches>
+      <d:ProbeMatch>
+        <wsa:EndpointReference><wsa:Address>urn:uuid:0001-front-gate</wsa:Address></wsa:EndpointReference>
+        <d:Types>dn:NetworkVideoTransmitter</d:Types>
+        <d:Scopes>onvif://www.onvif.org/type/video_encoder onvif://www.onvif.org/name/Front%20Gate onvif://www.onvif.org/hardware/IPC-200 onvif://www.onvif.org/location/Building_A</d:Scopes>
+        <d:XAddrs>http://[fe80::1]/onvif/device_service http://192.168.1.50/onvif/device_service</d:XAddrs>
+        <d:MetadataVersion>1</d:MetadataVersion>
+      </d:ProbeMatch>
+      <d:ProbeMatch>
+        <wsa:EndpointReference><wsa:Address>urn:uuid:0002-dock</wsa:Address></wsa:EndpointReference>
+        <d:XAddrs>http://192.168.1.51:8080/onvif/device_service</d:XAddrs>
+      </d:ProbeMatch>
+      <d:ProbeMatch>
+        <wsa:EndpointReference><wsa:Address>urn:uuid:0003-no-xaddrs</wsa:Address></wsa:EndpointReference>
+        <d:Scopes>onvif://www.onvif.org/name/Hidden</d:Scopes>
+      </d:ProbeMatch>
+    </d:ProbeMatches>
+  </SOAP-ENV:Body>
+</SOAP-ENV:Envelope>"#,
+            relates_to
+        )
+    }
+
+    #[test]
+    fn parses_probe_matches() {
+        let devices = parse_probe_matches(&probe_matches(MESSAGE_ID));
+        assert_eq!(devices.len(), 2);
+
+        let front = &devices[0];
+        assert_eq!(front.endpoint, "urn:uuid:0001-front-gate");
+        // The IPv4 address wins over the IPv6 one listed first
+        assert_eq!(front.onvif_url, "http://192.168.1.50/onvif/device_service");
+        assert_eq!(front.ip_address, "192.168.1.50");
+        assert_eq!(front.scopes.len(), 4);
+        assert_eq!(front.name.as_deref(), Some("Front Gate"));
+        assert_eq!(front.hardware.as_deref(), Some("IPC-200"));
+        assert_eq!(front.location.as_deref(), Some("Building_A"));
+
+        let dock = &devices[1];
+        assert_eq!(dock.endpoint, "urn:uuid:0002-dock");
+        assert_eq!(dock.ip_address, "192.168.1.51");
+        assert!(dock.scopes.is_empty());
+        assert_eq!(dock.name, None);
+    }
+
+    #[test]
+    fn ipv6_only_devices_keep_their_address() {
+        let reply = "<d:ProbeMatch><wsa:Address>urn:uuid:v6</wsa:Address><d:XAddrs>http://[fe80::2]:8000/onvif/device_service</d:XAddrs></d:ProbeMatch>";
+        let devices = parse_probe_matches(reply);
+
+        assert_eq!(devices[0].ip_address, "fe80::2");
+        assert_eq!(devices[0].onvif_url, "http://[fe80::2]:8000/onvif/device_service");
+    }
+
+    #[test]
+    fn repeated_and_unrelated_replies_add_nothing() {
+        let mut seen = HashSet::new();
+        let endpoints = |devices: Vec<DiscoveredDevice>| devices.into_iter().map(|d| d.endpoint).collect::<Vec<_>>();
+
+        assert_eq!(endpoints(new_devices(&probe_matches(MESSAGE_ID), MESSAGE_ID, &mut seen)), [
+            "urn:uuid:0001-front-gate",
+            "urn:uuid:0002-dock",
+        ]);
+        // Devices repeat their answer since UDP may drop it
+        assert!(new_devices(&probe_matches(MESSAGE_ID), MESSAGE_ID, &mut seen).is_empty());
+
+        let mut fresh = HashSet::new();
+        assert!(new_devices(&probe_matches("urn:uuid:someone-else"), MESSAGE_ID, &mut fresh).is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use chrono::{DateTime, Utc};
+use lettre::message::header::ContentType;
+use lettre::message::{Mailbox, Message};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use std::time::Duration;
+
+use crate::alerts;
+use crate::mock_data::{
+    Alert, AlertRule, EmailKind, OutboxEmail, OutboxStatus, Report, SmtpSecurity, SmtpSettings, User,
+    get_settings, get_smtp_settings, add_outbox_email, due_outbox_emails, record_outbox_attempt,
+    format_timestamp, next_id, now_timestamp,
+};
+
+// How often the outbox is checked for emails to send
+const TICK_SECONDS: u64 = 15;
+
+// Attempts before an email is marked as failed
+const MAX_ATTEMPTS: u32 = 5;
+
+// Wait after the first failure, doubled after each one after that
+const FIRST_RETRY_SECONDS: i64 = 30;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Message templates. `{{name}}` placeholders are filled in by `render`.
+const ALERT_SUBJECT: &str = "[{{severity}}] {{rule}}";
+const ALERT_BODY: &str = "\
+An alert was raised by the rule \"{{rule}}\".
+
+{{message}}
+
+Severity: {{severity}}
+Raised at: {{raised_at}}
+
+Acknowledge or resolve it at {{link}}
+";
+
+const REPORT_SUBJECT: &str = "Scheduled report: {{name}}";
+const REPORT_BODY: &str = "\
+Your scheduled report \"{{name}}\" is ready.
+
+Period: {{period}}
+Format: {{format}}
+
+Download it at {{link}}
+";
+
+const PASSWORD_RESET_SUBJECT: &str = "Reset your password";
+const PASSWORD_RESET_BODY: &str = "\
+Hello {{name}},
+
+Someone asked to reset the password for the account \"{{username}}\". If it
+was you, follow this link within the next hour:
+
+{{link}}
+
+If it wasn't, you can ignore this email.
+";
+
+const TEST_SUBJECT: &str = "Test email";
+const TEST_BODY: &str = "\
+This is a test email from {{registered_to}}'s camera management system.
+If you can read it, email notifications are working.
+";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmailMessage {
+    pub subject: String,
+    pub body: String,
+}
+
+// Replace each `{{key}}` in the template. Unknown placeholders are left as they are.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+fn message(subject: &str, body: &str, values: &[(&str, &str)]) -> EmailMessage {
+    EmailMessage { subject: render(subject, values), body: render(body, values) }
+}
+
+// Links in emails point at the web app, which serves the API under /api
+fn link(path: &str) -> String {
+    let base = get_settings().api_url;
+    let base = if base.starts_with("http://") || base.starts_with("https://") {
+        base
+    } else {
+        format!("https://{}", base)
+    };
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+pub fn alert_message(alert: &Alert, rule: &AlertRule) -> EmailMessage {
+    let severity = format!("{:?}", alert.severity);
+    message(ALERT_SUBJECT, ALERT_BODY, &[
+        ("rule", &rule.name),
+        ("message", &alert.message),
+        ("severity", &severity),
+        ("raised_at", &alert.raised_at),
+        ("link", &link("/")),
+    ])
+}
+
+pub fn report_message(report: &Report) -> EmailMessage {
+    let format = format!("{:?}", report.format);
+    message(REPORT_SUBJECT, REPORT_BODY, &[
+        ("name", &report.name),
+        ("period", &report.period),
+        ("format", &format),
+        ("link", &link(&report.url)),
+    ])
+}
+
+pub fn password_reset_message(user: &User, token: &str) -> EmailMessage {
+    message(PASSWORD_RESET_SUBJECT, PASSWORD_RESET_BODY, &[
+        ("name", &user.name),
+        ("username", &user.username),
+        ("link", &link(&format!("/?reset_token={}", token))),
+    ])
+}
+
+pub fn test_message() -> EmailMessage {
+    message(TEST_SUBJECT, TEST_BODY, &[("registered_to", &get_settings().registered_to)])
+}
+
+// Put an email in the outbox for the background sender
+pub fn queue(kind: EmailKind, to: Vec<String>, message: EmailMessage) -> Option<OutboxEmail> {
+    if to.is_empty() {
+        return None;
+    }
+
+    let now = now_timestamp();
+    Some(add_outbox_email(OutboxEmail {
+        id: next_id(),
+        kind,
+        to,
+        subject: message.subject,
+        body: message.body,
+        status: OutboxStatus::Pending,
+        attempts: 0,
+        created_at: now.clone(),
+        next_attempt_at: now,
+        sent_at: None,
+        last_error: None,
+    }))
+}
+
+pub fn notify_alert(alert: &Alert, rule: &AlertRule) {
+    if get_settings().email_alerts {
+        let to = alerts::audience(alert)
+            .into_iter()
+            .filter(|user| !user.email.is_empty())
+            .map(|user| user.email)
+            .collect();
+        queue(EmailKind::Alert, to, alert_message(alert, rule));
+    }
+}
+
+pub fn notify_report(report: &Report, recipients: Vec<String>) {
+    queue(EmailKind::ScheduledReport, recipients, report_message(report));
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, String> {
+    address.parse::<Mailbox>().map_err(|e| format!("Invalid address '{}': {}", address, e))
+}
+
+// Deliver one message straight away
+pub async fn send(smtp: &SmtpSettings, to: &[String], message: &EmailMessage) -> Result<(), String> {
+    if smtp.host.is_empty() {
+        return Err("SMTP is not configured".to_string());
+    }
+
+    let mut builder = Message::builder()
+        .from(mailbox(&smtp.from_address)?)
+        .subject(message.subject.clone())
+        .header(ContentType::TEXT_PLAIN);
+    for address in to {
+        builder = builder.to(mailbox(address)?);
+    }
+    let email = builder.body(message.body.clone()).map_err(|e| e.to_string())?;
+
+    let transport = match smtp.security {
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host).map_err(|e| e.to_string())?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host).map_err(|e| e.to_string())?,
+    };
+    let mut transport = transport.port(smtp.port).timeout(Some(SEND_TIMEOUT));
+    if !smtp.username.is_empty() {
+        transport = transport.credentials(Credentials::new(smtp.username.clone(), smtp.password.clone()));
+    }
+
+    transport.build().send(email).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+// How long to wait before the next attempt, after `attempts` failed ones.
+// None once the email should be given up on.
+pub fn retry_delay(attempts: u32) -> Option<chrono::Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    Some(chrono::Duration::seconds(FIRST_RETRY_SECONDS << attempts.saturating_sub(1)))
+}
+
+// Spawn the background task that sends queued email
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            deliver_due(Utc::now()).await;
+        }
+    });
+}
+
+async fn deliver_due(now: DateTime<Utc>) {
+    let due = due_outbox_emails(now);
+    if due.is_empty() {
+        return;
+    }
+
+    let smtp = get_smtp_settings();
+    for email in due {
+        let message = EmailMessage { subject: email.subject.clone(), body: email.body.clone() };
+        let result = send(&smtp, &email.to, &message).await;
+
+        let attempts = email.attempts + 1;
+        let retry_at = match &result {
+            Ok(()) => None,
+            Err(error) => {
+                eprintln!("Email {} to {} failed (attempt {}): {}", email.id, email.to.join(", "), attempts, error);
+                retry_delay(attempts).map(|delay| format_timestamp(Utc::now() + delay))
+            }
+        };
+        record_outbox_attempt(&email.id, result, retry_at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn smtp(port: u16) -> SmtpSettings {
+        SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            from_address: "cameras@example.com".to_string(),
+            ..SmtpSettings::default()
+        }
+    }
+
+    fn hello() -> EmailMessage {
+        EmailMessage { subject: "Camera offline".to_string(), body: "Front Gate is offline".to_string() }
+    }
+
+    // A minimal SMTP server for one session. Replies to RCPT TO with
+    // `rcpt_reply` and returns every line the client sent.
+    async fn fake_server(rcpt_reply: &'static str) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let session = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut reader = BufReader::new(read);
+            let mut received = Vec::new();
+            let mut in_data = false;
+
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                received.push(line.clone());
+
+                let command = line.to_ascii_uppercase();
+                let reply = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    "250 queued"
+                } else if command.starts_with("EHLO") || command.starts_with("HELO") {
+                    "250 fake"
+                } else if command.starts_with("RCPT TO") {
+                    rcpt_reply
+                } else if command == "DATA" {
+                    in_data = true;
+                    "354 go ahead"
+                } else if command == "QUIT" {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    "250 ok"
+                };
+                write.write_all(format!("{}\r\n", reply).as_bytes()).await.unwrap();
+            }
+            received
+        });
+
+        (port, session)
+    }
+
+    #[tokio::test]
+    async fn delivers_message_to_every_recipient() {
+        let (port, session) = fake_server("250 ok").await;
+        let to = vec!["ops@example.com".to_string(), "guard@example.com".to_string()];
+
+        send(&smtp(port), &to, &hello()).await.unwrap();
+
+        let received = session.await.unwrap();
+        assert!(received.iter().any(|l| l == "MAIL FROM:<cameras@example.com>"));
+        assert!(received.iter().any(|l| l == "RCPT TO:<ops@example.com>"));
+        assert!(received.iter().any(|l| l == "RCPT TO:<guard@example.com>"));
+        assert!(received.iter().any(|l| l == "Subject: Camera offline"));
+        assert!(received.iter().any(|l| l == "Front Gate is offline"));
+    }
+
+    #[tokio::test]
+    async fn rejected_recipient_is_an_error() {
+        let (port, _session) = fake_server("550 no such user").await;
+
+        let result = send(&smtp(port), &["nobody@example.com".to_string()], &hello()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn unreachable_or_unconfigured_server_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let to = vec!["ops@example.com".to_string()];
+
+        assert!(send(&smtp(port), &to, &hello()).await.is_err());
+        let unconfigured = SmtpSettings { host: String::new(), ..smtp(port) };
+        assert_eq!(send(&unconfigured, &to, &hello()).await, Err("SMTP is not configured".to_string()));
+    }
+
+    #[test]
+    fn retries_back_off_then_give_up() {
+        assert_eq!(retry_delay(1), Some(chrono::Duration::seconds(30)));
+        assert_eq!(retry_delay(2), Some(chrono::Duration::seconds(60)));
+        assert_eq!(retry_delay(4), Some(chrono::Duration::seconds(240)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn render_fills_known_placeholders_only() {
+        let text = render("{{rule}} for {{camera}} ({{unknown}})", &[("rule", "Offline"), ("camera", "Front Gate")]);
+        assert_eq!(text, "Offline for Front Gate ({{unknown}})");
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+use crate::rtsp;
+
+use crate::mock_data::{
+    ActivityLog, Camera, ProbeError, StreamInfo,
+    get_cameras, get_settings, record_health_check, add_activity_log,
+    next_id, now_timestamp,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub enum ProbeMode {
+    // Only check that the port accepts a TCP connection
+    Tcp,
+    // Speak RTSP to confirm the stream exists and read its parameters
+    Rtsp,
+}
+
+impl ProbeMode {
+    pub fn from_name(name: &str) -> Option<ProbeMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "tcp" => Some(ProbeMode::Tcp),
+            "rtsp" => Some(ProbeMode::Rtsp),
+            _ => None,
+        }
+    }
+}
+
+async fn probe(camera: &Camera, mode: ProbeMode, timeout: Duration) -> Result<Option<StreamInfo>, ProbeError> {
+    match mode {
+        ProbeMode::Tcp => {
+            let connect = TcpStream::connect((camera.ip_address.as_str(), camera.port));
+            match tokio::time::timeout(timeout, connect).await {
+                Ok(Ok(_)) => Ok(None),
+                Ok(Err(e)) => Err(ProbeError::Unreachable(e.to_string())),
+                Err(_) => Err(ProbeError::Unreachable("Connection timed out".to_string())),
+            }
+        }
+        ProbeMode::Rtsp => rtsp::probe(camera, timeout).await.map(Some),
+    }
+}
+
+// Spawn the background task that keeps camera status in sync with reality.
+// The pause between sweeps follows `Settings::refresh_interval`.
+pub fn start(mode: ProbeMode, timeout: Duration) {
+    tokio::spawn(async move {
+        loop {
+            check_cameras(mode, timeout).await;
+
+            let interval = get_settings().refresh_interval.max(1) as u64;
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+async fn check_cameras(mode: ProbeMode, timeout: Duration) {
+    let mut probes = JoinSet::new();
+    for camera in get_cameras().into_iter().filter(|c| c.active) {
+        probes.spawn(async move {
+            let result = probe(&camera, mode, timeout).await;
+            (camera, result)
+        });
+    }
+
+    while let Some(result) = probes.join_next().await {
+        let Ok((camera, result)) = result else { continue };
+        let error = result.as_ref().err().cloned();
+        // The camera may have been deleted while it was being probed
+        let Some((previous, status)) = record_health_check(&camera.id, result) else { continue };
+
+        if previous != status {
+            let reason = error.map(|e| format!(" ({:?})", e)).unwrap_or_default();
+            println!("Camera {} ({}) changed from {:?} to {:?}{}", camera.id, camera.name, previous, status, reason);
+            add_activity_log(ActivityLog {
+                id: next_id(),
+                timestamp: now_timestamp(),
+                user_id: "system".to_string(),
+                action: "CAMERA_STATUS_CHANGE".to_string(),
+                target: camera.id.clone(),
+                details: format!("{} went from {:?} to {:?}{}", camera.name, previous, status, reason),
+            });
+        }
+    }
+}
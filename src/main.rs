@@ -2,23 +2,69 @@ use axum::{
     routing::{get, post, put, delete, get_service},
     Router,
     response::Json,
-    extract::{Path, State, Query},
-    http::StatusCode,
+    extract::{ws::WebSocketUpgrade, Path, Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
 use tower_http::services::ServeDir;
-use std::{net::{SocketAddr, IpAddr}, env, collections::HashMap};
+use std::{net::{SocketAddr, IpAddr}, env};
 use std::sync::{Arc, Mutex};
 
 // Import our mock data module
 mod mock_data;
+mod reports;
+mod report_jobs;
+mod report_retention;
+mod scheduler;
+mod uptime;
+mod health;
+mod rtsp;
+mod discovery;
+mod xml;
+mod onvif;
+mod ptz;
+mod auth;
+mod snapshot;
+mod mjpeg;
+mod webrtc;
+mod secrets;
+mod sites;
+mod permissions;
+mod maintenance;
+mod alerts;
+mod email;
+mod sms;
+mod webhooks;
+mod camera_events;
+mod change_feed;
 use crate::mock_data::{
-    User, Camera, ActivityLog, Report, ReportType, ReportFormat, Settings,
+    User, UserGroup, Camera, CameraStatus, CameraFilter, Site, Zone, CameraGrant, GrantSubject, GrantTarget, ActivityLog, Report, ReportType, ReportFormat, ReportSchedule, LiveLayout, MaintenanceWindow, MaintenanceState, MaintenanceTarget,
+    Alert, AlertRule, AlertState, EmailKind, OutboxEmail, SmsMessage, Settings,
+    WebhookSubscription, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent,
+    CameraEvent, CameraEventType, CameraEventFilter,
     get_users, get_user, create_user, update_user, delete_user,
-    get_cameras, get_camera, create_camera, update_camera, delete_camera,
+    get_user_groups, get_user_group, create_user_group, update_user_group, delete_user_group,
+    add_group_member, remove_group_member,
+    get_cameras, get_cameras_in, get_camera, create_camera, update_camera, delete_camera, update_camera_details,
+    get_sites, get_site, create_site, update_site, delete_site,
+    get_zones, get_zone, create_zone, update_zone, delete_zone,
+    get_camera_grants, create_camera_grant, delete_camera_grant,
+    CameraCredentials, set_camera_credentials, rotate_master_key,
+    get_status_history, acknowledge_status_change,
     get_activity_logs, add_activity_log,
-    get_reports, get_report, add_report,
-    get_settings, update_settings,
+    get_reports, get_report, get_report_artifact, delete_report,
+    get_report_schedules, get_report_schedule, create_report_schedule, update_report_schedule, delete_report_schedule,
+    get_live_layouts, create_live_layout, update_live_layout, delete_live_layout,
+    get_maintenance_windows, get_maintenance_window, get_maintenance_cameras,
+    create_maintenance_window, update_maintenance_window, delete_maintenance_window,
+    get_alerts, get_alert, acknowledge_alert, resolve_alert, resolve_cleared_alerts,
+    get_alert_rules, get_alert_rule, create_alert_rule, update_alert_rule, delete_alert_rule, record_failed_login,
+    get_settings, update_settings, get_smtp_settings, get_outbox, retry_outbox_email,
+    get_sms_gateway, get_sms_messages, retry_sms_message,
+    set_camera_event_token, add_camera_event, get_camera_events, get_camera_event, get_event_snapshot,
+    get_webhooks, get_webhook, create_webhook, update_webhook, delete_webhook, get_webhook_deliveries, retry_webhook_delivery,
+    next_id, now_timestamp, format_timestamp, parse_timestamp,
 };
 
 type AppState = Arc<Mutex<()>>;
@@ -32,32 +78,162 @@ async fn main() {
     // Shared state (not used yet but prepared for future)
     let state = Arc::new(Mutex::new(()));
 
+    // Camera credentials are encrypted with this key. Without one they are
+    // sealed with a temporary key that only lasts as long as the process.
+    match env::var("CAMERA_SECRET_KEY") {
+        Ok(value) => match secrets::MasterKey::from_base64(&value) {
+            Ok(key) => secrets::set_master_key(key),
+            Err(e) => {
+                eprintln!("Invalid CAMERA_SECRET_KEY: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => println!("CAMERA_SECRET_KEY is not set; camera credentials use a temporary key"),
+    }
+
+    // Start the background workers that generate report artifacts
+    let report_workers = env::var("REPORT_WORKERS").ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(2);
+    let report_queue_size = env::var("REPORT_QUEUE_SIZE").ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(32);
+    report_jobs::start_workers(report_workers, report_queue_size);
+    scheduler::start();
+    report_retention::start();
+    maintenance::start();
+    alerts::start();
+    email::start();
+    sms::start();
+    webhooks::start();
+
+    // Probe cameras in the background so their status reflects reachability
+    let health_mode = env::var("HEALTH_CHECK_MODE").ok().and_then(|v| health::ProbeMode::from_name(&v)).unwrap_or(health::ProbeMode::Rtsp);
+    let health_timeout_ms = env::var("HEALTH_CHECK_TIMEOUT_MS").ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(2000);
+    health::start(health_mode, std::time::Duration::from_millis(health_timeout_ms));
+
+    let mjpeg_max_viewers = env::var("MJPEG_MAX_VIEWERS").ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(4);
+    mjpeg::set_max_viewers(mjpeg_max_viewers);
+
+    // Live view over WebRTC needs a media relay; until one is plugged in here,
+    // signaling works but offers are refused
+    webrtc::install_relay(Arc::new(webrtc::NoRelay));
+
     // Create our API routes
     let api_routes = Router::new()
         // Authentication routes
         .route("/auth/login", post(login_handler))
+        .route("/auth/session", get(get_session_handler))
+        .route("/auth/logout", post(logout_handler))
+        .route("/auth/password-reset", post(password_reset_handler))
+        .route("/auth/password-reset/confirm", post(confirm_password_reset_handler))
         // User routes
         .route("/users", get(get_users_handler))
         .route("/users/:id", get(get_user_handler))
         .route("/users", post(create_user_handler))
         .route("/users/:id", put(update_user_handler))
         .route("/users/:id", delete(delete_user_handler))
+        .route("/users/:id/permissions", get(get_user_permissions_handler))
+        // User group routes
+        .route("/groups", get(get_user_groups_handler))
+        .route("/groups/:id", get(get_user_group_handler))
+        .route("/groups", post(create_user_group_handler))
+        .route("/groups/:id", put(update_user_group_handler))
+        .route("/groups/:id", delete(delete_user_group_handler))
+        .route("/groups/:id/members/:user_id", put(add_group_member_handler))
+        .route("/groups/:id/members/:user_id", delete(remove_group_member_handler))
         // Camera routes
         .route("/cameras", get(get_cameras_handler))
+        .route("/cameras/discover", get(discover_cameras_handler))
+        .route("/cameras/import", post(import_cameras_handler))
         .route("/cameras/:id", get(get_camera_handler))
         .route("/cameras", post(create_camera_handler))
         .route("/cameras/:id", put(update_camera_handler))
         .route("/cameras/:id", delete(delete_camera_handler))
+        .route("/cameras/:id/credentials", put(set_camera_credentials_handler))
+        .route("/cameras/:id/refresh-info", post(refresh_camera_info_handler))
+        .route("/cameras/:id/ptz", post(camera_ptz_handler))
+        .route("/cameras/:id/snapshot", get(camera_snapshot_handler))
+        .route("/cameras/:id/mjpeg", get(camera_mjpeg_handler))
+        .route("/cameras/:id/webrtc", get(camera_webrtc_handler))
+        .route("/webrtc/sessions", get(get_webrtc_sessions_handler))
+        .route("/cameras/:id/status-history", get(get_camera_status_history_handler))
+        .route("/cameras/:id/events", get(get_camera_events_handler))
+        .route("/cameras/:id/events", post(ingest_camera_event_handler))
+        .route("/cameras/:id/event-token", post(issue_event_token_handler))
+        .route("/cameras/:id/event-token", delete(revoke_event_token_handler))
+        .route("/events", get(get_events_handler))
+        .route("/events/:id/snapshot", get(get_event_snapshot_handler))
+        .route("/cameras/:id/incidents", get(get_camera_incidents_handler))
+        .route("/cameras/:id/incidents/:change_id/acknowledge", post(acknowledge_incident_handler))
+        // Site and zone routes
+        .route("/sites", get(get_sites_handler))
+        .route("/sites/summary", get(get_sites_summary_handler))
+        .route("/sites/:id", get(get_site_handler))
+        .route("/sites", post(create_site_handler))
+        .route("/sites/:id", put(update_site_handler))
+        .route("/sites/:id", delete(delete_site_handler))
+        .route("/zones", get(get_zones_handler))
+        .route("/zones", post(create_zone_handler))
+        .route("/zones/:id", put(update_zone_handler))
+        .route("/zones/:id", delete(delete_zone_handler))
+        // Camera permission routes
+        .route("/permissions", get(get_camera_grants_handler))
+        .route("/permissions", post(create_camera_grant_handler))
+        .route("/permissions/:id", delete(delete_camera_grant_handler))
         // Activity log routes
         .route("/logs", get(get_logs_handler))
         .route("/logs", post(create_log_handler))
         // Report routes
         .route("/reports", get(get_reports_handler))
+        .route("/reports/storage", get(get_report_storage_handler))
         .route("/reports/:id", get(get_report_handler))
+        .route("/reports/:id", delete(delete_report_handler))
         .route("/reports", post(create_report_handler))
+        .route("/reports/:id/cancel", post(cancel_report_handler))
+        .route("/reports/:id/download", get(download_report_handler))
+        // Report schedule routes
+        .route("/report-schedules", get(get_report_schedules_handler))
+        .route("/report-schedules/:id", get(get_report_schedule_handler))
+        .route("/report-schedules", post(create_report_schedule_handler))
+        .route("/report-schedules/:id", put(update_report_schedule_handler))
+        .route("/report-schedules/:id", delete(delete_report_schedule_handler))
+        // Live View layout routes
+        .route("/layouts", get(get_live_layouts_handler))
+        .route("/layouts", post(create_live_layout_handler))
+        .route("/layouts/:id", put(update_live_layout_handler))
+        .route("/layouts/:id", delete(delete_live_layout_handler))
+        // Alert routes
+        .route("/alerts", get(get_alerts_handler))
+        .route("/alerts/:id/acknowledge", post(acknowledge_alert_handler))
+        .route("/alerts/:id/resolve", post(resolve_alert_handler))
+        .route("/alert-rules", get(get_alert_rules_handler))
+        .route("/alert-rules/:id", get(get_alert_rule_handler))
+        .route("/alert-rules", post(create_alert_rule_handler))
+        .route("/alert-rules/:id", put(update_alert_rule_handler))
+        .route("/alert-rules/:id", delete(delete_alert_rule_handler))
+        // Maintenance window routes
+        .route("/maintenance", get(get_maintenance_windows_handler))
+        .route("/maintenance", post(create_maintenance_window_handler))
+        .route("/maintenance/:id", put(update_maintenance_window_handler))
+        .route("/maintenance/:id", delete(delete_maintenance_window_handler))
         // Settings routes
+        .route("/secrets/rotate", post(rotate_secrets_handler))
         .route("/settings", get(get_settings_handler))
         .route("/settings", put(update_settings_handler))
+        .route("/settings/test-email", post(send_test_email_handler))
+        .route("/outbox", get(get_outbox_handler))
+        .route("/outbox/:id/retry", post(retry_outbox_email_handler))
+        .route("/settings/test-sms", post(send_test_sms_handler))
+        .route("/sms", get(get_sms_messages_handler))
+        .route("/sms/:id/retry", post(retry_sms_message_handler))
+        // Webhook routes
+        .route("/webhooks", get(get_webhooks_handler))
+        .route("/webhooks", post(create_webhook_handler))
+        .route("/webhooks/dead-letters", get(get_webhook_dead_letters_handler))
+        .route("/webhooks/deliveries/:id/retry", post(retry_webhook_delivery_handler))
+        .route("/webhooks/:id", get(get_webhook_handler))
+        .route("/webhooks/:id", put(update_webhook_handler))
+        .route("/webhooks/:id", delete(delete_webhook_handler))
+        .route("/webhooks/:id/deliveries", get(get_webhook_deliveries_handler))
+        // Live change feed for open dashboards
+        .route("/ws", get(change_feed_handler))
         // Legacy routes for backwards compatibility
         .route("/hello", get(hello_handler))
         .route("/hello/:name", get(hello_name_handler))
@@ -120,95 +296,339 @@ struct LoginResponse {
 }
 
 async fn login_handler(Json(payload): Json<LoginRequest>) -> Result<Json<LoginResponse>, StatusCode> {
-    // In a real app, we would validate credentials against a database
-    // For this mock implementation, we'll accept any login with a password "password"
-    if payload.password != "password" {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-    
-    // Find the user by username
+    // Users who have never set a password sign in with the mock one
     let users = get_users();
-    if let Some(user) = users.into_iter().find(|u| u.username == payload.username) {
+    let user = users
+        .into_iter()
+        .find(|u| u.username == payload.username)
+        .filter(|u| auth::password_matches(u, &payload.password));
+    if let Some(user) = user {
         // In a real app, we would generate a proper JWT token
         // For now, just use a simple token
-        let token = format!("mock-token-{}", user.id);
+        let token = auth::start_session(&user);
         
         Ok(Json(LoginResponse {
             token,
             user,
         }))
     } else {
+        record_failed_login(&payload.username);
         Err(StatusCode::UNAUTHORIZED)
     }
 }
 
+// Who the token belongs to, so a client can tell its session is still good
+async fn get_session_handler(user: auth::CurrentUser) -> Json<User> {
+    Json(user.0)
+}
+
+async fn logout_handler(auth::BearerToken(token): auth::BearerToken) -> StatusCode {
+    if auth::end_session(&token) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::UNAUTHORIZED
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PasswordResetRequest {
+    email: String,
+}
+
+// Always accepted, so the response doesn't reveal which addresses have accounts
+async fn password_reset_handler(Json(payload): Json<PasswordResetRequest>) -> StatusCode {
+    let user = get_users()
+        .into_iter()
+        .find(|u| u.active && !u.email.is_empty() && u.email.eq_ignore_ascii_case(payload.email.trim()));
+
+    if let Some(user) = user {
+        let token = auth::issue_password_reset(&user, chrono::Utc::now());
+        email::queue(EmailKind::PasswordReset, vec![user.email.clone()], email::password_reset_message(&user, &token));
+        add_activity_log(ActivityLog {
+            id: next_id(),
+            timestamp: now_timestamp(),
+            user_id: user.id.clone(),
+            action: "PASSWORD_RESET_REQUESTED".to_string(),
+            target: user.id,
+            details: "Password reset email queued".to_string(),
+        });
+    }
+    StatusCode::ACCEPTED
+}
+
+#[derive(Serialize, Deserialize)]
+struct PasswordResetConfirm {
+    token: String,
+    password: String,
+}
+
+async fn confirm_password_reset_handler(Json(payload): Json<PasswordResetConfirm>) -> StatusCode {
+    match auth::reset_password(payload.token.trim(), &payload.password, chrono::Utc::now()) {
+        Ok(user) => {
+            add_activity_log(ActivityLog {
+                id: next_id(),
+                timestamp: now_timestamp(),
+                user_id: user.id.clone(),
+                action: "PASSWORD_RESET".to_string(),
+                target: user.id,
+                details: "Password changed with an emailed reset link".to_string(),
+            });
+            StatusCode::NO_CONTENT
+        }
+        Err(auth::ResetError::TooShort | auth::ResetError::InvalidToken) => StatusCode::BAD_REQUEST,
+    }
+}
+
 // User handlers
-async fn get_users_handler(_state: State<AppState>) -> Json<Vec<User>> {
-    Json(get_users())
+// Users carry contact details, so non-admins only get their own record
+fn can_read_user(current: &auth::CurrentUser, id: &str) -> bool {
+    current.require_admin().is_ok() || current.0.id == id
+}
+
+async fn get_users_handler(current: auth::CurrentUser, _state: State<AppState>) -> Json<Vec<User>> {
+    Json(get_users().into_iter().filter(|u| can_read_user(&current, &u.id)).collect())
 }
 
-async fn get_user_handler(Path(id): Path<String>, _state: State<AppState>) -> Result<Json<User>, StatusCode> {
-    get_user(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+async fn get_user_handler(
+    Path(id): Path<String>,
+    current: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<User>, StatusCode> {
+    get_user(&id)
+        .filter(|u| can_read_user(&current, &u.id))
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
 }
 
 async fn create_user_handler(
+    current: auth::CurrentUser,
     _state: State<AppState>,
     Json(user): Json<User>,
-) -> Json<User> {
+) -> Result<Json<User>, StatusCode> {
+    current.require_admin()?;
+    if !valid_user_phone(&user) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
     // In a real app, we'd generate an ID, but for the mock we'll use the provided one
-    Json(create_user(user))
+    let user = create_user(user);
+    webhooks::emit(WebhookEvent::UserCreated, &user);
+    Ok(Json(user))
 }
 
+// Admins may edit anyone. Other users may edit their own details, but not
+// their role or whether the account is active.
 async fn update_user_handler(
     Path(id): Path<String>,
+    current: auth::CurrentUser,
     _state: State<AppState>,
     Json(user): Json<User>,
 ) -> Result<Json<User>, StatusCode> {
-    update_user(&id, user).map(Json).ok_or(StatusCode::NOT_FOUND)
+    let own_details_only = current.0.id == id && user.role == current.0.role && user.active == current.0.active;
+    if !own_details_only {
+        current.require_admin()?;
+    }
+    if !valid_user_phone(&user) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let user = update_user(&id, user).ok_or(StatusCode::NOT_FOUND)?;
+    webhooks::emit(WebhookEvent::UserUpdated, &user);
+    Ok(Json(user))
+}
+
+// The phone number is optional, but has to be usable for SMS when given
+fn valid_user_phone(user: &User) -> bool {
+    user.phone.is_empty() || sms::valid_phone(&user.phone)
 }
 
 async fn delete_user_handler(
     Path(id): Path<String>,
+    current: auth::CurrentUser,
     _state: State<AppState>,
 ) -> StatusCode {
+    if let Err(status) = current.require_admin() {
+        return status;
+    }
     if delete_user(&id) {
+        webhooks::emit(WebhookEvent::UserDeleted, serde_json::json!({ "id": id }));
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// What a user can do and where each permission comes from. Users may look up
+// their own; anyone else's needs an admin.
+async fn get_user_permissions_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<permissions::EffectivePermissions>, StatusCode> {
+    if user.0.id != id {
+        user.require_admin()?;
+    }
+    let target = get_user(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(permissions::effective_permissions(&target)))
+}
+
+// User group handlers
+async fn get_user_groups_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<UserGroup>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_user_groups()))
+}
+
+async fn get_user_group_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<UserGroup>, StatusCode> {
+    user.require_admin()?;
+    get_user_group(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// Admins can't hand out a role above their own through a group
+fn check_group(user: &auth::CurrentUser, group: &UserGroup) -> Result<(), StatusCode> {
+    user.require_admin()?;
+    if group.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if group.role.rank() > user.role().rank() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
+
+async fn create_user_group_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut group): Json<UserGroup>,
+) -> Result<(StatusCode, Json<UserGroup>), StatusCode> {
+    check_group(&user, &group)?;
+    if group.members.iter().any(|member| get_user(member).is_none()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if group.id.is_empty() {
+        group.id = next_id();
+    }
+
+    Ok((StatusCode::CREATED, Json(create_user_group(group))))
+}
+
+async fn update_user_group_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut group): Json<UserGroup>,
+) -> Result<Json<UserGroup>, StatusCode> {
+    check_group(&user, &group)?;
+
+    group.id = id.clone();
+    update_user_group(&id, group).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn delete_user_group_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    if delete_user_group(&id) {
         StatusCode::NO_CONTENT
     } else {
         StatusCode::NOT_FOUND
     }
 }
 
+// Joining a group can raise a user's role, so the same ceiling applies as
+// when creating one
+async fn add_group_member_handler(
+    Path((id, user_id)): Path<(String, String)>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<UserGroup>, StatusCode> {
+    let group = get_user_group(&id).ok_or(StatusCode::NOT_FOUND)?;
+    check_group(&user, &group)?;
+    get_user(&user_id).ok_or(StatusCode::NOT_FOUND)?;
+
+    add_group_member(&id, &user_id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn remove_group_member_handler(
+    Path((id, user_id)): Path<(String, String)>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<UserGroup>, StatusCode> {
+    let group = get_user_group(&id).ok_or(StatusCode::NOT_FOUND)?;
+    check_group(&user, &group)?;
+    get_user(&user_id).ok_or(StatusCode::NOT_FOUND)?;
+    if !group.members.contains(&user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    remove_group_member(&id, &user_id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
 // Camera handlers
-async fn get_cameras_handler(_state: State<AppState>) -> Json<Vec<Camera>> {
-    Json(get_cameras())
+async fn get_cameras_handler(
+    Query(filter): Query<CameraFilter>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<Camera>> {
+    Json(user.camera_access().filter(get_cameras_in(&filter)))
+}
+
+// Cameras may be unassigned, but not assigned to a zone that doesn't exist
+fn valid_zone(camera: &Camera) -> bool {
+    camera.zone_id.as_deref().is_none_or(|zone_id| get_zone(zone_id).is_some())
 }
 
 async fn get_camera_handler(
     Path(id): Path<String>,
+    user: auth::CurrentUser,
     _state: State<AppState>,
 ) -> Result<Json<Camera>, StatusCode> {
-    get_camera(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+    user.camera(&id).map(Json)
 }
 
 async fn create_camera_handler(
+    user: auth::CurrentUser,
     _state: State<AppState>,
     Json(camera): Json<Camera>,
-) -> Json<Camera> {
-    Json(create_camera(camera))
+) -> Result<Json<Camera>, StatusCode> {
+    user.require_admin()?;
+    if !valid_zone(&camera) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(Json(create_camera(camera)))
 }
 
 async fn update_camera_handler(
     Path(id): Path<String>,
+    user: auth::CurrentUser,
     _state: State<AppState>,
     Json(camera): Json<Camera>,
 ) -> Result<Json<Camera>, StatusCode> {
+    user.require_admin()?;
+    if !valid_zone(&camera) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
     update_camera(&id, camera).map(Json).ok_or(StatusCode::NOT_FOUND)
 }
 
 async fn delete_camera_handler(
     Path(id): Path<String>,
+    user: auth::CurrentUser,
     _state: State<AppState>,
 ) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
     if delete_camera(&id) {
         StatusCode::NO_CONTENT
     } else {
@@ -216,50 +636,1558 @@ async fn delete_camera_handler(
     }
 }
 
-// Activity Log handlers
-async fn get_logs_handler(_state: State<AppState>) -> Json<Vec<ActivityLog>> {
-    Json(get_activity_logs())
+// Replace a camera's stored credentials; an empty username removes them
+async fn set_camera_credentials_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(credentials): Json<CameraCredentials>,
+) -> Result<Json<Camera>, StatusCode> {
+    user.require_admin()?;
+    set_camera_credentials(&id, credentials).map(Json).ok_or(StatusCode::NOT_FOUND)
 }
 
-async fn create_log_handler(
+// Re-read device information, network settings and stream profiles over
+// ONVIF. This logs in to the camera and rewrites its details, so only admins
+// may do it.
+async fn refresh_camera_info_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
     _state: State<AppState>,
-    Json(log): Json<ActivityLog>,
-) -> StatusCode {
-    add_activity_log(log);
-    StatusCode::CREATED
+) -> Result<Json<Camera>, StatusCode> {
+    user.require_admin()?;
+    let camera = user.camera(&id)?;
+
+    let details = onvif::fetch_details(&camera, std::time::Duration::from_secs(10))
+        .await
+        .map_err(|e| {
+            eprintln!("Failed to refresh ONVIF info for camera {}: {}", id, e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    update_camera_details(&id, details.device_info, details.network_interfaces, details.media_profiles)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
 }
 
-// Report handlers
-async fn get_reports_handler(_state: State<AppState>) -> Json<Vec<Report>> {
-    Json(get_reports())
+// A current frame from the camera, proxied so browsers never need the
+// camera's credentials or network
+async fn camera_snapshot_handler(
+    Path(id): Path<String>,
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
+    _state: State<AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let camera = user.camera(&id)?;
+
+    let snapshot = snapshot::get(&camera).await.map_err(|e| match e {
+        snapshot::SnapshotError::NoSnapshotUri => StatusCode::NOT_FOUND,
+        e => {
+            eprintln!("Snapshot for camera {} failed: {}", id, e);
+            StatusCode::BAD_GATEWAY
+        }
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, snapshot.content_type),
+            (header::CACHE_CONTROL, "max-age=5".to_string()),
+        ],
+        snapshot.bytes,
+    ))
 }
 
-async fn get_report_handler(
+// Relay the camera's MJPEG stream to the browser
+async fn camera_mjpeg_handler(
     Path(id): Path<String>,
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
     _state: State<AppState>,
-) -> Result<Json<Report>, StatusCode> {
-    get_report(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+) -> Result<impl IntoResponse, StatusCode> {
+    let camera = user.camera(&id)?;
+
+    let (content_type, body) = mjpeg::open(&camera).await.map_err(|e| match e {
+        mjpeg::MjpegError::NoStreamUrl => StatusCode::NOT_FOUND,
+        mjpeg::MjpegError::TooManyViewers => StatusCode::SERVICE_UNAVAILABLE,
+        e => {
+            eprintln!("MJPEG stream for camera {} failed: {}", id, e);
+            StatusCode::BAD_GATEWAY
+        }
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        body,
+    ))
 }
 
-async fn create_report_handler(
+// WebSocket signaling for a WebRTC viewing session. Browsers can't set headers
+// on WebSockets, so the token usually comes as a query parameter.
+async fn camera_webrtc_handler(
+    Path(id): Path<String>,
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
+    ws: WebSocketUpgrade,
     _state: State<AppState>,
-    Json(report): Json<Report>,
-) -> StatusCode {
-    add_report(report);
-    StatusCode::CREATED
+) -> Result<impl IntoResponse, StatusCode> {
+    let camera = user.camera(&id)?;
+    Ok(webrtc::accept(ws, camera, user.0))
 }
 
-// Settings handlers
-async fn get_settings_handler(_state: State<AppState>) -> Json<Settings> {
-    Json(get_settings())
+// Pushes changes to users, cameras, logs, reports and settings, filtered to
+// what the signed-in user could read through the REST endpoints
+async fn change_feed_handler(
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
+    ws: WebSocketUpgrade,
+    _state: State<AppState>,
+) -> impl IntoResponse {
+    change_feed::accept(ws, user.0)
 }
 
-async fn update_settings_handler(
+async fn get_webrtc_sessions_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<webrtc::SignalingSession>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(webrtc::sessions()))
+}
+
+#[derive(Serialize, Deserialize)]
+struct PresetSavedResponse {
+    token: String,
+}
+
+// Move a PTZ camera or manage its presets. Viewers may not move cameras.
+async fn camera_ptz_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(command): Json<ptz::PtzCommand>,
+) -> Result<axum::response::Response, StatusCode> {
+    user.require_admin()?;
+    let camera = get_camera(&id).ok_or(StatusCode::NOT_FOUND)?;
+    if !command.is_valid() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let outcome = ptz::execute(&camera, command).await.map_err(|e| {
+        eprintln!("PTZ command for camera {} failed: {}", id, e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    Ok(match outcome {
+        ptz::PtzOutcome::Done => StatusCode::NO_CONTENT.into_response(),
+        ptz::PtzOutcome::Presets(presets) => Json(presets).into_response(),
+        ptz::PtzOutcome::PresetSaved(token) => Json(PresetSavedResponse { token }).into_response(),
+    })
+}
+
+// Camera event handlers
+#[derive(Serialize, Deserialize)]
+struct EventTokenResponse {
+    token: String,
+}
+
+// Issues a new token for the camera to push events with, replacing any
+// earlier one. The token is only returned here.
+async fn issue_event_token_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<(StatusCode, Json<EventTokenResponse>), StatusCode> {
+    user.require_admin()?;
+    user.camera(&id)?;
+
+    let token = camera_events::generate_token();
+    set_camera_event_token(&id, Some(camera_events::hash_token(&token))).ok_or(StatusCode::NOT_FOUND)?;
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id.clone(),
+        action: "ISSUE_EVENT_TOKEN".to_string(),
+        target: id,
+        details: "New event token issued; earlier tokens no longer work".to_string(),
+    });
+    Ok((StatusCode::CREATED, Json(EventTokenResponse { token })))
+}
+
+async fn revoke_event_token_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<StatusCode, StatusCode> {
+    user.require_admin()?;
+    user.camera(&id)?;
+
+    set_camera_event_token(&id, None).ok_or(StatusCode::NOT_FOUND)?;
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id.clone(),
+        action: "REVOKE_EVENT_TOKEN".to_string(),
+        target: id,
+        details: "Event token revoked".to_string(),
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize, Deserialize)]
+struct IncomingCameraEvent {
+    #[serde(rename = "type")]
+    event_type: CameraEventType,
+    // RFC 3339; the time of arrival when left out
+    timestamp: Option<String>,
+    #[serde(default)]
+    details: String,
+    // Base64 JPEG or PNG
+    snapshot: Option<String>,
+}
+
+// Called by the camera itself, with its event token rather than a user session.
+// An unknown camera and a wrong token look the same.
+async fn ingest_camera_event_handler(
+    Path(id): Path<String>,
+    auth::BearerToken(token): auth::BearerToken,
+    _state: State<AppState>,
+    Json(incoming): Json<IncomingCameraEvent>,
+) -> Result<(StatusCode, Json<CameraEvent>), StatusCode> {
+    let camera = get_camera(&id)
+        .filter(|camera| camera_events::token_matches(camera, &token))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let timestamp = camera_events::event_time(incoming.timestamp.as_deref()).ok_or(StatusCode::BAD_REQUEST)?;
+    let snapshot = match incoming.snapshot.as_deref() {
+        Some(encoded) => Some(camera_events::decode_snapshot(encoded).map_err(|e| match e {
+            camera_events::SnapshotError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::BAD_REQUEST,
+        })?),
+        None => None,
+    };
+
+    let event = CameraEvent {
+        id: next_id(),
+        camera_id: camera.id,
+        event_type: incoming.event_type,
+        timestamp,
+        received_at: now_timestamp(),
+        details: incoming.details,
+        snapshot_type: snapshot.as_ref().map(|(content_type, _)| content_type.to_string()),
+    };
+    let event = add_camera_event(event, snapshot.map(|(_, bytes)| bytes));
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+// Most events returned when the query doesn't say, and the most it may ask for
+const DEFAULT_EVENT_LIMIT: usize = 100;
+const MAX_EVENT_LIMIT: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+struct EventsQuery {
+    camera_id: Option<String>,
+    #[serde(rename = "type")]
+    event_type: Option<CameraEventType>,
+    // RFC 3339 bounds on the time the camera reported
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+}
+
+fn event_filter(query: EventsQuery) -> Result<(CameraEventFilter, usize), StatusCode> {
+    let bound = |value: Option<String>| match value {
+        Some(value) => parse_timestamp(&value).map(Some).ok_or(StatusCode::BAD_REQUEST),
+        None => Ok(None),
+    };
+    let filter = CameraEventFilter {
+        camera_id: query.camera_id,
+        event_type: query.event_type,
+        from: bound(query.from)?,
+        to: bound(query.to)?,
+    };
+    Ok((filter, query.limit.unwrap_or(DEFAULT_EVENT_LIMIT).min(MAX_EVENT_LIMIT)))
+}
+
+async fn get_camera_events_handler(
+    Path(id): Path<String>,
+    Query(query): Query<EventsQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<CameraEvent>>, StatusCode> {
+    user.camera(&id)?;
+    let (filter, limit) = event_filter(EventsQuery { camera_id: Some(id), ..query })?;
+    Ok(Json(get_camera_events(&filter).into_iter().take(limit).collect()))
+}
+
+// The timeline across every camera the user can see
+async fn get_events_handler(
+    Query(query): Query<EventsQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<CameraEvent>>, StatusCode> {
+    let (filter, limit) = event_filter(query)?;
+    let access = user.camera_access();
+    let events = get_camera_events(&filter)
+        .into_iter()
+        .filter(|event| access.allows(&event.camera_id))
+        .take(limit)
+        .collect();
+    Ok(Json(events))
+}
+
+async fn get_event_snapshot_handler(
+    Path(id): Path<String>,
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
+    _state: State<AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let event = get_camera_event(&id)
+        .filter(|event| user.camera_access().allows(&event.camera_id))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let content_type = event.snapshot_type.ok_or(StatusCode::NOT_FOUND)?;
+    let bytes = get_event_snapshot(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            // Snapshots never change once stored
+            (header::CACHE_CONTROL, "max-age=86400".to_string()),
+        ],
+        bytes,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatusHistoryQuery {
+    // Inclusive date range (YYYY-MM-DD); empty means all recorded history
+    #[serde(default)]
+    from: String,
+    #[serde(default)]
+    to: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatusIntervalResponse {
+    status: CameraStatus,
+    start: String,
+    end: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UptimePoint {
+    start: String,
+    // None when the camera was only in maintenance or not monitored at all
+    availability_percent: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatusHistoryResponse {
+    camera_id: String,
+    from: String,
+    to: String,
+    availability_percent: Option<f64>,
+    intervals: Vec<StatusIntervalResponse>,
+    daily: Vec<UptimePoint>,
+    weekly: Vec<UptimePoint>,
+}
+
+async fn get_camera_status_history_handler(
+    Path(id): Path<String>,
+    Query(query): Query<StatusHistoryQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<StatusHistoryResponse>, StatusCode> {
+    user.camera(&id)?;
+
+    let history = get_status_history(Some(&id));
+    let (from, to) = uptime::period_range(&query.from, &query.to, &history, chrono::Utc::now());
+    // Keep the number of daily points bounded; the response's `from` shows the cut
+    let from = from.max(to - chrono::Duration::days(uptime::MAX_RANGE_DAYS));
+    let intervals = uptime::status_intervals(&history, from, to);
+
+    let points = |bucket| {
+        uptime::availability_by(&intervals, from, to, bucket)
+            .into_iter()
+            .map(|(start, availability_percent)| UptimePoint { start: format_timestamp(start), availability_percent })
+            .collect()
+    };
+    let daily = points(uptime::Bucket::Day);
+    let weekly = points(uptime::Bucket::Week);
+
+    Ok(Json(StatusHistoryResponse {
+        camera_id: id,
+        from: format_timestamp(from),
+        to: format_timestamp(to),
+        availability_percent: uptime::totals(&intervals).availability_percent(),
+        intervals: intervals
+            .iter()
+            .map(|i| StatusIntervalResponse {
+                status: i.status.clone(),
+                start: format_timestamp(i.start),
+                end: format_timestamp(i.end),
+            })
+            .collect(),
+        daily,
+        weekly,
+    }))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Incident {
+    change_id: String,
+    camera_id: String,
+    started_at: String,
+    ended_at: Option<String>,
+    duration_minutes: i64,
+    acknowledged_by: Option<String>,
+    acknowledged_at: Option<String>,
+}
+
+async fn get_camera_incidents_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<Incident>>, StatusCode> {
+    user.camera(&id)?;
+
+    let now = chrono::Utc::now();
+    let history = get_status_history(Some(&id));
+    let (start, end) = uptime::period_range("", "", &history, now);
+
+    let incidents = uptime::outages(&history, start, end, now)
+        .into_iter()
+        .map(|outage| Incident {
+            duration_minutes: outage.duration(now).num_minutes(),
+            change_id: outage.change_id,
+            camera_id: id.clone(),
+            started_at: format_timestamp(outage.start),
+            ended_at: outage.end.map(format_timestamp),
+            acknowledged_by: outage.acknowledged_by,
+            acknowledged_at: outage.acknowledged_at,
+        })
+        .collect();
+
+    Ok(Json(incidents))
+}
+
+// Recorded as acknowledged by the signed-in user
+async fn acknowledge_incident_handler(
+    Path((id, change_id)): Path<(String, String)>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<StatusCode, StatusCode> {
+    user.camera(&id)?;
+    let change = get_status_history(Some(&id))
+        .into_iter()
+        .find(|c| c.id == change_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    acknowledge_status_change(&change.id, &user.0.id).ok_or(StatusCode::NOT_FOUND)?;
+
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id,
+        action: "ACKNOWLEDGE_INCIDENT".to_string(),
+        target: id,
+        details: format!("Acknowledged offline incident {}", change.id),
+    });
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Site handlers
+async fn get_sites_handler(_user: auth::CurrentUser, _state: State<AppState>) -> Json<Vec<Site>> {
+    Json(get_sites())
+}
+
+// Counts only the cameras the user may see
+async fn get_sites_summary_handler(user: auth::CurrentUser, _state: State<AppState>) -> Json<sites::SitesSummary> {
+    let cameras = user.camera_access().filter(get_cameras());
+    Json(sites::summary(&cameras, get_sites(), &get_zones(None)))
+}
+
+async fn get_site_handler(
+    Path(id): Path<String>,
+    _user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Site>, StatusCode> {
+    get_site(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn create_site_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut site): Json<Site>,
+) -> Result<(StatusCode, Json<Site>), StatusCode> {
+    user.require_admin()?;
+    if site.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if site.id.is_empty() {
+        site.id = next_id();
+    }
+
+    Ok((StatusCode::CREATED, Json(create_site(site))))
+}
+
+async fn update_site_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut site): Json<Site>,
+) -> Result<Json<Site>, StatusCode> {
+    user.require_admin()?;
+    if site.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    site.id = id.clone();
+    update_site(&id, site).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// A site can only be removed once its zones have been deleted or moved
+async fn delete_site_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
     _state: State<AppState>,
-    Json(settings): Json<Settings>,
 ) -> StatusCode {
-    update_settings(settings);
-    StatusCode::OK
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    if !get_zones(Some(&id)).is_empty() {
+        StatusCode::CONFLICT
+    } else if delete_site(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Zone handlers
+#[derive(Serialize, Deserialize)]
+struct ZonesQuery {
+    site_id: Option<String>,
+}
+
+fn valid_zone_fields(zone: &Zone) -> bool {
+    !zone.name.trim().is_empty() && get_site(&zone.site_id).is_some()
+}
+
+async fn get_zones_handler(
+    Query(query): Query<ZonesQuery>,
+    _user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<Zone>> {
+    Json(get_zones(query.site_id.as_deref()))
+}
+
+async fn create_zone_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut zone): Json<Zone>,
+) -> Result<(StatusCode, Json<Zone>), StatusCode> {
+    user.require_admin()?;
+    if !valid_zone_fields(&zone) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if zone.id.is_empty() {
+        zone.id = next_id();
+    }
+
+    Ok((StatusCode::CREATED, Json(create_zone(zone))))
+}
+
+async fn update_zone_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut zone): Json<Zone>,
+) -> Result<Json<Zone>, StatusCode> {
+    user.require_admin()?;
+    if !valid_zone_fields(&zone) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    zone.id = id.clone();
+    update_zone(&id, zone).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn delete_zone_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    if delete_zone(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Camera permission handlers
+#[derive(Serialize, Deserialize)]
+struct CameraGrantsQuery {
+    user_id: Option<String>,
+    group_id: Option<String>,
+}
+
+async fn get_camera_grants_handler(
+    Query(query): Query<CameraGrantsQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<CameraGrant>>, StatusCode> {
+    user.require_admin()?;
+    let subject = query
+        .user_id
+        .map(GrantSubject::User)
+        .or(query.group_id.map(GrantSubject::Group));
+    Ok(Json(get_camera_grants(subject.as_ref())))
+}
+
+// Both ends of a grant must exist when it is made
+fn valid_grant(grant: &CameraGrant) -> bool {
+    let subject_exists = match &grant.subject {
+        GrantSubject::User(id) => get_user(id).is_some(),
+        GrantSubject::Group(id) => get_user_group(id).is_some(),
+    };
+    let target_exists = match &grant.target {
+        GrantTarget::Camera(id) => get_camera(id).is_some(),
+        GrantTarget::Zone(id) => get_zone(id).is_some(),
+        GrantTarget::Site(id) => get_site(id).is_some(),
+    };
+    subject_exists && target_exists
+}
+
+async fn create_camera_grant_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut grant): Json<CameraGrant>,
+) -> Result<(StatusCode, Json<CameraGrant>), StatusCode> {
+    user.require_admin()?;
+    if !valid_grant(&grant) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if grant.id.is_empty() {
+        grant.id = next_id();
+    }
+    grant.created_by = user.0.id;
+
+    Ok((StatusCode::CREATED, Json(create_camera_grant(grant))))
+}
+
+async fn delete_camera_grant_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    if delete_camera_grant(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiscoverQuery {
+    // How long to wait for devices to answer
+    timeout_ms: Option<u64>,
+}
+
+async fn discover_cameras_handler(
+    Query(query): Query<DiscoverQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<discovery::DiscoveredDevice>>, StatusCode> {
+    user.require_admin()?;
+    let timeout = std::time::Duration::from_millis(query.timeout_ms.unwrap_or(3000).min(10_000));
+
+    discovery::discover(timeout).await.map(Json).map_err(|e| {
+        eprintln!("Camera discovery failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+// Register discovered devices as cameras. Devices whose IP address already
+// belongs to a camera are skipped.
+async fn import_cameras_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(devices): Json<Vec<discovery::DiscoveredDevice>>,
+) -> Result<(StatusCode, Json<Vec<Camera>>), StatusCode> {
+    user.require_admin()?;
+    let mut known: Vec<String> = get_cameras().into_iter().map(|c| c.ip_address).collect();
+    let mut imported = Vec::new();
+
+    for device in devices {
+        if known.contains(&device.ip_address) {
+            continue;
+        }
+        known.push(device.ip_address.clone());
+
+        let camera = Camera {
+            id: next_id(),
+            name: device.name.or(device.hardware).unwrap_or_else(|| device.ip_address.clone()),
+            ip_address: device.ip_address,
+            port: 554,
+            location: device.location.unwrap_or_default(),
+            active: true,
+            // The health checker sets the real status on its next sweep
+            status: CameraStatus::Offline,
+            last_update: now_timestamp(),
+            onvif_url: Some(device.onvif_url),
+            ..Camera::default()
+        };
+        imported.push(create_camera(camera));
+    }
+
+    Ok((StatusCode::CREATED, Json(imported)))
+}
+
+// Activity Log handlers
+// The audit trail covers every user's actions, so only admins read or add to it
+async fn get_logs_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<ActivityLog>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_activity_logs()))
+}
+
+async fn create_log_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(log): Json<ActivityLog>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    add_activity_log(log);
+    StatusCode::CREATED
+}
+
+// Report handlers
+// Reports hold the camera data their creator could see, so viewers only get
+// their own
+fn can_read_report(user: &auth::CurrentUser, report: &Report) -> bool {
+    user.require_admin().is_ok() || report.created_by == user.0.id
+}
+
+async fn get_reports_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<Report>> {
+    Json(get_reports().into_iter().filter(|r| can_read_report(&user, r)).collect())
+}
+
+async fn get_report_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Report>, StatusCode> {
+    get_report(&id)
+        .filter(|r| can_read_report(&user, r))
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn delete_report_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if !get_report(&id).is_some_and(|r| can_read_report(&user, &r)) {
+        return StatusCode::NOT_FOUND;
+    }
+
+    // Stop a pending job first so it doesn't store a file for a deleted report
+    report_jobs::cancel(&id);
+
+    if delete_report(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Covers every user's reports, so only admins see it
+async fn get_report_storage_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<report_retention::StorageUsage>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(report_retention::storage_usage()))
+}
+
+// Reports and schedules may cover every site or one that exists
+fn known_site(site_id: &Option<String>) -> bool {
+    site_id.as_deref().is_none_or(|site_id| get_site(site_id).is_some())
+}
+
+#[derive(Serialize, Deserialize)]
+struct CreateReportRequest {
+    name: Option<String>,
+    type_: ReportType,
+    format: ReportFormat,
+    // Inclusive date range (YYYY-MM-DD); empty means unbounded
+    #[serde(default)]
+    from: String,
+    #[serde(default)]
+    to: String,
+    // Limit camera data to one site
+    #[serde(default)]
+    site_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CreateReportResponse {
+    job_id: String,
+    report: Report,
+}
+
+// The report only covers cameras the signed-in user may see
+async fn create_report_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(request): Json<CreateReportRequest>,
+) -> Result<(StatusCode, Json<CreateReportResponse>), StatusCode> {
+    if !known_site(&request.site_id) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let report = report_jobs::submit(
+        request.name,
+        request.type_,
+        request.format,
+        request.from,
+        request.to,
+        user.0.id,
+        request.site_id,
+        Vec::new(),
+    )
+    .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(CreateReportResponse { job_id: report.id.clone(), report }),
+    ))
+}
+
+async fn cancel_report_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if !get_report(&id).is_some_and(|r| can_read_report(&user, &r)) {
+        StatusCode::NOT_FOUND
+    } else if report_jobs::cancel(&id) {
+        StatusCode::ACCEPTED
+    } else {
+        // The job already finished, failed or was cancelled
+        StatusCode::CONFLICT
+    }
+}
+
+async fn download_report_handler(
+    Path(id): Path<String>,
+    auth::UrlAuthorized(user): auth::UrlAuthorized,
+    _state: State<AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let report = get_report(&id)
+        .filter(|r| can_read_report(&user, r))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let data = get_report_artifact(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    let disposition = reports::content_disposition(&report.name, &report.format);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, reports::content_type(&report.format).to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        data,
+    ))
+}
+
+// Report schedule handlers
+// Like reports, users only see and change their own schedules; admins see all
+fn owns_schedule(user: &auth::CurrentUser, schedule: &ReportSchedule) -> bool {
+    user.require_admin().is_ok() || schedule.created_by == user.0.id
+}
+
+async fn get_report_schedules_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<ReportSchedule>> {
+    Json(get_report_schedules().into_iter().filter(|s| owns_schedule(&user, s)).collect())
+}
+
+async fn get_report_schedule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<ReportSchedule>, StatusCode> {
+    get_report_schedule(&id)
+        .filter(|s| owns_schedule(&user, s))
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// Scheduled reports run with the camera access of the user who created them
+async fn create_report_schedule_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut schedule): Json<ReportSchedule>,
+) -> Result<(StatusCode, Json<ReportSchedule>), StatusCode> {
+    scheduler::parse_cron(&schedule.cron).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !known_site(&schedule.site_id) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if schedule.id.is_empty() {
+        schedule.id = next_id();
+    }
+    schedule.created_by = user.0.id;
+    schedule.last_run = None;
+    schedule.last_report_id = None;
+    schedule.next_run = scheduler::next_run_after(&schedule.cron, chrono::Utc::now());
+
+    Ok((StatusCode::CREATED, Json(create_report_schedule(schedule))))
+}
+
+async fn update_report_schedule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut schedule): Json<ReportSchedule>,
+) -> Result<Json<ReportSchedule>, StatusCode> {
+    let existing = get_report_schedule(&id)
+        .filter(|s| owns_schedule(&user, s))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    scheduler::parse_cron(&schedule.cron).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !known_site(&schedule.site_id) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Run history and ownership are owned by the server, not the client
+    schedule.id = id.clone();
+    schedule.created_by = existing.created_by;
+    schedule.last_run = existing.last_run;
+    schedule.last_report_id = existing.last_report_id;
+    schedule.next_run = scheduler::next_run_after(&schedule.cron, chrono::Utc::now());
+
+    update_report_schedule(&id, schedule).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn delete_report_schedule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    let owned = get_report_schedule(&id).is_some_and(|s| owns_schedule(&user, &s));
+    if owned && delete_report_schedule(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Live View layout handlers
+fn valid_layout(layout: &LiveLayout) -> bool {
+    let tiles = usize::from(layout.grid_size) * usize::from(layout.grid_size);
+    !layout.name.trim().is_empty() && (1..=4).contains(&layout.grid_size) && layout.cameras.len() <= tiles
+}
+
+// Layouts are personal; each user only sees and changes their own
+fn own_layout(user: &auth::CurrentUser, id: &str) -> Option<LiveLayout> {
+    get_live_layouts().into_iter().find(|l| l.id == id && l.created_by == user.0.id)
+}
+
+async fn get_live_layouts_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<LiveLayout>> {
+    Json(get_live_layouts().into_iter().filter(|l| l.created_by == user.0.id).collect())
+}
+
+async fn create_live_layout_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut layout): Json<LiveLayout>,
+) -> Result<(StatusCode, Json<LiveLayout>), StatusCode> {
+    if !valid_layout(&layout) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if layout.id.is_empty() {
+        layout.id = next_id();
+    }
+    layout.created_by = user.0.id;
+
+    Ok((StatusCode::CREATED, Json(create_live_layout(layout))))
+}
+
+async fn update_live_layout_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut layout): Json<LiveLayout>,
+) -> Result<Json<LiveLayout>, StatusCode> {
+    let existing = own_layout(&user, &id).ok_or(StatusCode::NOT_FOUND)?;
+    if !valid_layout(&layout) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    layout.id = id.clone();
+    layout.created_by = existing.created_by;
+    update_live_layout(&id, layout).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn delete_live_layout_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if own_layout(&user, &id).is_some() && delete_live_layout(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Alert handlers
+#[derive(Serialize, Deserialize)]
+struct AlertsQuery {
+    state: Option<AlertState>,
+}
+
+// Viewers only hear about the cameras they can see; system alerts such as
+// failed sign-ins are for admins
+fn can_see_alert(user: &auth::CurrentUser, alert: &Alert) -> bool {
+    user.require_admin().is_ok()
+        || alert.camera_id.as_deref().is_some_and(|id| user.camera_access().allows(id))
+}
+
+async fn get_alerts_handler(
+    Query(query): Query<AlertsQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<Alert>> {
+    let alerts = get_alerts()
+        .into_iter()
+        .filter(|alert| query.state.as_ref().is_none_or(|state| &alert.state == state))
+        .filter(|alert| can_see_alert(&user, alert))
+        .collect();
+    Json(alerts)
+}
+
+async fn acknowledge_alert_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Alert>, StatusCode> {
+    let alert = get_alert(&id).filter(|alert| can_see_alert(&user, alert)).ok_or(StatusCode::NOT_FOUND)?;
+    if alert.state == AlertState::Resolved {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    acknowledge_alert(&id, &user.0.id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// Resolving by hand starts the rule's cooldown, so an alert whose condition
+// still holds stays quiet until the cooldown is over
+async fn resolve_alert_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Alert>, StatusCode> {
+    user.require_admin()?;
+    let alert = resolve_alert(&id, &user.0.id).ok_or(StatusCode::NOT_FOUND)?;
+
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id,
+        action: "ALERT_RESOLVED".to_string(),
+        target: alert.id.clone(),
+        details: format!("Resolved by hand: {}", alert.message),
+    });
+    Ok(Json(alert))
+}
+
+async fn get_alert_rules_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<AlertRule>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_alert_rules()))
+}
+
+async fn get_alert_rule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<AlertRule>, StatusCode> {
+    user.require_admin()?;
+    get_alert_rule(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn create_alert_rule_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut rule): Json<AlertRule>,
+) -> Result<(StatusCode, Json<AlertRule>), StatusCode> {
+    user.require_admin()?;
+    if !alerts::valid_rule(&rule) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if rule.id.is_empty() {
+        rule.id = next_id();
+    }
+
+    Ok((StatusCode::CREATED, Json(create_alert_rule(rule))))
+}
+
+async fn update_alert_rule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut rule): Json<AlertRule>,
+) -> Result<Json<AlertRule>, StatusCode> {
+    user.require_admin()?;
+    if !alerts::valid_rule(&rule) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    rule.id = id.clone();
+    update_alert_rule(&id, rule).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// Alerts raised by a deleted rule would never clear on their own
+async fn delete_alert_rule_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+
+    if delete_alert_rule(&id) {
+        resolve_cleared_alerts(&id, &[]);
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// Maintenance window handlers
+#[derive(Serialize, Deserialize)]
+struct MaintenanceQuery {
+    // Leave out windows that have already ended
+    upcoming: Option<bool>,
+}
+
+fn valid_maintenance_window(window: &MaintenanceWindow) -> bool {
+    let target_exists = match &window.target {
+        MaintenanceTarget::Camera(id) => get_camera(id).is_some(),
+        MaintenanceTarget::Zone(id) => get_zone(id).is_some(),
+    };
+    let times_valid = match (parse_timestamp(&window.start), parse_timestamp(&window.end)) {
+        (Some(start), Some(end)) => start < end && end > chrono::Utc::now(),
+        _ => false,
+    };
+    target_exists && times_valid && !window.reason.trim().is_empty()
+}
+
+// Users see the windows that touch at least one camera they can see
+async fn get_maintenance_windows_handler(
+    Query(query): Query<MaintenanceQuery>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Json<Vec<MaintenanceWindow>> {
+    let access = user.camera_access();
+    let windows = get_maintenance_windows()
+        .into_iter()
+        .filter(|w| !query.upcoming.unwrap_or(false) || w.state != MaintenanceState::Completed)
+        .filter(|w| get_maintenance_cameras(&w.target).iter().any(|id| access.allows(id)))
+        .collect();
+    Json(windows)
+}
+
+async fn create_maintenance_window_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut window): Json<MaintenanceWindow>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), StatusCode> {
+    user.require_admin()?;
+    if !valid_maintenance_window(&window) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if window.id.is_empty() {
+        window.id = next_id();
+    }
+    window.created_by = user.0.id.clone();
+    window.state = MaintenanceState::Scheduled;
+    window.affected = Vec::new();
+    let window = create_maintenance_window(window);
+
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id,
+        action: "SCHEDULE_MAINTENANCE".to_string(),
+        target: window.id.clone(),
+        details: format!(
+            "Maintenance scheduled for {} from {} to {}: {}",
+            maintenance::describe_target(&window.target), window.start, window.end, window.reason
+        ),
+    });
+
+    // A window that is already open takes effect straight away
+    maintenance::apply_windows(chrono::Utc::now());
+    let window = get_maintenance_window(&window.id).unwrap_or(window);
+    Ok((StatusCode::CREATED, Json(window)))
+}
+
+// Only windows that haven't started yet can be changed
+async fn update_maintenance_window_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut window): Json<MaintenanceWindow>,
+) -> Result<Json<MaintenanceWindow>, StatusCode> {
+    user.require_admin()?;
+    let existing = get_maintenance_window(&id).ok_or(StatusCode::NOT_FOUND)?;
+    if existing.state != MaintenanceState::Scheduled {
+        return Err(StatusCode::CONFLICT);
+    }
+    if !valid_maintenance_window(&window) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    window.id = id.clone();
+    window.created_by = existing.created_by;
+    window.state = MaintenanceState::Scheduled;
+    window.affected = Vec::new();
+    update_maintenance_window(&id, window).ok_or(StatusCode::NOT_FOUND)?;
+
+    maintenance::apply_windows(chrono::Utc::now());
+    get_maintenance_window(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// Deleting an open window ends it first so its cameras come back into service
+async fn delete_maintenance_window_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+    let Some(window) = get_maintenance_window(&id) else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    if window.state == MaintenanceState::Active {
+        maintenance::finish(&id, &user.0.id);
+    }
+    if !delete_maintenance_window(&id) {
+        return StatusCode::NOT_FOUND;
+    }
+
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id,
+        action: "CANCEL_MAINTENANCE".to_string(),
+        target: id,
+        details: format!("Maintenance window for {} removed", maintenance::describe_target(&window.target)),
+    });
+    StatusCode::NO_CONTENT
+}
+
+#[derive(Serialize, Deserialize)]
+struct RotateSecretsRequest {
+    // New master key, 32 bytes encoded as base64
+    new_key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RotateSecretsResponse {
+    rotated: usize,
+    key_id: String,
+}
+
+// Re-encrypt all stored camera credentials under a new master key. The server
+// must be started with the new CAMERA_SECRET_KEY from then on.
+async fn rotate_secrets_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(request): Json<RotateSecretsRequest>,
+) -> Result<Json<RotateSecretsResponse>, StatusCode> {
+    user.require_admin()?;
+    let new_key = secrets::MasterKey::from_base64(&request.new_key).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let rotated = rotate_master_key(new_key).map_err(|e| {
+        eprintln!("Key rotation failed: {}", e);
+        StatusCode::CONFLICT
+    })?;
+    println!("Rotated the master key; re-encrypted {} camera credentials", rotated);
+
+    Ok(Json(RotateSecretsResponse { rotated, key_id: secrets::current_key_id() }))
+}
+
+// Settings handlers
+async fn get_settings_handler(_state: State<AppState>) -> Json<Settings> {
+    Json(get_settings())
+}
+
+// Settings include the SMTP password, so only admins may change them
+async fn update_settings_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(settings): Json<Settings>,
+) -> StatusCode {
+    if let Err(status) = user.require_admin() {
+        return status;
+    }
+    update_settings(settings);
+    // A lower quota or shorter retention takes effect right away
+    report_retention::enforce(&get_settings(), chrono::Utc::now());
+    StatusCode::OK
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestEmailRequest {
+    to: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestEmailResponse {
+    sent: bool,
+    error: Option<String>,
+}
+
+// Sends right away rather than through the outbox, so the result can be shown
+async fn send_test_email_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(request): Json<TestEmailRequest>,
+) -> Result<(StatusCode, Json<TestEmailResponse>), StatusCode> {
+    user.require_admin()?;
+
+    match email::send(&get_smtp_settings(), &[request.to], &email::test_message()).await {
+        Ok(()) => Ok((StatusCode::OK, Json(TestEmailResponse { sent: true, error: None }))),
+        Err(error) => Ok((StatusCode::BAD_GATEWAY, Json(TestEmailResponse { sent: false, error: Some(error) }))),
+    }
+}
+
+async fn get_outbox_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<OutboxEmail>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_outbox()))
+}
+
+async fn retry_outbox_email_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<OutboxEmail>, StatusCode> {
+    user.require_admin()?;
+    retry_outbox_email(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestSmsRequest {
+    to: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestSmsResponse {
+    sent: bool,
+    gateway_status: Option<u16>,
+    error: Option<String>,
+}
+
+// Like the test email, sent right away and not counted against the rate limit
+async fn send_test_sms_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(request): Json<TestSmsRequest>,
+) -> Result<(StatusCode, Json<TestSmsResponse>), StatusCode> {
+    user.require_admin()?;
+    if !sms::valid_phone(&request.to) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match sms::send(&get_sms_gateway(), &request.to, &sms::test_text()).await {
+        Ok(status) => Ok((StatusCode::OK, Json(TestSmsResponse { sent: true, gateway_status: Some(status), error: None }))),
+        Err(error) => Ok((StatusCode::BAD_GATEWAY, Json(TestSmsResponse {
+            sent: false,
+            gateway_status: error.gateway_status,
+            error: Some(error.message),
+        }))),
+    }
+}
+
+async fn get_sms_messages_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<SmsMessage>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_sms_messages()))
+}
+
+async fn retry_sms_message_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<SmsMessage>, StatusCode> {
+    user.require_admin()?;
+    retry_sms_message(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// Webhook handlers
+async fn get_webhooks_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<WebhookSubscription>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_webhooks()))
+}
+
+async fn get_webhook_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<WebhookSubscription>, StatusCode> {
+    user.require_admin()?;
+    get_webhook(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+// The secret is only ever returned here, so the receiver can be set up with it
+#[derive(Serialize)]
+struct CreatedWebhook {
+    #[serde(flatten)]
+    webhook: WebhookSubscription,
+    secret: String,
+}
+
+async fn create_webhook_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(mut webhook): Json<WebhookSubscription>,
+) -> Result<(StatusCode, Json<CreatedWebhook>), StatusCode> {
+    user.require_admin()?;
+    if !webhooks::valid_subscription(&webhook) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if webhook.id.is_empty() {
+        webhook.id = next_id();
+    }
+    if webhook.secret.is_empty() {
+        webhook.secret = webhooks::generate_secret();
+    }
+    webhook.created_at = now_timestamp();
+    let secret = webhook.secret.clone();
+
+    let webhook = create_webhook(webhook);
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id.clone(),
+        action: "CREATE_WEBHOOK".to_string(),
+        target: webhook.id.clone(),
+        details: format!("Webhook '{}' to {} for {}", webhook.name, webhook.url, event_names(&webhook.events)),
+    });
+    Ok((StatusCode::CREATED, Json(CreatedWebhook { webhook, secret })))
+}
+
+fn event_names(events: &[WebhookEvent]) -> String {
+    events.iter().map(|event| event.name()).collect::<Vec<_>>().join(", ")
+}
+
+async fn update_webhook_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+    Json(webhook): Json<WebhookSubscription>,
+) -> Result<Json<WebhookSubscription>, StatusCode> {
+    user.require_admin()?;
+    if !webhooks::valid_subscription(&webhook) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let webhook = update_webhook(&id, webhook).ok_or(StatusCode::NOT_FOUND)?;
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id.clone(),
+        action: "UPDATE_WEBHOOK".to_string(),
+        target: webhook.id.clone(),
+        details: format!("Webhook '{}' to {} for {}", webhook.name, webhook.url, event_names(&webhook.events)),
+    });
+    Ok(Json(webhook))
+}
+
+async fn delete_webhook_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<StatusCode, StatusCode> {
+    user.require_admin()?;
+    if !delete_webhook(&id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user.0.id.clone(),
+        action: "DELETE_WEBHOOK".to_string(),
+        target: id,
+        details: "Webhook subscription deleted".to_string(),
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_webhook_deliveries_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<WebhookDelivery>>, StatusCode> {
+    user.require_admin()?;
+    get_webhook(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(get_webhook_deliveries(Some(&id), None)))
+}
+
+async fn get_webhook_dead_letters_handler(
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<Vec<WebhookDelivery>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(get_webhook_deliveries(None, Some(WebhookDeliveryStatus::DeadLetter))))
+}
+
+async fn retry_webhook_delivery_handler(
+    Path(id): Path<String>,
+    user: auth::CurrentUser,
+    _state: State<AppState>,
+) -> Result<Json<WebhookDelivery>, StatusCode> {
+    user.require_admin()?;
+    retry_webhook_delivery(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
 }
 
 // Legacy API handlers that we're keeping for backwards compatibility
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+
+use crate::mock_data::{
+    ActivityLog, MaintenanceState, MaintenanceTarget, MaintenanceWindow,
+    get_maintenance_windows, start_maintenance_window, end_maintenance_window, add_activity_log,
+    next_id, now_timestamp, parse_timestamp,
+};
+
+// How often windows are checked for their start and end
+const TICK_SECONDS: u64 = 30;
+
+pub fn describe_target(target: &MaintenanceTarget) -> String {
+    match target {
+        MaintenanceTarget::Camera(id) => format!("camera {}", id),
+        MaintenanceTarget::Zone(id) => format!("zone {}", id),
+    }
+}
+
+fn log(user_id: &str, action: &str, window: &MaintenanceWindow, details: String) {
+    add_activity_log(ActivityLog {
+        id: next_id(),
+        timestamp: now_timestamp(),
+        user_id: user_id.to_string(),
+        action: action.to_string(),
+        target: window.id.clone(),
+        details,
+    });
+}
+
+// Spawn the background task that moves cameras in and out of maintenance as
+// their windows open and close
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            apply_windows(Utc::now());
+        }
+    });
+}
+
+pub fn apply_windows(now: DateTime<Utc>) {
+    for window in get_maintenance_windows() {
+        let (Some(start), Some(end)) = (parse_timestamp(&window.start), parse_timestamp(&window.end)) else {
+            continue;
+        };
+
+        match window.state {
+            MaintenanceState::Scheduled if end <= now => {
+                // The whole window passed while nothing was watching
+                if let Some((window, _)) = end_maintenance_window(&window.id) {
+                    log("system", "MAINTENANCE_MISSED", &window, format!(
+                        "Maintenance window for {} ended before it could start",
+                        describe_target(&window.target)
+                    ));
+                }
+            }
+            MaintenanceState::Scheduled if start <= now => begin(&window.id),
+            MaintenanceState::Active if end <= now => finish(&window.id, "system"),
+            _ => {}
+        }
+    }
+}
+
+fn begin(id: &str) {
+    let Some(window) = start_maintenance_window(id) else { return };
+    println!("Maintenance window {} started for {}", window.id, describe_target(&window.target));
+    log("system", "MAINTENANCE_START", &window, format!(
+        "Maintenance started for {} ({}); cameras: {}",
+        describe_target(&window.target),
+        window.reason,
+        window.affected.join(", ")
+    ));
+}
+
+// End a window now, whether its time is up or someone cancelled it
+pub fn finish(id: &str, user_id: &str) {
+    let Some((window, restored)) = end_maintenance_window(id) else { return };
+    println!("Maintenance window {} ended for {}", window.id, describe_target(&window.target));
+    log(user_id, "MAINTENANCE_END", &window, format!(
+        "Maintenance ended for {} ({}); cameras back in service: {}",
+        describe_target(&window.target),
+        window.reason,
+        restored.join(", ")
+    ));
+}
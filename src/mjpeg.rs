@@ -0,0 +1,136 @@
+use axum::body::{boxed, Body, BoxBody};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::mock_data::Camera;
+use crate::snapshot;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// A stream that sends nothing for this long is considered dead
+const STALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Each viewer holds its own connection to the camera, and cameras only
+// serve a handful of those
+static MAX_VIEWERS: AtomicUsize = AtomicUsize::new(4);
+static VIEWERS: Lazy<Mutex<HashMap<String, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug)]
+pub enum MjpegError {
+    NoStreamUrl,
+    TooManyViewers,
+    Unreachable(String),
+    AuthFailed,
+    Status(u16),
+    NotMjpeg(String),
+}
+
+impl std::fmt::Display for MjpegError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MjpegError::NoStreamUrl => write!(f, "camera has no MJPEG URL"),
+            MjpegError::TooManyViewers => write!(f, "viewer limit reached"),
+            MjpegError::Unreachable(message) => write!(f, "unreachable: {}", message),
+            MjpegError::AuthFailed => write!(f, "authentication failed"),
+            MjpegError::Status(status) => write!(f, "camera returned {}", status),
+            MjpegError::NotMjpeg(content_type) => write!(f, "unexpected content type {}", content_type),
+        }
+    }
+}
+
+pub fn set_max_viewers(max_viewers: usize) {
+    MAX_VIEWERS.store(max_viewers, Ordering::Relaxed);
+}
+
+// One viewer's claim on a camera; released when the stream ends
+struct ViewerSlot {
+    camera_id: String,
+}
+
+impl ViewerSlot {
+    fn acquire(camera_id: &str) -> Option<ViewerSlot> {
+        let mut viewers = VIEWERS.lock().unwrap();
+        let count = viewers.entry(camera_id.to_string()).or_insert(0);
+        if *count >= MAX_VIEWERS.load(Ordering::Relaxed) {
+            return None;
+        }
+        *count += 1;
+        Some(ViewerSlot { camera_id: camera_id.to_string() })
+    }
+}
+
+impl Drop for ViewerSlot {
+    fn drop(&mut self) {
+        let mut viewers = VIEWERS.lock().unwrap();
+        if let Some(count) = viewers.get_mut(&self.camera_id) {
+            *count -= 1;
+            if *count == 0 {
+                viewers.remove(&self.camera_id);
+            }
+        }
+    }
+}
+
+// Connect to the camera's MJPEG stream and relay it until the viewer goes
+// away or the camera stops sending. Returns the upstream content type, which
+// carries the multipart boundary, and the body to send to the viewer.
+pub async fn open(camera: &Camera) -> Result<(String, BoxBody), MjpegError> {
+    let uri = camera.mjpeg_url.clone().ok_or(MjpegError::NoStreamUrl)?;
+    let slot = ViewerSlot::acquire(&camera.id).ok_or(MjpegError::TooManyViewers)?;
+
+    let url = reqwest::Url::parse(&uri).map_err(|e| MjpegError::Unreachable(e.to_string()))?;
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| MjpegError::Unreachable(e.to_string()))?;
+
+    let mut response = tokio::time::timeout(STALL_TIMEOUT, snapshot::authorized_get(&client, camera, url))
+        .await
+        .map_err(|_| MjpegError::Unreachable("no response".to_string()))?
+        .map_err(|e| MjpegError::Unreachable(e.to_string()))?;
+
+    match response.status().as_u16() {
+        200 => {}
+        401 | 403 => return Err(MjpegError::AuthFailed),
+        status => return Err(MjpegError::Status(status)),
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !content_type.starts_with("multipart/x-mixed-replace") {
+        return Err(MjpegError::NotMjpeg(content_type));
+    }
+
+    let (mut sender, body) = Body::channel();
+    let camera_id = camera.id.clone();
+    tokio::spawn(async move {
+        let _slot = slot;
+        loop {
+            match tokio::time::timeout(STALL_TIMEOUT, response.chunk()).await {
+                Ok(Ok(Some(chunk))) => {
+                    // The viewer closed the page
+                    if sender.send_data(chunk).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(Ok(None)) => break,
+                Ok(Err(e)) => {
+                    eprintln!("MJPEG stream for camera {} failed: {}", camera_id, e);
+                    break;
+                }
+                Err(_) => {
+                    eprintln!("MJPEG stream for camera {} stalled", camera_id);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok((content_type, boxed(body)))
+}
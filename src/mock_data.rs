@@ -3,6 +3,9 @@ use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
 
+use crate::change_feed::{self, Entity, FeedMessage};
+use crate::secrets::{self, MasterKey, SealedSecret, SecretError};
+
 // User Models
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct User {
@@ -10,6 +13,9 @@ pub struct User {
     pub username: String,
     pub name: String,
     pub email: String,
+    // Mobile number in international format (+15550100), for SMS alerts
+    #[serde(default)]
+    pub phone: String,
     pub role: UserRole,
     pub active: bool,
     pub last_login: String,
@@ -22,8 +28,30 @@ pub enum UserRole {
     Viewer,
 }
 
-// Camera Models
+impl UserRole {
+    // Higher ranks can do everything lower ones can
+    pub fn rank(&self) -> u8 {
+        match self {
+            UserRole::Viewer => 0,
+            UserRole::Admin => 1,
+            UserRole::SuperAdmin => 2,
+        }
+    }
+}
+
+// Users managed together: members get the group's role if it is higher than
+// their own, plus the group's camera grants
 #[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserGroup {
+    pub id: String,
+    pub name: String,
+    pub role: UserRole,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+// Camera Models
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Camera {
     pub id: String,
     pub name: String,
@@ -33,15 +61,214 @@ pub struct Camera {
     pub active: bool,
     pub status: CameraStatus,
     pub last_update: String,
+    // Path of the RTSP stream on the device, e.g. "/stream1"
+    #[serde(default)]
+    pub stream_path: String,
+    // Write-only: accepted when a camera is created but never serialized.
+    // Stored encrypted in `sealed_credentials` and decrypted again on read.
+    #[serde(default, skip_serializing)]
+    pub username: String,
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    #[serde(skip)]
+    pub sealed_credentials: Option<SealedSecret>,
+    // Lets clients show whether credentials are set without seeing them
+    #[serde(default)]
+    pub has_credentials: bool,
+    // Filled in by the health checker's RTSP probe
+    #[serde(default)]
+    pub stream_info: Option<StreamInfo>,
+    #[serde(default)]
+    pub probe_error: Option<ProbeError>,
+    // ONVIF device service URL, known for cameras added through discovery
+    #[serde(default)]
+    pub onvif_url: Option<String>,
+    // Filled in from the camera's ONVIF services by refresh-info
+    #[serde(default)]
+    pub device_info: Option<DeviceInfo>,
+    #[serde(default)]
+    pub network_interfaces: Vec<NetworkInterface>,
+    #[serde(default)]
+    pub media_profiles: Vec<MediaProfile>,
+    #[serde(default)]
+    pub info_updated_at: Option<String>,
+    // HTTP URL of the camera's MJPEG stream, for cameras that offer one
+    #[serde(default)]
+    pub mjpeg_url: Option<String>,
+    // Zone the camera is installed in; None while it is unassigned
+    #[serde(default)]
+    pub zone_id: Option<String>,
+    // SHA-256 of the token the camera pushes events with. The token itself is
+    // only shown once, when it is issued.
+    #[serde(skip)]
+    pub event_token_hash: Option<String>,
+    #[serde(default)]
+    pub has_event_token: bool,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub enum CameraStatus {
     Online,
+    #[default]
     Offline,
     Maintenance,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeviceInfo {
+    pub manufacturer: String,
+    pub model: String,
+    pub firmware_version: String,
+    pub serial_number: String,
+    pub hardware_id: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkInterface {
+    pub token: String,
+    pub hw_address: String,
+    pub ipv4_address: Option<String>,
+    pub prefix_length: Option<u8>,
+    pub dhcp: bool,
+}
+
+// An ONVIF media profile, i.e. one encoder configuration the camera can stream
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct MediaProfile {
+    pub token: String,
+    pub name: String,
+    pub encoding: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub stream_uri: Option<String>,
+    #[serde(default)]
+    pub snapshot_uri: Option<String>,
+}
+
+// A physical site such as a building or campus
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Site {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub address: String,
+}
+
+// An area within a site that cameras are assigned to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Zone {
+    pub id: String,
+    pub site_id: String,
+    pub name: String,
+}
+
+// Camera Event Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum CameraEventType {
+    Motion,
+    Tamper,
+    IoAlarm,
+}
+
+// An alarm a camera pushed to us
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraEvent {
+    pub id: String,
+    pub camera_id: String,
+    #[serde(rename = "type")]
+    pub event_type: CameraEventType,
+    // When the camera says it happened
+    pub timestamp: String,
+    pub received_at: String,
+    // Whatever else the camera told us, such as the input port or motion region
+    #[serde(default)]
+    pub details: String,
+    // Content type of the snapshot sent with the event, if there was one
+    pub snapshot_type: Option<String>,
+}
+
+// Narrows the event timeline; empty fields match everything
+#[derive(Clone, Debug, Default)]
+pub struct CameraEventFilter {
+    pub camera_id: Option<String>,
+    pub event_type: Option<CameraEventType>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Narrows a camera list to one site or zone; empty fields match everything
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CameraFilter {
+    pub site_id: Option<String>,
+    pub zone_id: Option<String>,
+}
+
+// Who a camera grant is given to
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum GrantSubject {
+    User(String),
+    Group(String),
+}
+
+// What a grant opens up: one camera, or every camera in a zone or site
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum GrantTarget {
+    Camera(String),
+    Zone(String),
+    Site(String),
+}
+
+// Lets a viewer see cameras; admins see every camera without grants
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraGrant {
+    pub id: String,
+    pub subject: GrantSubject,
+    pub target: GrantTarget,
+    #[serde(default)]
+    pub created_by: String,
+}
+
+// Camera login as sent to the credentials endpoint and sealed for storage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// Video stream parameters advertised in the camera's SDP description
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StreamInfo {
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+}
+
+// Why the last health probe of a camera failed
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ProbeError {
+    Unreachable(String),
+    AuthFailed,
+    StreamNotFound,
+    Protocol(String),
+}
+
+// A recorded transition of a camera into `status`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraStatusChange {
+    pub id: String,
+    pub camera_id: String,
+    pub previous_status: Option<CameraStatus>,
+    pub status: CameraStatus,
+    pub timestamp: String,
+    // Set when an operator acknowledges the outage this change started
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<String>,
+}
+
 // Activity Log Model
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ActivityLog {
@@ -64,6 +291,12 @@ pub struct Report {
     pub period: String,
     pub format: ReportFormat,
     pub url: String,
+    pub status: ReportStatus,
+    pub progress: u8,
+    pub error: Option<String>,
+    // Site the report was limited to; None covers every camera
+    #[serde(default)]
+    pub site_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -71,12 +304,179 @@ pub enum ReportType {
     UsageSummary,
     CameraStatus,
     UserActivity,
+    UptimeSla,
+    OfflineIncidents,
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum ReportFormat {
     PDF,
     CSV,
+    XLSX,
+    JSON,
+    HTML,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ReportStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+// Report Schedule Models
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReportSchedule {
+    pub id: String,
+    pub name: String,
+    // Cron expression evaluated in UTC, e.g. "0 6 1 * *" for 06:00 on the 1st
+    pub cron: String,
+    pub type_: ReportType,
+    pub format: ReportFormat,
+    pub period_rule: PeriodRule,
+    pub recipients: Vec<String>,
+    pub enabled: bool,
+    pub created_by: String,
+    #[serde(default)]
+    pub site_id: Option<String>,
+    #[serde(default)]
+    pub last_run: Option<String>,
+    #[serde(default)]
+    pub next_run: Option<String>,
+    #[serde(default)]
+    pub last_report_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PeriodRule {
+    PreviousDay,
+    PreviousWeek,
+    PreviousMonth,
+    PreviousQuarter,
+    Last7Days,
+    Last30Days,
+}
+
+// Live View layout: a square grid of camera tiles saved under a name
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiveLayout {
+    pub id: String,
+    pub name: String,
+    // Tiles per row and column, 1 to 4
+    pub grid_size: u8,
+    // Camera shown in each tile, row by row; None leaves a tile empty
+    pub cameras: Vec<Option<String>>,
+    pub created_by: String,
+}
+
+// Maintenance window Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "id")]
+pub enum MaintenanceTarget {
+    Camera(String),
+    // Every camera in the zone when the window starts
+    Zone(String),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum MaintenanceState {
+    #[default]
+    Scheduled,
+    Active,
+    Completed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    pub target: MaintenanceTarget,
+    pub start: String,
+    pub end: String,
+    pub reason: String,
+    #[serde(default)]
+    pub created_by: String,
+    #[serde(default)]
+    pub state: MaintenanceState,
+    // Cameras this window put into maintenance, to be brought back when it ends
+    #[serde(default)]
+    pub affected: Vec<String>,
+}
+
+// Alert Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AlertCondition {
+    // A camera has been offline for longer than this
+    CameraOffline { minutes: u32 },
+    // More than `count` failed sign-ins within the last `minutes`
+    FailedLogins { count: u32, minutes: u32 },
+    // The license expires within this many days, or already has
+    LicenseExpiring { days: u32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    pub condition: AlertCondition,
+    pub severity: AlertSeverity,
+    // After an alert is resolved, the same rule stays quiet about the same
+    // subject for this long
+    #[serde(default)]
+    pub cooldown_minutes: u32,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AlertState {
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: String,
+    pub rule_id: String,
+    pub severity: AlertSeverity,
+    // What the alert is about, e.g. "camera:102" or "license". A rule keeps at
+    // most one unresolved alert per subject.
+    pub subject: String,
+    // Set for alerts about a single camera, so only users who can see it get them
+    pub camera_id: Option<String>,
+    pub message: String,
+    pub state: AlertState,
+    pub raised_at: String,
+    // Last time the rule found the condition still true
+    pub last_seen: String,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FailedLogin {
+    pub username: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+// An emailed password reset link. Only a hash of its token is kept.
+#[derive(Clone, Debug)]
+pub struct PasswordReset {
+    pub token_hash: String,
+    pub user_id: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
 // Settings Model
@@ -91,6 +491,254 @@ pub struct Settings {
     pub sms_alerts: bool,
     pub refresh_interval: u32,
     pub app_version: String,
+    // Availability target used by the uptime/SLA report, in percent
+    #[serde(default = "default_sla_target_percent")]
+    pub sla_target_percent: f64,
+    // Finished reports older than this are deleted; 0 keeps them forever
+    #[serde(default)]
+    pub report_retention_days: u32,
+    // Total size allowed for stored report files before the oldest are evicted;
+    // 0 means no limit
+    #[serde(default = "default_report_storage_quota_mb")]
+    pub report_storage_quota_mb: u64,
+    #[serde(default)]
+    pub smtp: SmtpSettings,
+    #[serde(default)]
+    pub sms: SmsGatewaySettings,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SmtpSecurity {
+    // Plain text, for local relays only
+    None,
+    // Plain connection upgraded with STARTTLS, usually port 587
+    #[default]
+    StartTls,
+    // TLS from the first byte, usually port 465
+    Tls,
+}
+
+// Outgoing mail server. An empty host means email is not configured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: String,
+    // Write-only: sealed on save and never sent back. Leaving it empty keeps
+    // the stored password unless `has_password` is cleared.
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    #[serde(default)]
+    pub has_password: bool,
+    pub from_address: String,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        SmtpSettings {
+            host: String::new(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: String::new(),
+            password: String::new(),
+            has_password: false,
+            from_address: "cam-admin@localhost".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SmsBodyFormat {
+    // Sent as application/json
+    #[default]
+    Json,
+    // Sent as application/x-www-form-urlencoded
+    Form,
+}
+
+// Any provider that takes an HTTP POST per message. An empty URL means SMS
+// is not configured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmsGatewaySettings {
+    pub url: String,
+    #[serde(default)]
+    pub body_format: SmsBodyFormat,
+    // Request body with `{{to}}` and `{{message}}` placeholders, which are
+    // escaped to suit the body format
+    pub body_template: String,
+    // Header carrying the provider credentials, such as `Authorization`.
+    // Nothing is sent when it is empty.
+    #[serde(default)]
+    pub auth_header: String,
+    // Write-only, like the SMTP password
+    #[serde(default, skip_serializing)]
+    pub auth_value: String,
+    #[serde(default)]
+    pub has_auth_value: bool,
+    // Messages each phone number may get per hour; 0 turns the limit off
+    #[serde(default)]
+    pub max_per_hour: u32,
+}
+
+impl Default for SmsGatewaySettings {
+    fn default() -> Self {
+        SmsGatewaySettings {
+            url: String::new(),
+            body_format: SmsBodyFormat::Json,
+            body_template: r#"{"to":"{{to}}","message":"{{message}}"}"#.to_string(),
+            auth_header: "Authorization".to_string(),
+            auth_value: String::new(),
+            has_auth_value: false,
+            max_per_hour: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum SmsStatus {
+    Pending,
+    Sent,
+    // Gave up after the last retry
+    Failed,
+    // Dropped because the recipient already got their hourly allowance
+    RateLimited,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmsMessage {
+    pub id: String,
+    pub user_id: String,
+    pub to: String,
+    pub body: String,
+    pub status: SmsStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub sent_at: Option<String>,
+    // HTTP status of the gateway's last answer
+    pub gateway_status: Option<u16>,
+    pub last_error: Option<String>,
+}
+
+// Webhook Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum WebhookEvent {
+    #[serde(rename = "camera.status_changed")]
+    CameraStatusChanged,
+    #[serde(rename = "user.created")]
+    UserCreated,
+    #[serde(rename = "user.updated")]
+    UserUpdated,
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+    #[serde(rename = "report.ready")]
+    ReportReady,
+    #[serde(rename = "alert.raised")]
+    AlertRaised,
+}
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::CameraStatusChanged => "camera.status_changed",
+            WebhookEvent::UserCreated => "user.created",
+            WebhookEvent::UserUpdated => "user.updated",
+            WebhookEvent::UserDeleted => "user.deleted",
+            WebhookEvent::ReportReady => "report.ready",
+            WebhookEvent::AlertRaised => "alert.raised",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(default)]
+    pub created_at: String,
+    // Write-only signing secret, stored encrypted in `sealed_secret`. An empty
+    // one on update keeps the current secret.
+    #[serde(default, skip_serializing)]
+    pub secret: String,
+    #[serde(skip)]
+    pub sealed_secret: Option<SealedSecret>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    // Gave up after the last retry; can be sent again by hand
+    DeadLetter,
+}
+
+// One event sent to one subscription
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event: WebhookEvent,
+    // The exact JSON body, so every attempt sends and signs the same bytes
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub delivered_at: Option<String>,
+    // HTTP status of the receiver's last answer
+    pub response_status: Option<u16>,
+    pub last_error: Option<String>,
+}
+
+// Email Outbox Models
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum EmailKind {
+    Alert,
+    ScheduledReport,
+    PasswordReset,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    // Gave up after the last retry
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutboxEmail {
+    pub id: String,
+    pub kind: EmailKind,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub created_at: String,
+    // Not sent before this time; pushed back after each failed attempt
+    pub next_attempt_at: String,
+    pub sent_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_sla_target_percent() -> f64 {
+    99.5
+}
+
+fn default_report_storage_quota_mb() -> u64 {
+    100
 }
 
 // Global state for mock data
@@ -100,10 +748,36 @@ pub static MOCK_DATA: Lazy<Arc<Mutex<MockData>>> = Lazy::new(|| {
 
 pub struct MockData {
     users: HashMap<String, User>,
+    user_groups: Vec<UserGroup>,
     cameras: HashMap<String, Camera>,
+    sites: Vec<Site>,
+    zones: Vec<Zone>,
+    camera_grants: Vec<CameraGrant>,
+    status_history: Vec<CameraStatusChange>,
     activity_logs: Vec<ActivityLog>,
     reports: Vec<Report>,
+    report_artifacts: HashMap<String, Vec<u8>>,
+    report_schedules: Vec<ReportSchedule>,
+    live_layouts: Vec<LiveLayout>,
+    maintenance_windows: Vec<MaintenanceWindow>,
+    alert_rules: Vec<AlertRule>,
+    alerts: Vec<Alert>,
+    failed_logins: Vec<FailedLogin>,
+    // Argon2 hashes of passwords users have set, by user ID
+    password_hashes: HashMap<String, String>,
+    password_resets: Vec<PasswordReset>,
+    // Signed-in sessions: user ID by hash of the session token
+    sessions: HashMap<String, String>,
+    outbox: Vec<OutboxEmail>,
+    sms_messages: Vec<SmsMessage>,
+    webhooks: Vec<WebhookSubscription>,
+    webhook_deliveries: Vec<WebhookDelivery>,
+    camera_events: Vec<CameraEvent>,
+    event_snapshots: HashMap<String, Vec<u8>>,
     settings: Settings,
+    smtp_password: Option<SealedSecret>,
+    sms_auth_value: Option<SealedSecret>,
+    next_id: u64,
 }
 
 impl MockData {
@@ -114,6 +788,7 @@ impl MockData {
             username: "admin".to_string(),
             name: "Admin User".to_string(),
             email: "admin@example.com".to_string(),
+            phone: "+15550100001".to_string(),
             role: UserRole::SuperAdmin,
             active: true,
             last_login: "2025-02-25T14:30:00Z".to_string(),
@@ -123,6 +798,7 @@ impl MockData {
             username: "jdoe".to_string(),
             name: "John Doe".to_string(),
             email: "jdoe@example.com".to_string(),
+            phone: "+15550100002".to_string(),
             role: UserRole::Admin,
             active: true,
             last_login: "2025-02-24T10:15:00Z".to_string(),
@@ -132,6 +808,7 @@ impl MockData {
             username: "asmith".to_string(),
             name: "Alice Smith".to_string(),
             email: "asmith@example.com".to_string(),
+            phone: String::new(),
             role: UserRole::Viewer,
             active: true,
             last_login: "2025-02-23T09:45:00Z".to_string(),
@@ -147,6 +824,9 @@ impl MockData {
             active: true,
             status: CameraStatus::Online,
             last_update: "2025-02-25T14:35:00Z".to_string(),
+            stream_path: "/stream1".to_string(),
+            zone_id: Some("411".to_string()),
+            ..Camera::default()
         });
         cameras.insert("102".to_string(), Camera {
             id: "102".to_string(),
@@ -157,6 +837,9 @@ impl MockData {
             active: false,
             status: CameraStatus::Offline,
             last_update: "2025-02-25T10:20:00Z".to_string(),
+            stream_path: "/stream1".to_string(),
+            zone_id: Some("412".to_string()),
+            ..Camera::default()
         });
         cameras.insert("103".to_string(), Camera {
             id: "103".to_string(),
@@ -167,6 +850,9 @@ impl MockData {
             active: true,
             status: CameraStatus::Online,
             last_update: "2025-02-25T14:40:00Z".to_string(),
+            stream_path: "/stream1".to_string(),
+            zone_id: Some("421".to_string()),
+            ..Camera::default()
         });
         cameras.insert("104".to_string(), Camera {
             id: "104".to_string(),
@@ -177,8 +863,81 @@ impl MockData {
             active: true,
             status: CameraStatus::Maintenance,
             last_update: "2025-02-25T08:15:00Z".to_string(),
+            stream_path: "/stream1".to_string(),
+            zone_id: Some("413".to_string()),
+            ..Camera::default()
         });
 
+        let site = |id: &str, name: &str, address: &str| Site {
+            id: id.to_string(),
+            name: name.to_string(),
+            address: address.to_string(),
+        };
+        let sites = vec![
+            site("401", "Headquarters", "1 Main Street"),
+            site("402", "Parking Facility", "3 Main Street"),
+        ];
+
+        let zone = |id: &str, site_id: &str, name: &str| Zone {
+            id: id.to_string(),
+            site_id: site_id.to_string(),
+            name: name.to_string(),
+        };
+        let zones = vec![
+            zone("411", "401", "Main Entrance"),
+            zone("412", "401", "East Wing"),
+            zone("413", "401", "Main Building"),
+            zone("421", "402", "North Side"),
+        ];
+
+        let user_groups = vec![
+            UserGroup {
+                id: "4601".to_string(),
+                name: "Night Shift North".to_string(),
+                role: UserRole::Viewer,
+                members: Vec::new(),
+            },
+        ];
+
+        let camera_grants = vec![
+            CameraGrant {
+                id: "4501".to_string(),
+                subject: GrantSubject::User("3".to_string()),
+                target: GrantTarget::Site("401".to_string()),
+                created_by: "1".to_string(),
+            },
+            CameraGrant {
+                id: "4502".to_string(),
+                subject: GrantSubject::Group("4601".to_string()),
+                target: GrantTarget::Zone("421".to_string()),
+                created_by: "1".to_string(),
+            },
+        ];
+
+        let status_change = |id: &str, camera_id: &str, previous: Option<CameraStatus>, status: CameraStatus, timestamp: &str, acknowledged_by: Option<&str>| {
+            CameraStatusChange {
+                id: id.to_string(),
+                camera_id: camera_id.to_string(),
+                previous_status: previous,
+                status,
+                timestamp: timestamp.to_string(),
+                acknowledged_by: acknowledged_by.map(|u| u.to_string()),
+                acknowledged_at: acknowledged_by.map(|_| timestamp.to_string()),
+            }
+        };
+        let status_history = vec![
+            status_change("1501", "101", None, CameraStatus::Online, "2025-02-01T00:00:00Z", None),
+            status_change("1502", "102", None, CameraStatus::Online, "2025-02-01T00:00:00Z", None),
+            status_change("1503", "103", None, CameraStatus::Online, "2025-02-01T00:00:00Z", None),
+            status_change("1504", "104", None, CameraStatus::Online, "2025-02-01T00:00:00Z", None),
+            status_change("1505", "103", Some(CameraStatus::Online), CameraStatus::Offline, "2025-02-12T02:10:00Z", Some("2")),
+            status_change("1506", "103", Some(CameraStatus::Offline), CameraStatus::Online, "2025-02-12T03:40:00Z", None),
+            status_change("1507", "101", Some(CameraStatus::Online), CameraStatus::Offline, "2025-02-20T22:05:00Z", None),
+            status_change("1508", "101", Some(CameraStatus::Offline), CameraStatus::Online, "2025-02-20T22:35:00Z", None),
+            status_change("1509", "104", Some(CameraStatus::Online), CameraStatus::Maintenance, "2025-02-25T08:15:00Z", None),
+            status_change("1510", "102", Some(CameraStatus::Online), CameraStatus::Offline, "2025-02-25T10:20:00Z", None),
+        ];
+
         let activity_logs = vec![
             ActivityLog {
                 id: "1001".to_string(),
@@ -224,6 +983,10 @@ impl MockData {
                 period: "February 2025".to_string(),
                 format: ReportFormat::PDF,
                 url: "/api/reports/2001/download".to_string(),
+                status: ReportStatus::Done,
+                progress: 100,
+                error: None,
+                site_id: None,
             },
             Report {
                 id: "2002".to_string(),
@@ -234,6 +997,10 @@ impl MockData {
                 period: "Q1 2025".to_string(),
                 format: ReportFormat::CSV,
                 url: "/api/reports/2002/download".to_string(),
+                status: ReportStatus::Done,
+                progress: 100,
+                error: None,
+                site_id: None,
             },
             Report {
                 id: "2003".to_string(),
@@ -244,6 +1011,55 @@ impl MockData {
                 period: "January 2025".to_string(),
                 format: ReportFormat::PDF,
                 url: "/api/reports/2003/download".to_string(),
+                status: ReportStatus::Done,
+                progress: 100,
+                error: None,
+                site_id: None,
+            },
+        ];
+
+        let report_schedules = vec![
+            ReportSchedule {
+                id: "2501".to_string(),
+                name: "Monthly Camera Status".to_string(),
+                cron: "0 6 1 * *".to_string(),
+                type_: ReportType::CameraStatus,
+                format: ReportFormat::PDF,
+                period_rule: PeriodRule::PreviousMonth,
+                recipients: vec!["admin@example.com".to_string(), "jdoe@example.com".to_string()],
+                enabled: true,
+                created_by: "1".to_string(),
+                site_id: None,
+                last_run: None,
+                next_run: None,
+                last_report_id: None,
+            },
+        ];
+
+        let alert_rules = vec![
+            AlertRule {
+                id: "4701".to_string(),
+                name: "Camera offline".to_string(),
+                condition: AlertCondition::CameraOffline { minutes: 5 },
+                severity: AlertSeverity::Critical,
+                cooldown_minutes: 30,
+                enabled: true,
+            },
+            AlertRule {
+                id: "4702".to_string(),
+                name: "Repeated failed sign-ins".to_string(),
+                condition: AlertCondition::FailedLogins { count: 5, minutes: 10 },
+                severity: AlertSeverity::Warning,
+                cooldown_minutes: 15,
+                enabled: true,
+            },
+            AlertRule {
+                id: "4703".to_string(),
+                name: "License expiring".to_string(),
+                condition: AlertCondition::LicenseExpiring { days: 30 },
+                severity: AlertSeverity::Warning,
+                cooldown_minutes: 24 * 60,
+                enabled: true,
             },
         ];
 
@@ -257,20 +1073,69 @@ impl MockData {
             sms_alerts: false,
             refresh_interval: 10,
             app_version: "1.0.0".to_string(),
+            sla_target_percent: default_sla_target_percent(),
+            report_retention_days: 0,
+            report_storage_quota_mb: default_report_storage_quota_mb(),
+            smtp: SmtpSettings::default(),
+            sms: SmsGatewaySettings::default(),
         };
 
         MockData {
             users,
+            user_groups,
             cameras,
+            sites,
+            zones,
+            camera_grants,
+            status_history,
             activity_logs,
             reports,
+            report_artifacts: HashMap::new(),
+            report_schedules,
+            live_layouts: Vec::new(),
+            maintenance_windows: Vec::new(),
+            alert_rules,
+            alerts: Vec::new(),
+            failed_logins: Vec::new(),
+            password_hashes: HashMap::new(),
+            password_resets: Vec::new(),
+            sessions: HashMap::new(),
+            outbox: Vec::new(),
+            sms_messages: Vec::new(),
+            webhooks: Vec::new(),
+            webhook_deliveries: Vec::new(),
+            camera_events: Vec::new(),
+            event_snapshots: HashMap::new(),
             settings,
+            smtp_password: None,
+            sms_auth_value: None,
+            next_id: 3000,
         }
     }
 }
 
 // Functions to access and manipulate mock data
 
+// Current time in the same RFC 3339 format used by the seeded records
+pub fn now_timestamp() -> String {
+    format_timestamp(chrono::Utc::now())
+}
+
+pub fn format_timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+pub fn parse_timestamp(timestamp: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&chrono::Utc))
+}
+
+// Allocate a fresh ID for records created by the server itself
+pub fn next_id() -> String {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.next_id += 1;
+    mock_data.next_id.to_string()
+}
+
 // Users
 pub fn get_users() -> Vec<User> {
     let mock_data = MOCK_DATA.lock().unwrap();
@@ -285,6 +1150,7 @@ pub fn get_user(id: &str) -> Option<User> {
 pub fn create_user(user: User) -> User {
     let mut mock_data = MOCK_DATA.lock().unwrap();
     mock_data.users.insert(user.id.clone(), user.clone());
+    change_feed::created(Entity::User(user.clone()));
     user
 }
 
@@ -292,64 +1158,534 @@ pub fn update_user(id: &str, user: User) -> Option<User> {
     let mut mock_data = MOCK_DATA.lock().unwrap();
     if mock_data.users.contains_key(id) {
         mock_data.users.insert(id.to_string(), user.clone());
+        change_feed::updated(Entity::User(user.clone()));
         Some(user)
     } else {
         None
     }
 }
 
+// Also drops the user's group memberships and personal grants
 pub fn delete_user(id: &str) -> bool {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.users.remove(id).is_some()
+    for group in mock_data.user_groups.iter_mut() {
+        group.members.retain(|member| member != id);
+    }
+    let subject = GrantSubject::User(id.to_string());
+    mock_data.camera_grants.retain(|g| g.subject != subject);
+    let Some(user) = mock_data.users.remove(id) else { return false };
+    change_feed::deleted(Entity::User(user));
+    true
 }
 
-// Cameras
-pub fn get_cameras() -> Vec<Camera> {
+// User groups
+pub fn get_user_groups() -> Vec<UserGroup> {
     let mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.cameras.values().cloned().collect()
+    mock_data.user_groups.clone()
 }
 
-pub fn get_camera(id: &str) -> Option<Camera> {
+pub fn get_user_group(id: &str) -> Option<UserGroup> {
     let mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.cameras.get(id).cloned()
+    mock_data.user_groups.iter().find(|g| g.id == id).cloned()
 }
 
-pub fn create_camera(camera: Camera) -> Camera {
-    let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.cameras.insert(camera.id.clone(), camera.clone());
-    camera
+// Groups the user is a member of
+pub fn get_groups_of_user(user_id: &str) -> Vec<UserGroup> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .user_groups
+        .iter()
+        .filter(|g| g.members.iter().any(|member| member == user_id))
+        .cloned()
+        .collect()
 }
 
-pub fn update_camera(id: &str, camera: Camera) -> Option<Camera> {
+pub fn create_user_group(group: UserGroup) -> UserGroup {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    if mock_data.cameras.contains_key(id) {
-        mock_data.cameras.insert(id.to_string(), camera.clone());
-        Some(camera)
-    } else {
-        None
-    }
+    mock_data.user_groups.push(group.clone());
+    group
 }
 
-pub fn delete_camera(id: &str) -> bool {
+// Renames the group or changes its role; membership changes go through
+// add_group_member and remove_group_member
+pub fn update_user_group(id: &str, mut group: UserGroup) -> Option<UserGroup> {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.cameras.remove(id).is_some()
+    let existing = mock_data.user_groups.iter_mut().find(|g| g.id == id)?;
+    group.members = existing.members.clone();
+    *existing = group.clone();
+    Some(group)
 }
 
-// Activity Logs
-pub fn get_activity_logs() -> Vec<ActivityLog> {
-    let mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.activity_logs.clone()
+pub fn delete_user_group(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.user_groups.len();
+    mock_data.user_groups.retain(|g| g.id != id);
+    let subject = GrantSubject::Group(id.to_string());
+    mock_data.camera_grants.retain(|g| g.subject != subject);
+    mock_data.user_groups.len() != before
 }
 
-pub fn add_activity_log(log: ActivityLog) {
+pub fn add_group_member(group_id: &str, user_id: &str) -> Option<UserGroup> {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.activity_logs.push(log);
+    let group = mock_data.user_groups.iter_mut().find(|g| g.id == group_id)?;
+    if !group.members.iter().any(|member| member == user_id) {
+        group.members.push(user_id.to_string());
+    }
+    Some(group.clone())
 }
 
-// Reports
-pub fn get_reports() -> Vec<Report> {
-    let mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.reports.clone()
+pub fn remove_group_member(group_id: &str, user_id: &str) -> Option<UserGroup> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let group = mock_data.user_groups.iter_mut().find(|g| g.id == group_id)?;
+    group.members.retain(|member| member != user_id);
+    Some(group.clone())
+}
+
+// Cameras
+// Replace the camera's plaintext credentials with their sealed form. Sealing
+// happens under the data lock so it can't race a key rotation.
+fn seal_credentials(camera: &mut Camera, credentials: Option<CameraCredentials>) {
+    let credentials = credentials.filter(|c| !c.username.is_empty());
+    camera.has_credentials = credentials.is_some();
+    camera.sealed_credentials = credentials.map(|c| {
+        secrets::seal(&serde_json::to_vec(&c).expect("credentials serialize"))
+    });
+    camera.username.clear();
+    camera.password.clear();
+}
+
+// Copy of a stored camera with its credentials decrypted for talking to the device
+fn with_credentials(camera: &Camera) -> Camera {
+    let mut camera = camera.clone();
+    let Some(sealed) = &camera.sealed_credentials else { return camera };
+
+    let credentials = secrets::open(sealed)
+        .map_err(|e| e.to_string())
+        .and_then(|plaintext| serde_json::from_slice::<CameraCredentials>(&plaintext).map_err(|e| e.to_string()));
+    match credentials {
+        Ok(credentials) => {
+            camera.username = credentials.username;
+            camera.password = credentials.password;
+        }
+        Err(e) => eprintln!("Cannot read credentials for camera {}: {}", camera.id, e),
+    }
+    camera
+}
+
+pub fn get_cameras() -> Vec<Camera> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.cameras.values().map(with_credentials).collect()
+}
+
+pub fn get_camera(id: &str) -> Option<Camera> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.cameras.get(id).map(with_credentials)
+}
+
+pub fn create_camera(mut camera: Camera) -> Camera {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let credentials = CameraCredentials {
+        username: camera.username.clone(),
+        password: camera.password.clone(),
+    };
+    seal_credentials(&mut camera, Some(credentials));
+    camera.event_token_hash = None;
+    camera.has_event_token = false;
+    mock_data.cameras.insert(camera.id.clone(), camera.clone());
+    change_feed::created(Entity::Camera(Box::new(camera.clone())));
+    mock_data.record_status_change(&camera.id, None, camera.status.clone());
+    camera
+}
+
+// Credentials are kept as they are; they only change through set_camera_credentials
+pub fn update_camera(id: &str, mut camera: Camera) -> Option<Camera> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.cameras.get(id)?;
+    let previous = existing.status.clone();
+    camera.sealed_credentials = existing.sealed_credentials.clone();
+    camera.has_credentials = existing.has_credentials;
+    camera.event_token_hash = existing.event_token_hash.clone();
+    camera.has_event_token = existing.has_event_token;
+    camera.username.clear();
+    camera.password.clear();
+
+    mock_data.cameras.insert(id.to_string(), camera.clone());
+    change_feed::updated(Entity::Camera(Box::new(camera.clone())));
+    if previous != camera.status {
+        mock_data.record_status_change(id, Some(previous), camera.status.clone());
+    }
+    Some(camera)
+}
+
+// Store the hash of a newly issued event token, or revoke it with None
+pub fn set_camera_event_token(id: &str, token_hash: Option<String>) -> Option<Camera> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let camera = mock_data.cameras.get_mut(id)?;
+    camera.has_event_token = token_hash.is_some();
+    camera.event_token_hash = token_hash;
+    change_feed::updated(Entity::Camera(Box::new(camera.clone())));
+    Some(camera.clone())
+}
+
+// Store new credentials for a camera, or clear them when the username is empty
+pub fn set_camera_credentials(id: &str, credentials: CameraCredentials) -> Option<Camera> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let camera = mock_data.cameras.get_mut(id)?;
+    seal_credentials(camera, Some(credentials));
+    change_feed::updated(Entity::Camera(Box::new(camera.clone())));
+    Some(camera.clone())
+}
+
+// Re-encrypt every stored camera secret with `new_key` and make it the active
+// key. Nothing changes unless all secrets could be decrypted with the old key.
+pub fn rotate_master_key(new_key: MasterKey) -> Result<usize, SecretError> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+
+    let mut resealed = Vec::new();
+    for camera in mock_data.cameras.values() {
+        if let Some(sealed) = &camera.sealed_credentials {
+            let plaintext = secrets::open(sealed)?;
+            resealed.push((camera.id.clone(), secrets::seal_with(&new_key, &plaintext)));
+        }
+    }
+
+    let smtp_password = match &mock_data.smtp_password {
+        Some(sealed) => Some(secrets::seal_with(&new_key, &secrets::open(sealed)?)),
+        None => None,
+    };
+    let sms_auth_value = match &mock_data.sms_auth_value {
+        Some(sealed) => Some(secrets::seal_with(&new_key, &secrets::open(sealed)?)),
+        None => None,
+    };
+    let mut webhook_secrets = Vec::new();
+    for webhook in &mock_data.webhooks {
+        if let Some(sealed) = &webhook.sealed_secret {
+            webhook_secrets.push((webhook.id.clone(), secrets::seal_with(&new_key, &secrets::open(sealed)?)));
+        }
+    }
+
+    let count = resealed.len()
+        + usize::from(smtp_password.is_some())
+        + usize::from(sms_auth_value.is_some())
+        + webhook_secrets.len();
+    for (id, sealed) in resealed {
+        if let Some(camera) = mock_data.cameras.get_mut(&id) {
+            camera.sealed_credentials = Some(sealed);
+        }
+    }
+    mock_data.smtp_password = smtp_password;
+    mock_data.sms_auth_value = sms_auth_value;
+    for (id, sealed) in webhook_secrets {
+        if let Some(webhook) = mock_data.webhooks.iter_mut().find(|w| w.id == id) {
+            webhook.sealed_secret = Some(sealed);
+        }
+    }
+    secrets::set_master_key(new_key);
+    Ok(count)
+}
+
+// Apply the result of a health probe and stamp `last_update`. Cameras in
+// maintenance keep their status. Returns the previous and new status.
+pub fn record_health_check(id: &str, result: Result<Option<StreamInfo>, ProbeError>) -> Option<(CameraStatus, CameraStatus)> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let camera = mock_data.cameras.get_mut(id)?;
+    let previous = camera.status.clone();
+
+    let reachable = result.is_ok();
+    match result {
+        Ok(stream_info) => {
+            // A plain TCP probe learns nothing about the stream, so keep what we had
+            if stream_info.is_some() {
+                camera.stream_info = stream_info;
+            }
+            camera.probe_error = None;
+        }
+        Err(error) => camera.probe_error = Some(error),
+    }
+
+    if previous != CameraStatus::Maintenance {
+        camera.status = if reachable { CameraStatus::Online } else { CameraStatus::Offline };
+    }
+    camera.last_update = now_timestamp();
+    let status = camera.status.clone();
+
+    if previous != status {
+        mock_data.record_status_change(id, Some(previous.clone()), status.clone());
+    }
+    Some((previous, status))
+}
+
+// Store what was learned from the camera's ONVIF services. A camera without a
+// stream path adopts the one from its first profile's stream URI.
+pub fn update_camera_details(
+    id: &str,
+    device_info: DeviceInfo,
+    network_interfaces: Vec<NetworkInterface>,
+    media_profiles: Vec<MediaProfile>,
+) -> Option<Camera> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let camera = mock_data.cameras.get_mut(id)?;
+
+    if camera.stream_path.is_empty() {
+        let uri = media_profiles.iter().find_map(|p| p.stream_uri.as_deref());
+        if let Some(path) = uri.and_then(|u| u.split("://").nth(1)).and_then(|rest| rest.find('/').map(|i| &rest[i..])) {
+            camera.stream_path = path.to_string();
+        }
+    }
+    camera.device_info = Some(device_info);
+    camera.network_interfaces = network_interfaces;
+    camera.media_profiles = media_profiles;
+    camera.info_updated_at = Some(now_timestamp());
+    change_feed::updated(Entity::Camera(Box::new(camera.clone())));
+    Some(camera.clone())
+}
+
+// Also drops the camera's events
+pub fn delete_camera(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.revoke_grants(&GrantTarget::Camera(id.to_string()));
+    let dropped: Vec<String> = mock_data.camera_events.iter().filter(|e| e.camera_id == id).map(|e| e.id.clone()).collect();
+    for event_id in &dropped {
+        mock_data.event_snapshots.remove(event_id);
+    }
+    mock_data.camera_events.retain(|e| e.camera_id != id);
+    let Some(camera) = mock_data.cameras.remove(id) else { return false };
+    change_feed::deleted(Entity::Camera(Box::new(camera)));
+    true
+}
+
+impl MockData {
+    fn camera_matches(&self, camera: &Camera, filter: &CameraFilter) -> bool {
+        let zone_id = camera.zone_id.as_deref();
+        if filter.zone_id.is_some() && filter.zone_id.as_deref() != zone_id {
+            return false;
+        }
+        match &filter.site_id {
+            Some(site_id) => self.zones.iter().any(|z| Some(z.id.as_str()) == zone_id && &z.site_id == site_id),
+            None => true,
+        }
+    }
+}
+
+pub fn get_cameras_in(filter: &CameraFilter) -> Vec<Camera> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .cameras
+        .values()
+        .filter(|camera| mock_data.camera_matches(camera, filter))
+        .map(with_credentials)
+        .collect()
+}
+
+// Sites
+pub fn get_sites() -> Vec<Site> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sites.clone()
+}
+
+pub fn get_site(id: &str) -> Option<Site> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sites.iter().find(|s| s.id == id).cloned()
+}
+
+pub fn create_site(site: Site) -> Site {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sites.push(site.clone());
+    site
+}
+
+pub fn update_site(id: &str, site: Site) -> Option<Site> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.sites.iter_mut().find(|s| s.id == id)?;
+    *existing = site.clone();
+    Some(site)
+}
+
+pub fn delete_site(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.sites.len();
+    mock_data.sites.retain(|s| s.id != id);
+    mock_data.revoke_grants(&GrantTarget::Site(id.to_string()));
+    mock_data.sites.len() != before
+}
+
+// Zones, optionally limited to one site
+pub fn get_zones(site_id: Option<&str>) -> Vec<Zone> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .zones
+        .iter()
+        .filter(|z| site_id.is_none_or(|site_id| z.site_id == site_id))
+        .cloned()
+        .collect()
+}
+
+pub fn get_zone(id: &str) -> Option<Zone> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.zones.iter().find(|z| z.id == id).cloned()
+}
+
+pub fn create_zone(zone: Zone) -> Zone {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.zones.push(zone.clone());
+    zone
+}
+
+pub fn update_zone(id: &str, zone: Zone) -> Option<Zone> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.zones.iter_mut().find(|z| z.id == id)?;
+    *existing = zone.clone();
+    Some(zone)
+}
+
+// Cameras in the zone become unassigned rather than being deleted with it
+pub fn delete_zone(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.zones.len();
+    mock_data.zones.retain(|z| z.id != id);
+    if mock_data.zones.len() == before {
+        return false;
+    }
+    mock_data.revoke_grants(&GrantTarget::Zone(id.to_string()));
+
+    for camera in mock_data.cameras.values_mut() {
+        if camera.zone_id.as_deref() == Some(id) {
+            camera.zone_id = None;
+        }
+    }
+    true
+}
+
+// Camera grants
+impl MockData {
+    fn revoke_grants(&mut self, target: &GrantTarget) {
+        self.camera_grants.retain(|g| &g.target != target);
+    }
+
+    fn grant_covers(&self, target: &GrantTarget, camera: &Camera) -> bool {
+        match target {
+            GrantTarget::Camera(id) => &camera.id == id,
+            GrantTarget::Zone(id) => camera.zone_id.as_ref() == Some(id),
+            GrantTarget::Site(id) => {
+                let filter = CameraFilter { site_id: Some(id.clone()), zone_id: None };
+                self.camera_matches(camera, &filter)
+            }
+        }
+    }
+}
+
+// Grants, optionally limited to one subject
+pub fn get_camera_grants(subject: Option<&GrantSubject>) -> Vec<CameraGrant> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .camera_grants
+        .iter()
+        .filter(|g| subject.is_none_or(|subject| &g.subject == subject))
+        .cloned()
+        .collect()
+}
+
+pub fn create_camera_grant(grant: CameraGrant) -> CameraGrant {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.camera_grants.push(grant.clone());
+    grant
+}
+
+pub fn delete_camera_grant(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.camera_grants.len();
+    mock_data.camera_grants.retain(|g| g.id != id);
+    mock_data.camera_grants.len() != before
+}
+
+// The grants given to any of `subjects`, each with the IDs of the cameras it
+// currently covers; zone and site grants follow the cameras' current zones
+pub fn resolve_grants(subjects: &[GrantSubject]) -> Vec<(CameraGrant, Vec<String>)> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .camera_grants
+        .iter()
+        .filter(|g| subjects.contains(&g.subject))
+        .map(|grant| {
+            let camera_ids = mock_data
+                .cameras
+                .values()
+                .filter(|camera| mock_data.grant_covers(&grant.target, camera))
+                .map(|camera| camera.id.clone())
+                .collect();
+            (grant.clone(), camera_ids)
+        })
+        .collect()
+}
+
+// Camera status history
+impl MockData {
+    fn record_status_change(&mut self, camera_id: &str, previous_status: Option<CameraStatus>, status: CameraStatus) {
+        self.next_id += 1;
+        let change = CameraStatusChange {
+            id: self.next_id.to_string(),
+            camera_id: camera_id.to_string(),
+            previous_status,
+            status,
+            timestamp: now_timestamp(),
+            acknowledged_by: None,
+            acknowledged_at: None,
+        };
+        let camera_name = self.cameras.get(camera_id).map(|camera| camera.name.clone()).unwrap_or_default();
+        self.queue_webhook_event(WebhookEvent::CameraStatusChanged, serde_json::json!({
+            "camera_id": change.camera_id,
+            "camera_name": camera_name,
+            "previous_status": change.previous_status,
+            "status": change.status,
+            "timestamp": change.timestamp,
+        }));
+        change_feed::publish(FeedMessage::CameraStatus {
+            camera_id: change.camera_id.clone(),
+            previous_status: change.previous_status.clone(),
+            status: change.status.clone(),
+            timestamp: change.timestamp.clone(),
+        });
+        self.status_history.push(change);
+    }
+}
+
+// Status changes ordered by time, optionally limited to one camera
+pub fn get_status_history(camera_id: Option<&str>) -> Vec<CameraStatusChange> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut history: Vec<CameraStatusChange> = mock_data
+        .status_history
+        .iter()
+        .filter(|change| camera_id.is_none_or(|id| change.camera_id == id))
+        .cloned()
+        .collect();
+    history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    history
+}
+
+pub fn acknowledge_status_change(id: &str, user_id: &str) -> Option<CameraStatusChange> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let change = mock_data.status_history.iter_mut().find(|c| c.id == id)?;
+    change.acknowledged_by = Some(user_id.to_string());
+    change.acknowledged_at = Some(now_timestamp());
+    Some(change.clone())
+}
+
+// Activity Logs
+pub fn get_activity_logs() -> Vec<ActivityLog> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.activity_logs.clone()
+}
+
+pub fn add_activity_log(log: ActivityLog) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.activity_logs.push(log.clone());
+    change_feed::created(Entity::ActivityLog(log));
+}
+
+// Reports
+pub fn get_reports() -> Vec<Report> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.reports.clone()
 }
 
 pub fn get_report(id: &str) -> Option<Report> {
@@ -359,7 +1695,436 @@ pub fn get_report(id: &str) -> Option<Report> {
 
 pub fn add_report(report: Report) {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.reports.push(report);
+    mock_data.reports.push(report.clone());
+    change_feed::created(Entity::Report(report));
+}
+
+pub fn update_report_status(id: &str, status: ReportStatus, progress: u8, error: Option<String>) -> Option<Report> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let report = mock_data.reports.iter_mut().find(|r| r.id == id)?;
+    report.status = status;
+    report.progress = progress;
+    report.error = error;
+    change_feed::updated(Entity::Report(report.clone()));
+    Some(report.clone())
+}
+
+pub fn store_report_artifact(id: &str, data: Vec<u8>) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    // The report may have been deleted while its job was still running
+    if mock_data.reports.iter().any(|r| r.id == id) {
+        mock_data.report_artifacts.insert(id.to_string(), data);
+    }
+}
+
+pub fn get_report_artifact(id: &str) -> Option<Vec<u8>> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.report_artifacts.get(id).cloned()
+}
+
+// Size in bytes of every stored report file, keyed by report ID
+pub fn get_report_artifact_sizes() -> HashMap<String, u64> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .report_artifacts
+        .iter()
+        .map(|(id, data)| (id.clone(), data.len() as u64))
+        .collect()
+}
+
+// Remove a report's metadata together with its stored file
+pub fn delete_report(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.report_artifacts.remove(id);
+    let Some(index) = mock_data.reports.iter().position(|r| r.id == id) else { return false };
+    change_feed::deleted(Entity::Report(mock_data.reports.remove(index)));
+    true
+}
+
+// Report Schedules
+pub fn get_report_schedules() -> Vec<ReportSchedule> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.report_schedules.clone()
+}
+
+pub fn get_report_schedule(id: &str) -> Option<ReportSchedule> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.report_schedules.iter().find(|s| s.id == id).cloned()
+}
+
+pub fn create_report_schedule(schedule: ReportSchedule) -> ReportSchedule {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.report_schedules.push(schedule.clone());
+    schedule
+}
+
+pub fn update_report_schedule(id: &str, schedule: ReportSchedule) -> Option<ReportSchedule> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.report_schedules.iter_mut().find(|s| s.id == id)?;
+    *existing = schedule.clone();
+    Some(schedule)
+}
+
+pub fn delete_report_schedule(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.report_schedules.len();
+    mock_data.report_schedules.retain(|s| s.id != id);
+    mock_data.report_schedules.len() != before
+}
+
+pub fn record_schedule_run(id: &str, last_run: Option<String>, next_run: Option<String>, report_id: Option<String>) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    if let Some(schedule) = mock_data.report_schedules.iter_mut().find(|s| s.id == id) {
+        if last_run.is_some() {
+            schedule.last_run = last_run;
+        }
+        if report_id.is_some() {
+            schedule.last_report_id = report_id;
+        }
+        schedule.next_run = next_run;
+    }
+}
+
+// Live View layouts
+pub fn get_live_layouts() -> Vec<LiveLayout> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.live_layouts.clone()
+}
+
+pub fn create_live_layout(layout: LiveLayout) -> LiveLayout {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.live_layouts.push(layout.clone());
+    layout
+}
+
+pub fn update_live_layout(id: &str, layout: LiveLayout) -> Option<LiveLayout> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.live_layouts.iter_mut().find(|l| l.id == id)?;
+    *existing = layout.clone();
+    Some(layout)
+}
+
+pub fn delete_live_layout(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.live_layouts.len();
+    mock_data.live_layouts.retain(|l| l.id != id);
+    mock_data.live_layouts.len() != before
+}
+
+// Maintenance windows
+impl MockData {
+    fn maintenance_covers(target: &MaintenanceTarget, camera: &Camera) -> bool {
+        match target {
+            MaintenanceTarget::Camera(id) => &camera.id == id,
+            MaintenanceTarget::Zone(id) => camera.zone_id.as_deref() == Some(id.as_str()),
+        }
+    }
+}
+
+// Windows ordered by start time
+pub fn get_maintenance_windows() -> Vec<MaintenanceWindow> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut windows = mock_data.maintenance_windows.clone();
+    windows.sort_by(|a, b| a.start.cmp(&b.start));
+    windows
+}
+
+pub fn get_maintenance_window(id: &str) -> Option<MaintenanceWindow> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.maintenance_windows.iter().find(|w| w.id == id).cloned()
+}
+
+// Cameras a window applies to right now
+pub fn get_maintenance_cameras(target: &MaintenanceTarget) -> Vec<String> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .cameras
+        .values()
+        .filter(|camera| MockData::maintenance_covers(target, camera))
+        .map(|camera| camera.id.clone())
+        .collect()
+}
+
+pub fn create_maintenance_window(window: MaintenanceWindow) -> MaintenanceWindow {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.maintenance_windows.push(window.clone());
+    window
+}
+
+pub fn update_maintenance_window(id: &str, window: MaintenanceWindow) -> Option<MaintenanceWindow> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.maintenance_windows.iter_mut().find(|w| w.id == id)?;
+    *existing = window.clone();
+    Some(window)
+}
+
+pub fn delete_maintenance_window(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.maintenance_windows.len();
+    mock_data.maintenance_windows.retain(|w| w.id != id);
+    mock_data.maintenance_windows.len() != before
+}
+
+// Put every camera the window covers into maintenance. Cameras already in
+// maintenance are left alone so the window doesn't take them out of it later.
+pub fn start_maintenance_window(id: &str) -> Option<MaintenanceWindow> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let target = mock_data.maintenance_windows.iter().find(|w| w.id == id)?.target.clone();
+
+    let ids: Vec<String> = mock_data
+        .cameras
+        .values()
+        .filter(|camera| MockData::maintenance_covers(&target, camera) && camera.status != CameraStatus::Maintenance)
+        .map(|camera| camera.id.clone())
+        .collect();
+    for camera_id in &ids {
+        let camera = mock_data.cameras.get_mut(camera_id)?;
+        let previous = camera.status.clone();
+        camera.status = CameraStatus::Maintenance;
+        camera.last_update = now_timestamp();
+        mock_data.record_status_change(camera_id, Some(previous), CameraStatus::Maintenance);
+    }
+
+    let window = mock_data.maintenance_windows.iter_mut().find(|w| w.id == id)?;
+    window.state = MaintenanceState::Active;
+    window.affected = ids;
+    Some(window.clone())
+}
+
+// Take the window's cameras out of maintenance, back to whatever the last
+// health probe found. A camera still covered by another active window is
+// handed over to that window instead. Returns the window and the cameras
+// that were brought back.
+pub fn end_maintenance_window(id: &str) -> Option<(MaintenanceWindow, Vec<String>)> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let index = mock_data.maintenance_windows.iter().position(|w| w.id == id)?;
+    let affected = std::mem::take(&mut mock_data.maintenance_windows[index].affected);
+    mock_data.maintenance_windows[index].state = MaintenanceState::Completed;
+
+    let mut restored = Vec::new();
+    for camera_id in affected {
+        let Some(camera) = mock_data.cameras.get(&camera_id).cloned() else { continue };
+        if camera.status != CameraStatus::Maintenance {
+            continue;
+        }
+
+        let other = mock_data.maintenance_windows.iter_mut().find(|w| {
+            w.id != id && w.state == MaintenanceState::Active && MockData::maintenance_covers(&w.target, &camera)
+        });
+        if let Some(other) = other {
+            other.affected.push(camera_id);
+            continue;
+        }
+
+        let status = if camera.probe_error.is_some() { CameraStatus::Offline } else { CameraStatus::Online };
+        if let Some(camera) = mock_data.cameras.get_mut(&camera_id) {
+            camera.status = status.clone();
+            camera.last_update = now_timestamp();
+        }
+        mock_data.record_status_change(&camera_id, Some(CameraStatus::Maintenance), status);
+        restored.push(camera_id);
+    }
+
+    Some((mock_data.maintenance_windows[index].clone(), restored))
+}
+
+// When the camera's current outage began, according to its status history
+pub fn offline_since(camera_id: &str) -> Option<String> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .status_history
+        .iter()
+        .filter(|change| change.camera_id == camera_id)
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+        .filter(|change| change.status == CameraStatus::Offline)
+        .map(|change| change.timestamp.clone())
+}
+
+// Failed sign-ins are kept for a day, long enough for any alert rule window
+pub fn record_failed_login(username: &str) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let now = chrono::Utc::now();
+    mock_data.failed_logins.retain(|f| now - f.timestamp < chrono::Duration::days(1));
+    mock_data.failed_logins.push(FailedLogin { username: username.to_string(), timestamp: now });
+}
+
+// Users who never set a password still sign in with the mock one
+pub fn get_password_hash(user_id: &str) -> Option<String> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.password_hashes.get(user_id).cloned()
+}
+
+pub fn set_password_hash(user_id: &str, hash: String) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.password_hashes.insert(user_id.to_string(), hash);
+}
+
+// A new link replaces any earlier one for the same user
+pub fn add_password_reset(reset: PasswordReset) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let now = chrono::Utc::now();
+    mock_data.password_resets.retain(|r| r.user_id != reset.user_id && r.expires_at > now);
+    mock_data.password_resets.push(reset);
+}
+
+// Remove and return the reset with this token hash, so each link works once
+pub fn take_password_reset(token_hash: &str) -> Option<PasswordReset> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let index = mock_data.password_resets.iter().position(|r| r.token_hash == token_hash)?;
+    Some(mock_data.password_resets.remove(index))
+}
+
+pub fn add_session(token_hash: String, user_id: &str) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sessions.insert(token_hash, user_id.to_string());
+}
+
+pub fn get_session_user_id(token_hash: &str) -> Option<String> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sessions.get(token_hash).cloned()
+}
+
+pub fn remove_session(token_hash: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sessions.remove(token_hash).is_some()
+}
+
+// Sign the user out everywhere
+pub fn remove_user_sessions(user_id: &str) {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sessions.retain(|_, session_user| session_user != user_id);
+}
+
+pub fn failed_logins_since(since: chrono::DateTime<chrono::Utc>) -> Vec<FailedLogin> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.failed_logins.iter().filter(|f| f.timestamp >= since).cloned().collect()
+}
+
+// Alert rules
+pub fn get_alert_rules() -> Vec<AlertRule> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.alert_rules.clone()
+}
+
+pub fn get_alert_rule(id: &str) -> Option<AlertRule> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.alert_rules.iter().find(|r| r.id == id).cloned()
+}
+
+pub fn create_alert_rule(rule: AlertRule) -> AlertRule {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.alert_rules.push(rule.clone());
+    rule
+}
+
+pub fn update_alert_rule(id: &str, rule: AlertRule) -> Option<AlertRule> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.alert_rules.iter_mut().find(|r| r.id == id)?;
+    *existing = rule.clone();
+    Some(rule)
+}
+
+pub fn delete_alert_rule(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.alert_rules.len();
+    mock_data.alert_rules.retain(|r| r.id != id);
+    mock_data.alert_rules.len() != before
+}
+
+// Alerts, newest first
+pub fn get_alerts() -> Vec<Alert> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut alerts = mock_data.alerts.clone();
+    alerts.sort_by(|a, b| b.raised_at.cmp(&a.raised_at));
+    alerts
+}
+
+pub fn get_alert(id: &str) -> Option<Alert> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.alerts.iter().find(|a| a.id == id).cloned()
+}
+
+// Report that `rule` found its condition true for `subject`. An unresolved
+// alert for the same rule and subject is refreshed rather than duplicated, and
+// nothing is raised while the rule is cooling down from the last resolved
+// one. Returns the alert only when a new one was raised.
+pub fn raise_alert(rule: &AlertRule, subject: &str, camera_id: Option<String>, message: String) -> Option<Alert> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let now = chrono::Utc::now();
+    let same = |a: &Alert| a.rule_id == rule.id && a.subject == subject;
+
+    if let Some(alert) = mock_data.alerts.iter_mut().find(|a| same(a) && a.state != AlertState::Resolved) {
+        alert.last_seen = format_timestamp(now);
+        alert.message = message;
+        return None;
+    }
+
+    let cooldown = chrono::Duration::minutes(i64::from(rule.cooldown_minutes));
+    let cooling_down = mock_data.alerts.iter().any(|a| {
+        same(a) && a.resolved_at.as_deref().and_then(parse_timestamp).is_some_and(|at| now - at < cooldown)
+    });
+    if cooling_down {
+        return None;
+    }
+
+    mock_data.next_id += 1;
+    let alert = Alert {
+        id: mock_data.next_id.to_string(),
+        rule_id: rule.id.clone(),
+        severity: rule.severity.clone(),
+        subject: subject.to_string(),
+        camera_id,
+        message,
+        state: AlertState::Open,
+        raised_at: format_timestamp(now),
+        last_seen: format_timestamp(now),
+        acknowledged_by: None,
+        acknowledged_at: None,
+        resolved_by: None,
+        resolved_at: None,
+    };
+    mock_data.alerts.push(alert.clone());
+    Some(alert)
+}
+
+// Resolve the rule's unresolved alerts whose subject is no longer firing, or
+// all of them when the rule is gone or disabled
+pub fn resolve_cleared_alerts(rule_id: &str, firing: &[String]) -> Vec<Alert> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let now = now_timestamp();
+    mock_data
+        .alerts
+        .iter_mut()
+        .filter(|a| a.rule_id == rule_id && a.state != AlertState::Resolved && !firing.contains(&a.subject))
+        .map(|alert| {
+            alert.state = AlertState::Resolved;
+            alert.resolved_by = Some("system".to_string());
+            alert.resolved_at = Some(now.clone());
+            alert.clone()
+        })
+        .collect()
+}
+
+pub fn acknowledge_alert(id: &str, user_id: &str) -> Option<Alert> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let alert = mock_data.alerts.iter_mut().find(|a| a.id == id)?;
+    if alert.state == AlertState::Open {
+        alert.state = AlertState::Acknowledged;
+        alert.acknowledged_by = Some(user_id.to_string());
+        alert.acknowledged_at = Some(now_timestamp());
+    }
+    Some(alert.clone())
+}
+
+pub fn resolve_alert(id: &str, user_id: &str) -> Option<Alert> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let alert = mock_data.alerts.iter_mut().find(|a| a.id == id)?;
+    if alert.state != AlertState::Resolved {
+        alert.state = AlertState::Resolved;
+        alert.resolved_by = Some(user_id.to_string());
+        alert.resolved_at = Some(now_timestamp());
+    }
+    Some(alert.clone())
 }
 
 // Settings
@@ -368,7 +2133,412 @@ pub fn get_settings() -> Settings {
     mock_data.settings.clone()
 }
 
-pub fn update_settings(settings: Settings) {
+// Seals a new SMTP password if one was given; otherwise the stored one is
+// kept, unless the client cleared `has_password` to remove it
+pub fn update_settings(mut settings: Settings) {
     let mut mock_data = MOCK_DATA.lock().unwrap();
-    mock_data.settings = settings;
-} 
\ No newline at end of file
+    let password = std::mem::take(&mut settings.smtp.password);
+    if !password.is_empty() {
+        mock_data.smtp_password = Some(secrets::seal(password.as_bytes()));
+    } else if !settings.smtp.has_password {
+        mock_data.smtp_password = None;
+    }
+    settings.smtp.has_password = mock_data.smtp_password.is_some();
+
+    let auth_value = std::mem::take(&mut settings.sms.auth_value);
+    if !auth_value.is_empty() {
+        mock_data.sms_auth_value = Some(secrets::seal(auth_value.as_bytes()));
+    } else if !settings.sms.has_auth_value {
+        mock_data.sms_auth_value = None;
+    }
+    settings.sms.has_auth_value = mock_data.sms_auth_value.is_some();
+    mock_data.settings = settings.clone();
+    change_feed::updated(Entity::Settings(Box::new(settings)));
+}
+
+// SMTP settings with the password decrypted, for connecting to the server
+pub fn get_smtp_settings() -> SmtpSettings {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut smtp = mock_data.settings.smtp.clone();
+    if let Some(sealed) = &mock_data.smtp_password {
+        match secrets::open(sealed).map(|plaintext| String::from_utf8_lossy(&plaintext).into_owned()) {
+            Ok(password) => smtp.password = password,
+            Err(e) => eprintln!("Cannot read the SMTP password: {}", e),
+        }
+    }
+    smtp
+}
+
+// SMS gateway settings with the auth header value decrypted, for sending
+pub fn get_sms_gateway() -> SmsGatewaySettings {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut gateway = mock_data.settings.sms.clone();
+    if let Some(sealed) = &mock_data.sms_auth_value {
+        match secrets::open(sealed).map(|plaintext| String::from_utf8_lossy(&plaintext).into_owned()) {
+            Ok(value) => gateway.auth_value = value,
+            Err(e) => eprintln!("Cannot read the SMS gateway credentials: {}", e),
+        }
+    }
+    gateway
+}
+
+// Email outbox, newest first
+pub fn get_outbox() -> Vec<OutboxEmail> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut outbox = mock_data.outbox.clone();
+    outbox.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    outbox
+}
+
+pub fn add_outbox_email(email: OutboxEmail) -> OutboxEmail {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.outbox.push(email.clone());
+    email
+}
+
+// Pending emails whose next attempt is due
+pub fn due_outbox_emails(now: chrono::DateTime<chrono::Utc>) -> Vec<OutboxEmail> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .outbox
+        .iter()
+        .filter(|e| e.status == OutboxStatus::Pending)
+        .filter(|e| parse_timestamp(&e.next_attempt_at).is_none_or(|at| at <= now))
+        .cloned()
+        .collect()
+}
+
+// Record one delivery attempt. A failure with a retry time stays pending;
+// without one the email is given up on.
+pub fn record_outbox_attempt(id: &str, result: Result<(), String>, retry_at: Option<String>) -> Option<OutboxEmail> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let email = mock_data.outbox.iter_mut().find(|e| e.id == id)?;
+    email.attempts += 1;
+    match result {
+        Ok(()) => {
+            email.status = OutboxStatus::Sent;
+            email.sent_at = Some(now_timestamp());
+            email.last_error = None;
+        }
+        Err(error) => {
+            email.last_error = Some(error);
+            match retry_at {
+                Some(at) => email.next_attempt_at = at,
+                None => email.status = OutboxStatus::Failed,
+            }
+        }
+    }
+    Some(email.clone())
+}
+
+// Put a failed email back in the queue for another round of attempts
+pub fn retry_outbox_email(id: &str) -> Option<OutboxEmail> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let email = mock_data.outbox.iter_mut().find(|e| e.id == id)?;
+    if email.status == OutboxStatus::Failed {
+        email.status = OutboxStatus::Pending;
+        email.attempts = 0;
+        email.next_attempt_at = now_timestamp();
+    }
+    Some(email.clone())
+} 
+
+// SMS log, newest first
+pub fn get_sms_messages() -> Vec<SmsMessage> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut messages = mock_data.sms_messages.clone();
+    messages.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    messages
+}
+
+pub fn add_sms_message(message: SmsMessage) -> SmsMessage {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.sms_messages.push(message.clone());
+    message
+}
+
+// Messages queued for a number since the given time, not counting the ones
+// the rate limit already dropped
+pub fn count_sms_since(to: &str, since: chrono::DateTime<chrono::Utc>) -> usize {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .sms_messages
+        .iter()
+        .filter(|m| m.to == to && m.status != SmsStatus::RateLimited)
+        .filter(|m| parse_timestamp(&m.created_at).is_some_and(|at| at >= since))
+        .count()
+}
+
+// Pending messages whose next attempt is due
+pub fn due_sms_messages(now: chrono::DateTime<chrono::Utc>) -> Vec<SmsMessage> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .sms_messages
+        .iter()
+        .filter(|m| m.status == SmsStatus::Pending)
+        .filter(|m| parse_timestamp(&m.next_attempt_at).is_none_or(|at| at <= now))
+        .cloned()
+        .collect()
+}
+
+// Record one delivery attempt and the gateway's answer, if there was one.
+// A failure with a retry time stays pending; without one it is given up on.
+pub fn record_sms_attempt(
+    id: &str,
+    gateway_status: Option<u16>,
+    result: Result<(), String>,
+    retry_at: Option<String>,
+) -> Option<SmsMessage> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let message = mock_data.sms_messages.iter_mut().find(|m| m.id == id)?;
+    message.attempts += 1;
+    message.gateway_status = gateway_status;
+    match result {
+        Ok(()) => {
+            message.status = SmsStatus::Sent;
+            message.sent_at = Some(now_timestamp());
+            message.last_error = None;
+        }
+        Err(error) => {
+            message.last_error = Some(error);
+            match retry_at {
+                Some(at) => message.next_attempt_at = at,
+                None => message.status = SmsStatus::Failed,
+            }
+        }
+    }
+    Some(message.clone())
+}
+
+// Put a failed message back in the queue for another round of attempts
+pub fn retry_sms_message(id: &str) -> Option<SmsMessage> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let message = mock_data.sms_messages.iter_mut().find(|m| m.id == id)?;
+    if message.status == SmsStatus::Failed {
+        message.status = SmsStatus::Pending;
+        message.attempts = 0;
+        message.next_attempt_at = now_timestamp();
+    }
+    Some(message.clone())
+}
+
+// Webhook subscriptions
+pub fn get_webhooks() -> Vec<WebhookSubscription> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.webhooks.clone()
+}
+
+pub fn get_webhook(id: &str) -> Option<WebhookSubscription> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.webhooks.iter().find(|w| w.id == id).cloned()
+}
+
+pub fn create_webhook(mut webhook: WebhookSubscription) -> WebhookSubscription {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let secret = std::mem::take(&mut webhook.secret);
+    webhook.sealed_secret = Some(secrets::seal(secret.as_bytes()));
+    mock_data.webhooks.push(webhook.clone());
+    webhook
+}
+
+// Replaces the URL, events and active flag, and the secret when a new one is given
+pub fn update_webhook(id: &str, mut webhook: WebhookSubscription) -> Option<WebhookSubscription> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let existing = mock_data.webhooks.iter_mut().find(|w| w.id == id)?;
+    let secret = std::mem::take(&mut webhook.secret);
+    webhook.sealed_secret = if secret.is_empty() {
+        existing.sealed_secret.clone()
+    } else {
+        Some(secrets::seal(secret.as_bytes()))
+    };
+    webhook.id = existing.id.clone();
+    webhook.created_at = existing.created_at.clone();
+    *existing = webhook.clone();
+    Some(webhook)
+}
+
+// Also drops the subscription's delivery log
+pub fn delete_webhook(id: &str) -> bool {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let before = mock_data.webhooks.len();
+    mock_data.webhooks.retain(|w| w.id != id);
+    mock_data.webhook_deliveries.retain(|d| d.subscription_id != id);
+    mock_data.webhooks.len() != before
+}
+
+// The subscription's signing secret, decrypted
+pub fn get_webhook_secret(id: &str) -> Option<String> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let sealed = mock_data.webhooks.iter().find(|w| w.id == id)?.sealed_secret.as_ref()?;
+    match secrets::open(sealed) {
+        Ok(plaintext) => Some(String::from_utf8_lossy(&plaintext).into_owned()),
+        Err(e) => {
+            eprintln!("Cannot read the secret of webhook {}: {}", id, e);
+            None
+        }
+    }
+}
+
+impl MockData {
+    // Queue a delivery of the event to every active subscription that wants it
+    fn queue_webhook_event(&mut self, event: WebhookEvent, data: serde_json::Value) -> Vec<WebhookDelivery> {
+        self.next_id += 1;
+        let event_id = self.next_id.to_string();
+        let now = now_timestamp();
+        let payload = serde_json::json!({
+            "id": event_id,
+            "event": event,
+            "created_at": now,
+            "data": data,
+        })
+        .to_string();
+
+        let subscribers: Vec<String> = self
+            .webhooks
+            .iter()
+            .filter(|w| w.active && w.events.contains(&event))
+            .map(|w| w.id.clone())
+            .collect();
+        let mut queued = Vec::new();
+        for subscription_id in subscribers {
+            self.next_id += 1;
+            let delivery = WebhookDelivery {
+                id: self.next_id.to_string(),
+                subscription_id,
+                event: event.clone(),
+                payload: payload.clone(),
+                status: WebhookDeliveryStatus::Pending,
+                attempts: 0,
+                created_at: now.clone(),
+                next_attempt_at: now.clone(),
+                delivered_at: None,
+                response_status: None,
+                last_error: None,
+            };
+            self.webhook_deliveries.push(delivery.clone());
+            queued.push(delivery);
+        }
+        queued
+    }
+}
+
+pub fn queue_webhook_event(event: WebhookEvent, data: serde_json::Value) -> Vec<WebhookDelivery> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.queue_webhook_event(event, data)
+}
+
+// Delivery log, newest first, optionally for one subscription or one status
+pub fn get_webhook_deliveries(subscription_id: Option<&str>, status: Option<WebhookDeliveryStatus>) -> Vec<WebhookDelivery> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut deliveries: Vec<WebhookDelivery> = mock_data
+        .webhook_deliveries
+        .iter()
+        .filter(|d| subscription_id.is_none_or(|id| d.subscription_id == id))
+        .filter(|d| status.as_ref().is_none_or(|status| d.status == *status))
+        .cloned()
+        .collect();
+    deliveries.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+    deliveries
+}
+
+// Pending deliveries whose next attempt is due
+pub fn due_webhook_deliveries(now: chrono::DateTime<chrono::Utc>) -> Vec<WebhookDelivery> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data
+        .webhook_deliveries
+        .iter()
+        .filter(|d| d.status == WebhookDeliveryStatus::Pending)
+        .filter(|d| parse_timestamp(&d.next_attempt_at).is_none_or(|at| at <= now))
+        .cloned()
+        .collect()
+}
+
+// Record one delivery attempt and the receiver's answer, if there was one.
+// A failure with a retry time stays pending; without one it is dead-lettered.
+pub fn record_webhook_attempt(
+    id: &str,
+    response_status: Option<u16>,
+    result: Result<(), String>,
+    retry_at: Option<String>,
+) -> Option<WebhookDelivery> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let delivery = mock_data.webhook_deliveries.iter_mut().find(|d| d.id == id)?;
+    delivery.attempts += 1;
+    delivery.response_status = response_status;
+    match result {
+        Ok(()) => {
+            delivery.status = WebhookDeliveryStatus::Delivered;
+            delivery.delivered_at = Some(now_timestamp());
+            delivery.last_error = None;
+        }
+        Err(error) => {
+            delivery.last_error = Some(error);
+            match retry_at {
+                Some(at) => delivery.next_attempt_at = at,
+                None => delivery.status = WebhookDeliveryStatus::DeadLetter,
+            }
+        }
+    }
+    Some(delivery.clone())
+}
+
+// Take a dead-lettered delivery off the list for another round of attempts
+pub fn retry_webhook_delivery(id: &str) -> Option<WebhookDelivery> {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    let delivery = mock_data.webhook_deliveries.iter_mut().find(|d| d.id == id)?;
+    if delivery.status == WebhookDeliveryStatus::DeadLetter {
+        delivery.status = WebhookDeliveryStatus::Pending;
+        delivery.attempts = 0;
+        delivery.next_attempt_at = now_timestamp();
+    }
+    Some(delivery.clone())
+}
+
+// Events kept before the oldest are dropped, snapshots and all
+const MAX_CAMERA_EVENTS: usize = 10_000;
+
+pub fn add_camera_event(event: CameraEvent, snapshot: Option<Vec<u8>>) -> CameraEvent {
+    let mut mock_data = MOCK_DATA.lock().unwrap();
+    if let Some(bytes) = snapshot {
+        mock_data.event_snapshots.insert(event.id.clone(), bytes);
+    }
+    mock_data.camera_events.push(event.clone());
+
+    let excess = mock_data.camera_events.len().saturating_sub(MAX_CAMERA_EVENTS);
+    if excess > 0 {
+        let dropped: Vec<CameraEvent> = mock_data.camera_events.drain(..excess).collect();
+        for old in dropped {
+            mock_data.event_snapshots.remove(&old.id);
+        }
+    }
+    event
+}
+
+// Matching events, newest first by the time the camera reported
+pub fn get_camera_events(filter: &CameraEventFilter) -> Vec<CameraEvent> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    let mut events: Vec<CameraEvent> = mock_data
+        .camera_events
+        .iter()
+        .filter(|e| filter.camera_id.as_ref().is_none_or(|id| &e.camera_id == id))
+        .filter(|e| filter.event_type.as_ref().is_none_or(|t| &e.event_type == t))
+        .filter(|e| {
+            let at = parse_timestamp(&e.timestamp);
+            filter.from.is_none_or(|from| at.is_some_and(|at| at >= from))
+                && filter.to.is_none_or(|to| at.is_some_and(|at| at <= to))
+        })
+        .cloned()
+        .collect();
+    events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| b.received_at.cmp(&a.received_at)));
+    events
+}
+
+pub fn get_camera_event(id: &str) -> Option<CameraEvent> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.camera_events.iter().find(|e| e.id == id).cloned()
+}
+
+pub fn get_event_snapshot(id: &str) -> Option<Vec<u8>> {
+    let mock_data = MOCK_DATA.lock().unwrap();
+    mock_data.event_snapshots.get(id).cloned()
+}
@@ -0,0 +1,418 @@
+use base64::Engine;
+use rand::RngCore;
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+
+use crate::mock_data::{Camera, DeviceInfo, MediaProfile, NetworkInterface, format_timestamp};
+use crate::xml;
+
+const DEVICE_NAMESPACE: &str = "http://www.onvif.org/ver10/device/wsdl";
+pub const MEDIA_NAMESPACE: &str = "http://www.onvif.org/ver10/media/wsdl";
+
+#[derive(Debug, PartialEq)]
+pub enum OnvifError {
+    // The device could not be reached or answered with a non-SOAP error
+    Http(String),
+    // The device rejected the camera's credentials
+    Unauthorized,
+    // The device answered with a SOAP fault
+    Fault(String),
+    // The response was missing something we need
+    Parse(String),
+}
+
+impl std::fmt::Display for OnvifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OnvifError::Http(message) => write!(f, "HTTP error: {}", message),
+            OnvifError::Unauthorized => write!(f, "Not authorized"),
+            OnvifError::Fault(message) => write!(f, "SOAP fault: {}", message),
+            OnvifError::Parse(message) => write!(f, "Invalid response: {}", message),
+        }
+    }
+}
+
+// Everything refresh-info learns about a camera
+pub struct DeviceDetails {
+    pub device_info: DeviceInfo,
+    pub network_interfaces: Vec<NetworkInterface>,
+    pub media_profiles: Vec<MediaProfile>,
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    device_url: String,
+    username: String,
+    password: String,
+}
+
+// Where a camera's device service lives when discovery didn't tell us
+pub fn device_url(camera: &Camera) -> String {
+    camera
+        .onvif_url
+        .clone()
+        .unwrap_or_else(|| format!("http://{}/onvif/device_service", camera.ip_address))
+}
+
+// WS-Security UsernameToken with a password digest, as required by ONVIF
+fn security_header(username: &str, password: &str) -> String {
+    let mut nonce = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let created = format_timestamp(chrono::Utc::now());
+
+    let mut hasher = Sha1::new();
+    hasher.update(nonce);
+    hasher.update(created.as_bytes());
+    hasher.update(password.as_bytes());
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    format!(
+        r#"<s:Header><wsse:Security s:mustUnderstand="1" xmlns:wsse="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd" xmlns:wsu="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd"><wsse:UsernameToken><wsse:Username>{}</wsse:Username><wsse:Password Type="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest">{}</wsse:Password><wsse:Nonce EncodingType="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary">{}</wsse:Nonce><wsu:Created>{}</wsu:Created></wsse:UsernameToken></wsse:Security></s:Header>"#,
+        xml::escape(username),
+        engine.encode(hasher.finalize()),
+        engine.encode(nonce),
+        created
+    )
+}
+
+fn parse_bool(value: Option<&str>) -> bool {
+    value.is_some_and(|v| v.trim().eq_ignore_ascii_case("true"))
+}
+
+impl Client {
+    pub fn new(camera: &Camera, timeout: Duration) -> Result<Client, OnvifError> {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| OnvifError::Http(e.to_string()))?;
+
+        Ok(Client {
+            http,
+            device_url: device_url(camera),
+            username: camera.username.clone(),
+            password: camera.password.clone(),
+        })
+    }
+
+    // Send one SOAP request and return the body of the response envelope
+    pub async fn call(&self, url: &str, namespace: &str, body: &str) -> Result<String, OnvifError> {
+        let header = if self.username.is_empty() {
+            String::new()
+        } else {
+            security_header(&self.username, &self.password)
+        };
+        let envelope = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:ns="{}" xmlns:tt="http://www.onvif.org/ver10/schema">{}<s:Body>{}</s:Body></s:Envelope>"#,
+            namespace, header, body
+        );
+
+        let response = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/soap+xml; charset=utf-8")
+            .body(envelope)
+            .send()
+            .await
+            .map_err(|e| OnvifError::Http(e.to_string()))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| OnvifError::Http(e.to_string()))?;
+
+        if let Some(fault) = xml::text(&text, "Fault") {
+            let reason = xml::text(fault, "Text").or_else(|| xml::text(fault, "faultstring")).unwrap_or_default();
+            let subcodes = xml::elements(fault, "Value");
+            if subcodes.iter().any(|code| code.text.ends_with("NotAuthorized")) {
+                return Err(OnvifError::Unauthorized);
+            }
+            return Err(OnvifError::Fault(xml::unescape(reason)));
+        }
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(OnvifError::Unauthorized);
+        }
+        if !status.is_success() {
+            return Err(OnvifError::Http(format!("{} returned {}", url, status)));
+        }
+
+        xml::text(&text, "Body")
+            .map(str::to_string)
+            .ok_or_else(|| OnvifError::Parse("no SOAP body".to_string()))
+    }
+
+    pub async fn device_information(&self) -> Result<DeviceInfo, OnvifError> {
+        let body = self.call(&self.device_url, DEVICE_NAMESPACE, "<ns:GetDeviceInformation/>").await?;
+        let field = |name| xml::text(&body, name).map(xml::unescape).unwrap_or_default();
+
+        Ok(DeviceInfo {
+            manufacturer: field("Manufacturer"),
+            model: field("Model"),
+            firmware_version: field("FirmwareVersion"),
+            serial_number: field("SerialNumber"),
+            hardware_id: field("HardwareId"),
+        })
+    }
+
+    pub async fn network_interfaces(&self) -> Result<Vec<NetworkInterface>, OnvifError> {
+        let body = self.call(&self.device_url, DEVICE_NAMESPACE, "<ns:GetNetworkInterfaces/>").await?;
+
+        Ok(xml::elements(&body, "NetworkInterfaces")
+            .into_iter()
+            .map(|interface| {
+                let ipv4 = xml::text(interface.text, "IPv4").unwrap_or_default();
+                let dhcp = parse_bool(xml::text(ipv4, "DHCP"));
+                // The active address lives under FromDHCP or Manual depending on the mode
+                let config = xml::text(ipv4, if dhcp { "FromDHCP" } else { "Manual" }).unwrap_or_default();
+
+                NetworkInterface {
+                    token: interface.attribute("token").unwrap_or_default().to_string(),
+                    hw_address: xml::text(interface.text, "HwAddress").unwrap_or_default().to_string(),
+                    ipv4_address: xml::text(config, "Address").map(str::to_string),
+                    prefix_length: xml::text(config, "PrefixLength").and_then(|p| p.parse().ok()),
+                    dhcp,
+                }
+            })
+            .collect())
+    }
+
+    // URL of one of the device's services ("Media", "PTZ", ...) as advertised
+    // in its capabilities
+    pub async fn service_url(&self, category: &str) -> Result<String, OnvifError> {
+        let request = format!("<ns:GetCapabilities><ns:Category>{}</ns:Category></ns:GetCapabilities>", category);
+        let body = self.call(&self.device_url, DEVICE_NAMESPACE, &request).await?;
+
+        xml::text(&body, category)
+            .and_then(|service| xml::text(service, "XAddr"))
+            .map(str::to_string)
+            .ok_or_else(|| OnvifError::Parse(format!("device has no {} service", category)))
+    }
+
+    pub async fn media_profiles(&self, media_url: &str) -> Result<Vec<MediaProfile>, OnvifError> {
+        let body = self.call(media_url, MEDIA_NAMESPACE, "<ns:GetProfiles/>").await?;
+
+        let mut profiles: Vec<MediaProfile> = xml::elements(&body, "Profiles")
+            .into_iter()
+            .map(|profile| {
+                let encoder = xml::text(profile.text, "VideoEncoderConfiguration").unwrap_or_default();
+                let resolution = xml::text(encoder, "Resolution").unwrap_or_default();
+
+                MediaProfile {
+                    token: profile.attribute("token").unwrap_or_default().to_string(),
+                    name: xml::text(profile.text, "Name").map(xml::unescape).unwrap_or_default(),
+                    encoding: xml::text(encoder, "Encoding").map(str::to_string),
+                    width: xml::text(resolution, "Width").and_then(|w| w.parse().ok()),
+                    height: xml::text(resolution, "Height").and_then(|h| h.parse().ok()),
+                    frame_rate: xml::text(encoder, "FrameRateLimit").and_then(|f| f.parse().ok()),
+                    stream_uri: None,
+                    snapshot_uri: None,
+                }
+            })
+            .collect();
+
+        for profile in &mut profiles {
+            let request = format!(
+                "<ns:GetStreamUri><ns:StreamSetup><tt:Stream>RTP-Unicast</tt:Stream><tt:Transport><tt:Protocol>RTSP</tt:Protocol></tt:Transport></ns:StreamSetup><ns:ProfileToken>{}</ns:ProfileToken></ns:GetStreamUri>",
+                xml::escape(&profile.token)
+            );
+            // A profile without a stream URI is still worth listing
+            match self.call(media_url, MEDIA_NAMESPACE, &request).await {
+                Ok(body) => profile.stream_uri = xml::text(&body, "Uri").map(xml::unescape),
+                Err(OnvifError::Unauthorized) => return Err(OnvifError::Unauthorized),
+                Err(e) => eprintln!("GetStreamUri failed for profile {}: {}", profile.token, e),
+            }
+
+            // Not every camera can serve JPEG snapshots
+            let request = format!(
+                "<ns:GetSnapshotUri><ns:ProfileToken>{}</ns:ProfileToken></ns:GetSnapshotUri>",
+                xml::escape(&profile.token)
+            );
+            match self.call(media_url, MEDIA_NAMESPACE, &request).await {
+                Ok(body) => profile.snapshot_uri = xml::text(&body, "Uri").map(xml::unescape),
+                Err(OnvifError::Unauthorized) => return Err(OnvifError::Unauthorized),
+                Err(e) => eprintln!("GetSnapshotUri failed for profile {}: {}", profile.token, e),
+            }
+        }
+
+        Ok(profiles)
+    }
+}
+
+// Fetch device information, network settings and stream profiles
+pub async fn fetch_details(camera: &Camera, timeout: Duration) -> Result<DeviceDetails, OnvifError> {
+    let client = Client::new(camera, timeout)?;
+
+    let device_info = client.device_information().await?;
+    let network_interfaces = client.network_interfaces().await?;
+    let media_profiles = match client.service_url("Media").await {
+        Ok(media_url) => client.media_profiles(&media_url).await?,
+        Err(OnvifError::Parse(_)) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    Ok(DeviceDetails { device_info, network_interfaces, media_profiles })
+}
+
+// A mock ONVIF device for tests of this and other modules
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use axum::{extract::State, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    pub const USERNAME: &str = "admin";
+    pub const PASSWORD: &str = "secret";
+
+    // Answers a request with the body of the response envelope, given the
+    // device's base URL. None means the device doesn't know the action.
+    pub type Responder = fn(&str, &str) -> Option<String>;
+
+    fn envelope(body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope" xmlns:tds="http://www.onvif.org/ver10/device/wsdl" xmlns:trt="http://www.onvif.org/ver10/media/wsdl" xmlns:tptz="http://www.onvif.org/ver20/ptz/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema"><env:Body>{}</env:Body></env:Envelope>"#,
+            body
+        )
+    }
+
+    fn not_authorized() -> String {
+        envelope(
+            r#"<env:Fault><env:Code><env:Value>env:Sender</env:Value><env:Subcode><env:Value>ter:NotAuthorized</env:Value></env:Subcode></env:Code><env:Reason><env:Text xml:lang="en">Sender not authorized</env:Text></env:Reason></env:Fault>"#,
+        )
+    }
+
+    // Check the request's UsernameToken the way a camera would
+    fn authorized(request: &str) -> bool {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let (Some(username), Some(digest), Some(nonce), Some(created)) = (
+            xml::text(request, "Username"),
+            xml::text(request, "Password"),
+            xml::text(request, "Nonce"),
+            xml::text(request, "Created"),
+        ) else {
+            return false;
+        };
+
+        let mut hasher = Sha1::new();
+        hasher.update(engine.decode(nonce).unwrap());
+        hasher.update(created.as_bytes());
+        hasher.update(PASSWORD.as_bytes());
+        username == USERNAME && digest == engine.encode(hasher.finalize())
+    }
+
+    #[derive(Clone)]
+    struct Device {
+        base: String,
+        respond: Responder,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    async fn handle(State(device): State<Device>, request: String) -> String {
+        if !authorized(&request) {
+            return not_authorized();
+        }
+        device.requests.lock().unwrap().push(request.clone());
+
+        match (device.respond)(&device.base, &request) {
+            Some(body) => envelope(&body),
+            None => envelope("<env:Fault><env:Reason><env:Text>Unknown action</env:Text></env:Reason></env:Fault>"),
+        }
+    }
+
+    pub struct MockDevice {
+        base: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockDevice {
+        // A camera pointing at the device
+        pub fn camera(&self, password: &str) -> Camera {
+            Camera {
+                onvif_url: Some(format!("{}/onvif/device_service", self.base)),
+                username: USERNAME.to_string(),
+                password: password.to_string(),
+                ..Camera::default()
+            }
+        }
+
+        // Every authorized request the device got, in order
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    // Serve a device with device, media and PTZ services under /onvif
+    pub fn serve(respond: Responder) -> MockDevice {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/onvif/device_service", post(handle))
+            .route("/onvif/media_service", post(handle))
+            .route("/onvif/ptz_service", post(handle))
+            .with_state(Device { base: base.clone(), respond, requests: requests.clone() });
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        MockDevice { base, requests }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::testing::PASSWORD;
+
+    fn respond(base: &str, request: &str) -> Option<String> {
+        let body = if request.contains("GetDeviceInformation") {
+            "<tds:GetDeviceInformationResponse><tds:Manufacturer>Acme</tds:Manufacturer><tds:Model>IPC-200</tds:Model><tds:FirmwareVersion>2.1.0</tds:FirmwareVersion><tds:SerialNumber>SN123</tds:SerialNumber><tds:HardwareId>HW1</tds:HardwareId></tds:GetDeviceInformationResponse>".to_string()
+        } else if request.contains("GetNetworkInterfaces") {
+            "<tds:GetNetworkInterfacesResponse><tds:NetworkInterfaces token=\"eth0\"><tt:Enabled>true</tt:Enabled><tt:Info><tt:Name>eth0</tt:Name><tt:HwAddress>00:11:22:33:44:55</tt:HwAddress></tt:Info><tt:IPv4><tt:Enabled>true</tt:Enabled><tt:Config><tt:Manual><tt:Address>192.168.1.50</tt:Address><tt:PrefixLength>24</tt:PrefixLength></tt:Manual><tt:DHCP>false</tt:DHCP></tt:Config></tt:IPv4></tds:NetworkInterfaces></tds:GetNetworkInterfacesResponse>".to_string()
+        } else if request.contains("GetCapabilities") {
+            format!("<tds:GetCapabilitiesResponse><tds:Capabilities><tt:Media><tt:XAddr>{}/onvif/media_service</tt:XAddr></tt:Media></tds:Capabilities></tds:GetCapabilitiesResponse>", base)
+        } else if request.contains("GetProfiles") {
+            "<trt:GetProfilesResponse><trt:Profiles token=\"main\" fixed=\"true\"><tt:Name>Main Stream</tt:Name><tt:VideoEncoderConfiguration token=\"ve0\"><tt:Name>VE0</tt:Name><tt:Encoding>H264</tt:Encoding><tt:Resolution><tt:Width>1920</tt:Width><tt:Height>1080</tt:Height></tt:Resolution><tt:RateControl><tt:FrameRateLimit>25</tt:FrameRateLimit></tt:RateControl></tt:VideoEncoderConfiguration></trt:Profiles></trt:GetProfilesResponse>".to_string()
+        } else if request.contains("GetSnapshotUri") {
+            "<trt:GetSnapshotUriResponse><trt:MediaUri><tt:Uri>http://192.168.1.50/snapshot.jpg</tt:Uri></trt:MediaUri></trt:GetSnapshotUriResponse>".to_string()
+        } else if request.contains("GetStreamUri") {
+            "<trt:GetStreamUriResponse><trt:MediaUri><tt:Uri>rtsp://192.168.1.50:554/stream1?profile=main&amp;x=1</tt:Uri></trt:MediaUri></trt:GetStreamUriResponse>".to_string()
+        } else {
+            return None;
+        };
+
+        Some(body)
+    }
+
+    fn mock_camera(password: &str) -> Camera {
+        testing::serve(respond).camera(password)
+    }
+
+    #[tokio::test]
+    async fn fetches_device_details() {
+        let camera = mock_camera(PASSWORD);
+        let details = fetch_details(&camera, Duration::from_secs(2)).await.unwrap();
+
+        assert_eq!(details.device_info.manufacturer, "Acme");
+        assert_eq!(details.device_info.model, "IPC-200");
+        assert_eq!(details.device_info.firmware_version, "2.1.0");
+        assert_eq!(details.device_info.serial_number, "SN123");
+
+        let interface = &details.network_interfaces[0];
+        assert_eq!(interface.token, "eth0");
+        assert_eq!(interface.hw_address, "00:11:22:33:44:55");
+        assert_eq!(interface.ipv4_address.as_deref(), Some("192.168.1.50"));
+        assert_eq!(interface.prefix_length, Some(24));
+        assert!(!interface.dhcp);
+
+        let profile = &details.media_profiles[0];
+        assert_eq!(profile.token, "main");
+        assert_eq!(profile.name, "Main Stream");
+        assert_eq!(profile.encoding.as_deref(), Some("H264"));
+        assert_eq!((profile.width, profile.height), (Some(1920), Some(1080)));
+        assert_eq!(profile.frame_rate, Some(25.0));
+        assert_eq!(profile.stream_uri.as_deref(), Some("rtsp://192.168.1.50:554/stream1?profile=main&x=1"));
+        assert_eq!(profile.snapshot_uri.as_deref(), Some("http://192.168.1.50/snapshot.jpg"));
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_unauthorized() {
+        let camera = mock_camera("wrong");
+        let result = fetch_details(&camera, Duration::from_secs(2)).await;
+
+        assert!(matches!(result, Err(OnvifError::Unauthorized)));
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::mock_data::{
+    Camera, GrantSubject, GrantTarget, User, UserGroup, UserRole,
+    get_cameras, get_groups_of_user, get_user, resolve_grants,
+};
+
+// Which cameras a user may see
+#[derive(Clone, Debug, PartialEq)]
+pub enum CameraAccess {
+    All,
+    Only(HashSet<String>),
+}
+
+impl CameraAccess {
+    pub fn allows(&self, camera_id: &str) -> bool {
+        match self {
+            CameraAccess::All => true,
+            CameraAccess::Only(ids) => ids.contains(camera_id),
+        }
+    }
+
+    pub fn filter(&self, cameras: Vec<Camera>) -> Vec<Camera> {
+        cameras.into_iter().filter(|camera| self.allows(&camera.id)).collect()
+    }
+}
+
+// Where part of a user's effective permissions comes from
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum PermissionSource {
+    // Set on the user itself
+    Direct,
+    Group { id: String, name: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrantSource {
+    pub grant_id: String,
+    pub target: GrantTarget,
+    pub source: PermissionSource,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EffectiveCamera {
+    pub camera_id: String,
+    pub camera_name: String,
+    // Every grant that lets the user see this camera
+    pub sources: Vec<GrantSource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EffectivePermissions {
+    pub user_id: String,
+    pub role: UserRole,
+    pub role_source: PermissionSource,
+    // Admins see every camera whatever they have been granted
+    pub all_cameras: bool,
+    pub cameras: Vec<EffectiveCamera>,
+}
+
+fn group_source(group: &UserGroup) -> PermissionSource {
+    PermissionSource::Group { id: group.id.clone(), name: group.name.clone() }
+}
+
+// The user's own role or a group's, whichever ranks highest. Ties go to the
+// user's own role.
+fn role_with_source(user: &User, groups: &[UserGroup]) -> (UserRole, PermissionSource) {
+    groups.iter().fold((user.role.clone(), PermissionSource::Direct), |best, group| {
+        if group.role.rank() > best.0.rank() {
+            (group.role.clone(), group_source(group))
+        } else {
+            best
+        }
+    })
+}
+
+pub fn effective_role(user: &User) -> UserRole {
+    role_with_source(user, &get_groups_of_user(&user.id)).0
+}
+
+// The user and every group they belong to
+fn subjects(user: &User, groups: &[UserGroup]) -> Vec<GrantSubject> {
+    let mut subjects = vec![GrantSubject::User(user.id.clone())];
+    subjects.extend(groups.iter().map(|group| GrantSubject::Group(group.id.clone())));
+    subjects
+}
+
+// Admins manage every camera; viewers see the union of their own grants and
+// their groups' grants
+pub fn camera_access(user: &User) -> CameraAccess {
+    let groups = get_groups_of_user(&user.id);
+    match role_with_source(user, &groups).0 {
+        UserRole::SuperAdmin | UserRole::Admin => CameraAccess::All,
+        UserRole::Viewer => CameraAccess::Only(
+            resolve_grants(&subjects(user, &groups))
+                .into_iter()
+                .flat_map(|(_, camera_ids)| camera_ids)
+                .collect(),
+        ),
+    }
+}
+
+// Access of a user known only by ID, such as the creator of a scheduled
+// report. A user who no longer exists sees nothing.
+pub fn camera_access_for(user_id: &str) -> CameraAccess {
+    get_user(user_id).map_or_else(|| CameraAccess::Only(HashSet::new()), |user| camera_access(&user))
+}
+
+// Everything the user may do, with the grant or group each part comes from
+pub fn effective_permissions(user: &User) -> EffectivePermissions {
+    let groups = get_groups_of_user(&user.id);
+    let (role, role_source) = role_with_source(user, &groups);
+    let names: BTreeMap<String, String> = get_cameras().into_iter().map(|c| (c.id, c.name)).collect();
+
+    let mut cameras: BTreeMap<String, Vec<GrantSource>> = BTreeMap::new();
+    for (grant, camera_ids) in resolve_grants(&subjects(user, &groups)) {
+        let source = match &grant.subject {
+            GrantSubject::User(_) => PermissionSource::Direct,
+            GrantSubject::Group(id) => groups
+                .iter()
+                .find(|group| &group.id == id)
+                .map_or(PermissionSource::Direct, group_source),
+        };
+        for camera_id in camera_ids {
+            cameras.entry(camera_id).or_default().push(GrantSource {
+                grant_id: grant.id.clone(),
+                target: grant.target.clone(),
+                source: source.clone(),
+            });
+        }
+    }
+
+    EffectivePermissions {
+        user_id: user.id.clone(),
+        all_cameras: role != UserRole::Viewer,
+        role,
+        role_source,
+        cameras: cameras
+            .into_iter()
+            .map(|(camera_id, sources)| EffectiveCamera {
+                camera_name: names.get(&camera_id).cloned().unwrap_or_default(),
+                camera_id,
+                sources,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::{
+        CameraGrant, add_group_member, create_camera, create_camera_grant, create_user, create_user_group,
+        delete_user_group, remove_group_member,
+    };
+
+    fn user(id: &str, role: UserRole) -> User {
+        create_user(User {
+            id: id.to_string(),
+            username: id.to_string(),
+            name: id.to_string(),
+            email: format!("{}@example.com", id),
+            phone: String::new(),
+            role,
+            active: true,
+            last_login: String::new(),
+        })
+    }
+
+    fn group(id: &str, role: UserRole, members: &[&User]) -> UserGroup {
+        create_user_group(UserGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            role,
+            members: members.iter().map(|user| user.id.clone()).collect(),
+        })
+    }
+
+    fn grant(subject: GrantSubject, camera_id: &str) {
+        create_camera(Camera { id: camera_id.to_string(), name: camera_id.to_string(), ..Camera::default() });
+        create_camera_grant(CameraGrant {
+            id: format!("{}-grant", camera_id),
+            subject,
+            target: GrantTarget::Camera(camera_id.to_string()),
+            created_by: "1".to_string(),
+        });
+    }
+
+    fn only(ids: &[&str]) -> CameraAccess {
+        CameraAccess::Only(ids.iter().map(|id| id.to_string()).collect())
+    }
+
+    #[test]
+    fn groups_can_raise_but_not_lower_the_role() {
+        let viewer = user("permissions-role-viewer", UserRole::Viewer);
+        let admin = user("permissions-role-admin", UserRole::Admin);
+        group("permissions-role-viewers", UserRole::Viewer, &[&viewer, &admin]);
+        assert_eq!(effective_role(&viewer), UserRole::Viewer);
+        assert_eq!(effective_role(&admin), UserRole::Admin);
+
+        group("permissions-role-admins", UserRole::Admin, &[&viewer]);
+        assert_eq!(effective_role(&viewer), UserRole::Admin);
+        assert_eq!(camera_access(&viewer), CameraAccess::All);
+        assert_eq!(role_with_source(&viewer, &get_groups_of_user(&viewer.id)).1, PermissionSource::Group {
+            id: "permissions-role-admins".to_string(),
+            name: "permissions-role-admins".to_string(),
+        });
+    }
+
+    #[test]
+    fn viewers_see_their_own_and_their_groups_grants() {
+        let viewer = user("permissions-union-viewer", UserRole::Viewer);
+        let first = group("permissions-union-first", UserRole::Viewer, &[&viewer]);
+        let second = group("permissions-union-second", UserRole::Viewer, &[&viewer]);
+        grant(GrantSubject::User(viewer.id.clone()), "permissions-union-own");
+        grant(GrantSubject::Group(first.id.clone()), "permissions-union-first");
+        grant(GrantSubject::Group(second.id.clone()), "permissions-union-second");
+        // Granted twice, listed once
+        create_camera_grant(CameraGrant {
+            id: "permissions-union-again".to_string(),
+            subject: GrantSubject::Group(second.id.clone()),
+            target: GrantTarget::Camera("permissions-union-own".to_string()),
+            created_by: "1".to_string(),
+        });
+        let other = group("permissions-union-other", UserRole::Viewer, &[]);
+        grant(GrantSubject::Group(other.id), "permissions-union-hidden");
+
+        assert_eq!(
+            camera_access(&viewer),
+            only(&["permissions-union-own", "permissions-union-first", "permissions-union-second"]),
+        );
+        assert!(!camera_access(&viewer).allows("permissions-union-hidden"));
+    }
+
+    #[test]
+    fn a_group_stops_counting_once_left_or_deleted() {
+        let viewer = user("permissions-removed-viewer", UserRole::Viewer);
+        let left = group("permissions-removed-left", UserRole::Admin, &[&viewer]);
+        let deleted = group("permissions-removed-deleted", UserRole::Viewer, &[&viewer]);
+        grant(GrantSubject::Group(left.id.clone()), "permissions-removed-left");
+        grant(GrantSubject::Group(deleted.id.clone()), "permissions-removed-deleted");
+        assert_eq!(effective_role(&viewer), UserRole::Admin);
+
+        remove_group_member(&left.id, &viewer.id);
+        assert_eq!(effective_role(&viewer), UserRole::Viewer);
+        assert_eq!(camera_access(&viewer), only(&["permissions-removed-deleted"]));
+
+        delete_user_group(&deleted.id);
+        assert_eq!(camera_access(&viewer), only(&[]));
+
+        // Rejoining a deleted group's ID doesn't bring its grants back
+        assert!(add_group_member(&deleted.id, &viewer.id).is_none());
+        assert_eq!(camera_access(&viewer), only(&[]));
+    }
+
+    #[test]
+    fn unknown_users_see_nothing() {
+        assert_eq!(camera_access_for("permissions-no-such-user"), only(&[]));
+        let admin = user("permissions-known-admin", UserRole::SuperAdmin);
+        assert_eq!(camera_access_for(&admin.id), CameraAccess::All);
+    }
+}
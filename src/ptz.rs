@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::mock_data::Camera;
+use crate::onvif::{Client, OnvifError, MEDIA_NAMESPACE};
+use crate::xml;
+
+const PTZ_NAMESPACE: &str = "http://www.onvif.org/ver20/ptz/wsdl";
+
+// A request to move a camera or work with its presets. Pan, tilt and zoom
+// use ONVIF's generic spaces: velocities and translations are -1..1, absolute
+// pan/tilt is -1..1 and absolute zoom 0..1.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PtzCommand {
+    ContinuousMove {
+        pan: f64,
+        tilt: f64,
+        #[serde(default)]
+        zoom: f64,
+        // Stop automatically after this long if no Stop arrives
+        timeout_ms: Option<u64>,
+    },
+    Stop,
+    AbsoluteMove { pan: f64, tilt: f64, zoom: f64 },
+    RelativeMove {
+        pan: f64,
+        tilt: f64,
+        #[serde(default)]
+        zoom: f64,
+    },
+    // Continuous zoom only, negative zooms out
+    Zoom { speed: f64, timeout_ms: Option<u64> },
+    ListPresets,
+    GotoPreset { token: String },
+    SetPreset { name: String, token: Option<String> },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PtzPreset {
+    pub token: String,
+    pub name: String,
+}
+
+pub enum PtzOutcome {
+    Done,
+    Presets(Vec<PtzPreset>),
+    PresetSaved(String),
+}
+
+fn in_range(value: f64, min: f64, max: f64) -> bool {
+    value.is_finite() && (min..=max).contains(&value)
+}
+
+impl PtzCommand {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PtzCommand::ContinuousMove { pan, tilt, zoom, .. } | PtzCommand::RelativeMove { pan, tilt, zoom } => {
+                [pan, tilt, zoom].iter().all(|v| in_range(**v, -1.0, 1.0))
+            }
+            PtzCommand::AbsoluteMove { pan, tilt, zoom } => {
+                in_range(*pan, -1.0, 1.0) && in_range(*tilt, -1.0, 1.0) && in_range(*zoom, 0.0, 1.0)
+            }
+            PtzCommand::Zoom { speed, .. } => in_range(*speed, -1.0, 1.0),
+            PtzCommand::GotoPreset { token } => !token.trim().is_empty(),
+            PtzCommand::SetPreset { name, .. } => !name.trim().is_empty(),
+            PtzCommand::Stop | PtzCommand::ListPresets => true,
+        }
+    }
+}
+
+fn vector(pan: f64, tilt: f64, zoom: f64) -> String {
+    format!(r#"<tt:PanTilt x="{}" y="{}"/><tt:Zoom x="{}"/>"#, pan, tilt, zoom)
+}
+
+// ONVIF timeouts are xs:duration values
+fn duration(timeout_ms: Option<u64>) -> String {
+    timeout_ms
+        .map(|ms| format!("<ns:Timeout>PT{}S</ns:Timeout>", ms as f64 / 1000.0))
+        .unwrap_or_default()
+}
+
+// The profile PTZ commands apply to: the first one we know of, asking the
+// camera when refresh-info hasn't run yet
+async fn profile_token(client: &Client, camera: &Camera) -> Result<String, OnvifError> {
+    if let Some(profile) = camera.media_profiles.first() {
+        return Ok(profile.token.clone());
+    }
+
+    let media_url = client.service_url("Media").await?;
+    let body = client.call(&media_url, MEDIA_NAMESPACE, "<ns:GetProfiles/>").await?;
+    xml::elements(&body, "Profiles")
+        .first()
+        .and_then(|profile| profile.attribute("token"))
+        .map(str::to_string)
+        .ok_or_else(|| OnvifError::Parse("camera has no media profiles".to_string()))
+}
+
+pub async fn execute(camera: &Camera, command: PtzCommand) -> Result<PtzOutcome, OnvifError> {
+    let client = Client::new(camera, Duration::from_secs(10))?;
+    let ptz_url = client.service_url("PTZ").await?;
+    let profile = xml::escape(&profile_token(&client, camera).await?);
+    let profile_token = format!("<ns:ProfileToken>{}</ns:ProfileToken>", profile);
+
+    let request = match &command {
+        PtzCommand::ContinuousMove { pan, tilt, zoom, timeout_ms } => format!(
+            "<ns:ContinuousMove>{}<ns:Velocity>{}</ns:Velocity>{}</ns:ContinuousMove>",
+            profile_token, vector(*pan, *tilt, *zoom), duration(*timeout_ms)
+        ),
+        PtzCommand::Zoom { speed, timeout_ms } => format!(
+            r#"<ns:ContinuousMove>{}<ns:Velocity><tt:Zoom x="{}"/></ns:Velocity>{}</ns:ContinuousMove>"#,
+            profile_token, speed, duration(*timeout_ms)
+        ),
+        PtzCommand::Stop => format!(
+            "<ns:Stop>{}<ns:PanTilt>true</ns:PanTilt><ns:Zoom>true</ns:Zoom></ns:Stop>",
+            profile_token
+        ),
+        PtzCommand::AbsoluteMove { pan, tilt, zoom } => format!(
+            "<ns:AbsoluteMove>{}<ns:Position>{}</ns:Position></ns:AbsoluteMove>",
+            profile_token, vector(*pan, *tilt, *zoom)
+        ),
+        PtzCommand::RelativeMove { pan, tilt, zoom } => format!(
+            "<ns:RelativeMove>{}<ns:Translation>{}</ns:Translation></ns:RelativeMove>",
+            profile_token, vector(*pan, *tilt, *zoom)
+        ),
+        PtzCommand::ListPresets => format!("<ns:GetPresets>{}</ns:GetPresets>", profile_token),
+        PtzCommand::GotoPreset { token } => format!(
+            "<ns:GotoPreset>{}<ns:PresetToken>{}</ns:PresetToken></ns:GotoPreset>",
+            profile_token, xml::escape(token)
+        ),
+        PtzCommand::SetPreset { name, token } => format!(
+            "<ns:SetPreset>{}<ns:PresetName>{}</ns:PresetName>{}</ns:SetPreset>",
+            profile_token,
+            xml::escape(name),
+            token
+                .as_ref()
+                .map(|t| format!("<ns:PresetToken>{}</ns:PresetToken>", xml::escape(t)))
+                .unwrap_or_default()
+        ),
+    };
+
+    let body = client.call(&ptz_url, PTZ_NAMESPACE, &request).await?;
+
+    match command {
+        PtzCommand::ListPresets => Ok(PtzOutcome::Presets(
+            xml::elements(&body, "Preset")
+                .into_iter()
+                .map(|preset| PtzPreset {
+                    token: preset.attribute("token").unwrap_or_default().to_string(),
+                    name: xml::text(preset.text, "Name").map(xml::unescape).unwrap_or_default(),
+                })
+                .collect(),
+        )),
+        PtzCommand::SetPreset { .. } => xml::text(&body, "PresetToken")
+            .map(|token| PtzOutcome::PresetSaved(xml::unescape(token)))
+            .ok_or_else(|| OnvifError::Parse("no preset token in response".to_string())),
+        _ => Ok(PtzOutcome::Done),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::MediaProfile;
+    use crate::onvif::testing::{self, MockDevice, PASSWORD};
+
+    fn respond(base: &str, request: &str) -> Option<String> {
+        let body = if request.contains("GetCapabilities") {
+            format!("<tds:GetCapabilitiesResponse><tds:Capabilities><tt:Media><tt:XAddr>{0}/onvif/media_service</tt:XAddr></tt:Media><tt:PTZ><tt:XAddr>{0}/onvif/ptz_service</tt:XAddr></tt:PTZ></tds:Capabilities></tds:GetCapabilitiesResponse>", base)
+        } else if request.contains("GetProfiles") {
+            "<trt:GetProfilesResponse><trt:Profiles token=\"main\" fixed=\"true\"><tt:Name>Main Stream</tt:Name></trt:Profiles><trt:Profiles token=\"sub\"><tt:Name>Sub Stream</tt:Name></trt:Profiles></trt:GetProfilesResponse>".to_string()
+        } else if request.contains("<ns:GetPresets>") {
+            "<tptz:GetPresetsResponse><tptz:Preset token=\"1\"><tt:Name>Front &amp; Gate</tt:Name><tt:PTZPosition><tt:PanTilt x=\"0.1\" y=\"0.2\"/></tt:PTZPosition></tptz:Preset><tptz:Preset token=\"2\"></tptz:Preset></tptz:GetPresetsResponse>".to_string()
+        } else if request.contains("<ns:SetPreset>") {
+            "<tptz:SetPresetResponse><tptz:PresetToken>7</tptz:PresetToken></tptz:SetPresetResponse>".to_string()
+        } else if request.contains("<ns:ContinuousMove>") {
+            "<tptz:ContinuousMoveResponse/>".to_string()
+        } else if request.contains("<ns:Stop>") {
+            "<tptz:StopResponse/>".to_string()
+        } else if request.contains("<ns:GotoPreset>") {
+            "<tptz:GotoPresetResponse/>".to_string()
+        } else {
+            return None;
+        };
+
+        Some(body)
+    }
+
+    // A PTZ camera whose profiles are already known, so commands go straight to the PTZ service
+    fn ptz_camera() -> (MockDevice, Camera) {
+        let device = testing::serve(respond);
+        let mut camera = device.camera(PASSWORD);
+        camera.media_profiles = vec![MediaProfile { token: "main".to_string(), ..MediaProfile::default() }];
+        (device, camera)
+    }
+
+    // The SOAP body of the last request the device got
+    fn last_body(device: &MockDevice) -> String {
+        let requests = device.requests();
+        xml::text(requests.last().unwrap(), "Body").unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn continuous_move_sends_a_velocity_and_timeout() {
+        let (device, camera) = ptz_camera();
+        let command = PtzCommand::ContinuousMove { pan: 0.5, tilt: -0.25, zoom: 0.0, timeout_ms: Some(1500) };
+        assert!(matches!(execute(&camera, command).await, Ok(PtzOutcome::Done)));
+
+        assert_eq!(
+            last_body(&device),
+            r#"<ns:ContinuousMove><ns:ProfileToken>main</ns:ProfileToken><ns:Velocity><tt:PanTilt x="0.5" y="-0.25"/><tt:Zoom x="0"/></ns:Velocity><ns:Timeout>PT1.5S</ns:Timeout></ns:ContinuousMove>"#,
+        );
+        assert!(device.requests().last().unwrap().contains(&format!(r#"xmlns:ns="{}""#, PTZ_NAMESPACE)));
+    }
+
+    #[tokio::test]
+    async fn stop_halts_pan_tilt_and_zoom() {
+        let (device, camera) = ptz_camera();
+        assert!(matches!(execute(&camera, PtzCommand::Stop).await, Ok(PtzOutcome::Done)));
+
+        assert_eq!(
+            last_body(&device),
+            "<ns:Stop><ns:ProfileToken>main</ns:ProfileToken><ns:PanTilt>true</ns:PanTilt><ns:Zoom>true</ns:Zoom></ns:Stop>",
+        );
+        // Capabilities, then the command itself
+        assert_eq!(device.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn goto_preset_escapes_the_token() {
+        let (device, camera) = ptz_camera();
+        let command = PtzCommand::GotoPreset { token: "a&b".to_string() };
+        assert!(matches!(execute(&camera, command).await, Ok(PtzOutcome::Done)));
+
+        assert_eq!(
+            last_body(&device),
+            "<ns:GotoPreset><ns:ProfileToken>main</ns:ProfileToken><ns:PresetToken>a&amp;b</ns:PresetToken></ns:GotoPreset>",
+        );
+    }
+
+    #[tokio::test]
+    async fn presets_are_listed_and_saved() {
+        // No profiles known yet, so the first one is asked for over Media
+        let device = testing::serve(respond);
+        let camera = device.camera(PASSWORD);
+
+        let Ok(PtzOutcome::Presets(presets)) = execute(&camera, PtzCommand::ListPresets).await else {
+            panic!("expected presets");
+        };
+        let presets: Vec<_> = presets.into_iter().map(|p| (p.token, p.name)).collect();
+        assert_eq!(presets, [
+            ("1".to_string(), "Front & Gate".to_string()),
+            ("2".to_string(), String::new()),
+        ]);
+        assert_eq!(last_body(&device), "<ns:GetPresets><ns:ProfileToken>main</ns:ProfileToken></ns:GetPresets>");
+
+        let command = PtzCommand::SetPreset { name: "Dock <2>".to_string(), token: None };
+        let Ok(PtzOutcome::PresetSaved(token)) = execute(&camera, command).await else {
+            panic!("expected a preset token");
+        };
+        assert_eq!(token, "7");
+        assert_eq!(
+            last_body(&device),
+            "<ns:SetPreset><ns:ProfileToken>main</ns:ProfileToken><ns:PresetName>Dock &lt;2&gt;</ns:PresetName></ns:SetPreset>",
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_unauthorized() {
+        let device = testing::serve(respond);
+        let result = execute(&device.camera("wrong"), PtzCommand::Stop).await;
+
+        assert!(matches!(result, Err(OnvifError::Unauthorized)));
+        assert!(device.requests().is_empty());
+    }
+}
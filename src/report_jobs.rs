@@ -0,0 +1,314 @@
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::mock_data::{
+    Report, ReportType, ReportFormat, ReportStatus, WebhookEvent,
+    get_report, add_report, update_report_status, store_report_artifact,
+    get_settings, next_id, now_timestamp,
+};
+use crate::reports::{build_table, render, report_type_label, RenderError};
+use crate::report_retention;
+use crate::email;
+use crate::webhooks;
+use crate::permissions::{camera_access_for, CameraAccess};
+
+// A queued request to generate the artifact for an existing report record
+struct ReportJob {
+    report_id: String,
+    type_: ReportType,
+    format: ReportFormat,
+    from: String,
+    to: String,
+    site_id: Option<String>,
+    // Cameras the report's creator may see, fixed when the job is queued
+    access: CameraAccess,
+    // Addresses the finished report is emailed to
+    recipients: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum EnqueueError {
+    NotStarted,
+    QueueFull,
+}
+
+static QUEUE: OnceCell<mpsc::Sender<ReportJob>> = OnceCell::new();
+
+// Cancellation flags for jobs that are queued or running, keyed by report ID
+static CANCEL_FLAGS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// Spawn a fixed number of workers sharing a bounded job queue
+pub fn start_workers(workers: usize, queue_size: usize) {
+    let (sender, receiver) = mpsc::channel::<ReportJob>(queue_size.max(1));
+    let receiver = Arc::new(tokio::sync::Mutex::new(receiver));
+
+    for _ in 0..workers.max(1) {
+        let receiver = receiver.clone();
+        tokio::spawn(async move {
+            loop {
+                let job = receiver.lock().await.recv().await;
+                match job {
+                    Some(job) => run_job(job).await,
+                    None => break,
+                }
+            }
+        });
+    }
+
+    if QUEUE.set(sender).is_err() {
+        eprintln!("Report workers were already started");
+    }
+}
+
+// Store the report and queue its job. A slot is reserved first, so a full
+// queue leaves no report record behind.
+fn enqueue(queue: &mpsc::Sender<ReportJob>, report: Report, job: ReportJob) -> Result<Report, EnqueueError> {
+    let permit = queue.try_reserve().map_err(|_| EnqueueError::QueueFull)?;
+
+    add_report(report.clone());
+    CANCEL_FLAGS
+        .lock()
+        .unwrap()
+        .insert(report.id.clone(), Arc::new(AtomicBool::new(false)));
+    permit.send(job);
+
+    Ok(report)
+}
+
+// Create a report record and queue the job that generates its artifact.
+// `from`/`to` are inclusive YYYY-MM-DD dates; empty means unbounded.
+// `site_id` limits camera data to one site. Once the report is done it is
+// emailed to `recipients`, if there are any.
+#[allow(clippy::too_many_arguments)]
+pub fn submit(
+    name: Option<String>,
+    type_: ReportType,
+    format: ReportFormat,
+    from: String,
+    to: String,
+    created_by: String,
+    site_id: Option<String>,
+    recipients: Vec<String>,
+) -> Result<Report, EnqueueError> {
+    let queue = QUEUE.get().ok_or(EnqueueError::NotStarted)?;
+    let (report, job) = new_report(name, type_, format, from, to, created_by, site_id, recipients);
+    enqueue(queue, report, job)
+}
+
+// The queued report record and the job that will generate it
+#[allow(clippy::too_many_arguments)]
+fn new_report(
+    name: Option<String>,
+    type_: ReportType,
+    format: ReportFormat,
+    from: String,
+    to: String,
+    created_by: String,
+    site_id: Option<String>,
+    recipients: Vec<String>,
+) -> (Report, ReportJob) {
+    let id = next_id();
+    let period = if from.is_empty() && to.is_empty() {
+        "All time".to_string()
+    } else {
+        format!("{} to {}", from, to)
+    };
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        format!("{}_{}", report_type_label(&type_).replace(' ', "_"), id)
+    });
+
+    let report = Report {
+        id: id.clone(),
+        name,
+        type_: type_.clone(),
+        created_at: now_timestamp(),
+        created_by,
+        period,
+        format: format.clone(),
+        url: format!("/api/reports/{}/download", id),
+        status: ReportStatus::Queued,
+        progress: 0,
+        error: None,
+        site_id: site_id.clone(),
+    };
+
+    let access = camera_access_for(&report.created_by);
+    let job = ReportJob { report_id: id, type_, format, from, to, site_id, access, recipients };
+    (report, job)
+}
+
+// Request cancellation of a queued or running job. Returns false if the job
+// has already finished or never existed.
+pub fn cancel(report_id: &str) -> bool {
+    let flag = CANCEL_FLAGS.lock().unwrap().get(report_id).cloned();
+
+    match flag {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            // Queued jobs are marked right away; running ones stop at the next progress check
+            if get_report(report_id).map(|r| r.status) == Some(ReportStatus::Queued) {
+                update_report_status(report_id, ReportStatus::Cancelled, 0, None);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+// Tracks a running job: records each new percentage on its report and says
+// whether rendering should go on
+struct Progress {
+    report_id: String,
+    flag: Arc<AtomicBool>,
+    last: u8,
+}
+
+impl Progress {
+    fn update(&mut self, percent: u8) -> bool {
+        if percent != self.last {
+            self.last = percent;
+            update_report_status(&self.report_id, ReportStatus::Running, percent, None);
+        }
+        !self.flag.load(Ordering::SeqCst)
+    }
+}
+
+async fn run_job(job: ReportJob) {
+    let flag = CANCEL_FLAGS
+        .lock()
+        .unwrap()
+        .get(&job.report_id)
+        .cloned()
+        .unwrap_or_default();
+
+    if flag.load(Ordering::SeqCst) {
+        CANCEL_FLAGS.lock().unwrap().remove(&job.report_id);
+        return;
+    }
+
+    let report_id = job.report_id.clone();
+    let recipients = job.recipients.clone();
+    update_report_status(&report_id, ReportStatus::Running, 0, None);
+
+    // Generation is CPU-bound, so keep it off the async worker threads
+    let result = tokio::task::spawn_blocking(move || {
+        let table = build_table(&job.type_, &job.from, &job.to, job.site_id.as_deref(), &job.access);
+        let mut progress = Progress { report_id: job.report_id, flag, last: 0 };
+
+        render(&table, &job.format, &mut |percent| progress.update(percent))
+            .map_err(|e| (e, progress.last))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(data)) => {
+            store_report_artifact(&report_id, data);
+            if let Some(report) = update_report_status(&report_id, ReportStatus::Done, 100, None) {
+                email::notify_report(&report, recipients);
+                webhooks::emit(WebhookEvent::ReportReady, &report);
+            }
+            // Apply the storage quota as soon as a new file lands
+            report_retention::enforce(&get_settings(), chrono::Utc::now());
+        }
+        Ok(Err((RenderError::Cancelled, progress))) => {
+            update_report_status(&report_id, ReportStatus::Cancelled, progress, None);
+        }
+        Ok(Err((RenderError::Failed(message), progress))) => {
+            eprintln!("Report job {} failed: {}", report_id, message);
+            update_report_status(&report_id, ReportStatus::Failed, progress, Some(message));
+        }
+        Err(e) => {
+            eprintln!("Report job {} failed: {}", report_id, e);
+            update_report_status(&report_id, ReportStatus::Failed, 0, Some(e.to_string()));
+        }
+    }
+
+    CANCEL_FLAGS.lock().unwrap().remove(&report_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::get_report_artifact;
+
+    fn report(name: &str) -> (Report, ReportJob) {
+        new_report(
+            Some(name.to_string()),
+            ReportType::UsageSummary,
+            ReportFormat::CSV,
+            String::new(),
+            String::new(),
+            "1".to_string(),
+            None,
+            Vec::new(),
+        )
+    }
+
+    fn status(report: &Report) -> (ReportStatus, u8) {
+        get_report(&report.id).map(|r| (r.status, r.progress)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_rejects_without_storing_a_report() {
+        let (queue, _receiver) = mpsc::channel(1);
+        let (first, job) = report("queue-full-first");
+        assert!(enqueue(&queue, first.clone(), job).is_ok());
+
+        let (second, job) = report("queue-full-second");
+        assert!(matches!(enqueue(&queue, second.clone(), job), Err(EnqueueError::QueueFull)));
+        assert_eq!(status(&first), (ReportStatus::Queued, 0));
+        assert!(get_report(&second.id).is_none());
+        assert!(!cancel(&second.id));
+    }
+
+    #[tokio::test]
+    async fn jobs_run_to_done_and_cancelled_jobs_never_start() {
+        let (queue, mut receiver) = mpsc::channel(2);
+        let (done, job) = report("run-done");
+        enqueue(&queue, done.clone(), job).unwrap();
+        let (cancelled, job) = report("run-cancelled");
+        enqueue(&queue, cancelled.clone(), job).unwrap();
+
+        assert!(cancel(&cancelled.id));
+        assert_eq!(status(&cancelled), (ReportStatus::Cancelled, 0));
+
+        run_job(receiver.recv().await.unwrap()).await;
+        assert_eq!(status(&done), (ReportStatus::Done, 100));
+        assert!(get_report_artifact(&done.id).is_some());
+
+        run_job(receiver.recv().await.unwrap()).await;
+        assert_eq!(status(&cancelled), (ReportStatus::Cancelled, 0));
+        assert!(get_report_artifact(&cancelled.id).is_none());
+
+        // Finished jobs can't be cancelled any more
+        assert!(!cancel(&done.id));
+        assert!(!cancel(&cancelled.id));
+    }
+
+    #[test]
+    fn progress_is_recorded_until_cancelled() {
+        let (report, _) = report("progress");
+        add_report(report.clone());
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut progress = Progress { report_id: report.id.clone(), flag: flag.clone(), last: 0 };
+
+        assert!(progress.update(40));
+        assert_eq!(status(&report), (ReportStatus::Running, 40));
+        assert!(progress.update(40));
+        assert_eq!(progress.last, 40);
+
+        // Cancelling a running job stops rendering at its next progress check
+        flag.store(true, Ordering::SeqCst);
+        assert!(!progress.update(60));
+        assert_eq!(status(&report), (ReportStatus::Running, 60));
+
+        let table = build_table(&ReportType::UsageSummary, "", "", None, &CameraAccess::All);
+        let result = render(&table, &ReportFormat::CSV, &mut |percent| progress.update(percent));
+        assert!(matches!(result, Err(RenderError::Cancelled)));
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::mock_data::{
+    Report, ReportStatus, Settings,
+    get_reports, get_report_artifact_sizes, get_settings, delete_report, parse_timestamp,
+};
+
+// How often expired reports are cleaned up in the background
+const TICK_SECONDS: u64 = 3600;
+
+#[derive(Serialize)]
+pub struct StorageUsage {
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub reports: usize,
+    pub stored_files: usize,
+}
+
+pub fn storage_usage() -> StorageUsage {
+    let sizes = get_report_artifact_sizes();
+
+    StorageUsage {
+        used_bytes: sizes.values().sum(),
+        quota_bytes: get_settings().report_storage_quota_mb * 1024 * 1024,
+        reports: get_reports().len(),
+        stored_files: sizes.len(),
+    }
+}
+
+// Queued and running reports are never removed from under their job
+fn is_finished(report: &Report) -> bool {
+    matches!(report.status, ReportStatus::Done | ReportStatus::Failed | ReportStatus::Cancelled)
+}
+
+// Pick the reports to remove: those past the retention age, then the oldest
+// stored files until usage fits the quota. A retention or quota of 0 turns
+// that rule off. IDs come back in the order they should be deleted.
+fn select_for_removal(
+    settings: &Settings,
+    now: DateTime<Utc>,
+    reports: Vec<Report>,
+    sizes: &HashMap<String, u64>,
+) -> Vec<String> {
+    let mut removed = Vec::new();
+
+    let mut reports: Vec<(DateTime<Utc>, Report)> = reports
+        .into_iter()
+        .filter(is_finished)
+        .filter_map(|r| parse_timestamp(&r.created_at).map(|created| (created, r)))
+        .collect();
+    reports.sort_by_key(|(created, _)| *created);
+
+    if settings.report_retention_days > 0 {
+        let cutoff = now - Duration::days(settings.report_retention_days as i64);
+        reports.retain(|(created, report)| {
+            if *created < cutoff {
+                removed.push(report.id.clone());
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    if settings.report_storage_quota_mb > 0 {
+        let quota = settings.report_storage_quota_mb * 1024 * 1024;
+        let mut used: u64 = sizes.iter().filter(|(id, _)| !removed.contains(id)).map(|(_, size)| size).sum();
+
+        // The newest file is always kept, even if it alone exceeds the quota
+        let mut stored: Vec<&Report> = reports.iter().map(|(_, r)| r).filter(|r| sizes.contains_key(&r.id)).collect();
+        stored.pop();
+
+        for report in stored {
+            if used <= quota {
+                break;
+            }
+            used -= sizes[&report.id];
+            removed.push(report.id.clone());
+        }
+    }
+
+    removed
+}
+
+// Apply the retention policy in `settings` to the stored reports as of `now`.
+// Returns the IDs of the removed reports.
+pub fn enforce(settings: &Settings, now: DateTime<Utc>) -> Vec<String> {
+    let removed: Vec<String> = select_for_removal(settings, now, get_reports(), &get_report_artifact_sizes())
+        .into_iter()
+        .filter(|id| delete_report(id))
+        .collect();
+
+    if !removed.is_empty() {
+        println!("Removed {} report(s) by retention policy: {}", removed.len(), removed.join(", "));
+    }
+    removed
+}
+
+// Spawn the background task that applies the retention policy periodically
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            enforce(&get_settings(), Utc::now());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::{ReportFormat, ReportType, add_report, get_report, store_report_artifact};
+
+    const MB: u64 = 1024 * 1024;
+
+    fn report(id: &str, created_at: DateTime<Utc>, status: ReportStatus) -> Report {
+        Report {
+            id: id.to_string(),
+            name: id.to_string(),
+            type_: ReportType::UsageSummary,
+            created_at: created_at.to_rfc3339(),
+            created_by: "1".to_string(),
+            period: String::new(),
+            format: ReportFormat::CSV,
+            url: String::new(),
+            status,
+            progress: 100,
+            error: None,
+            site_id: None,
+        }
+    }
+
+    fn policy(retention_days: u32, quota_mb: u64) -> Settings {
+        let mut settings = get_settings();
+        settings.report_retention_days = retention_days;
+        settings.report_storage_quota_mb = quota_mb;
+        settings
+    }
+
+    fn sizes(entries: &[(&str, u64)]) -> HashMap<String, u64> {
+        entries.iter().map(|(id, size)| (id.to_string(), *size)).collect()
+    }
+
+    #[test]
+    fn reports_past_retention_expire() {
+        let now = Utc::now();
+        let reports = vec![
+            report("expired", now - Duration::days(31), ReportStatus::Done),
+            report("expired-failed", now - Duration::days(40), ReportStatus::Failed),
+            // Still queued, so its job owns it
+            report("expired-queued", now - Duration::days(40), ReportStatus::Queued),
+            report("recent", now - Duration::days(29), ReportStatus::Done),
+        ];
+
+        let removed = select_for_removal(&policy(30, 0), now, reports, &sizes(&[("expired", MB), ("recent", MB)]));
+        assert_eq!(removed, ["expired-failed", "expired"]);
+    }
+
+    #[test]
+    fn quota_evicts_the_oldest_files_first() {
+        let now = Utc::now();
+        let reports = vec![
+            report("newest", now - Duration::hours(1), ReportStatus::Done),
+            report("oldest", now - Duration::hours(4), ReportStatus::Done),
+            report("middle", now - Duration::hours(3), ReportStatus::Done),
+            report("no-file", now - Duration::hours(5), ReportStatus::Failed),
+            report("newer", now - Duration::hours(2), ReportStatus::Done),
+        ];
+        let stored = sizes(&[("newest", MB), ("oldest", MB), ("middle", MB), ("newer", MB)]);
+
+        // Four files in a 2 MB quota: the two oldest go
+        assert_eq!(select_for_removal(&policy(0, 2), now, reports.clone(), &stored), ["oldest", "middle"]);
+        assert!(select_for_removal(&policy(0, 4), now, reports.clone(), &stored).is_empty());
+
+        // The newest file stays even when it alone is over the quota
+        let large = sizes(&[("newest", 5 * MB), ("newer", MB)]);
+        assert_eq!(select_for_removal(&policy(0, 1), now, reports, &large), ["newer"]);
+    }
+
+    #[test]
+    fn expired_files_count_towards_the_quota_once() {
+        let now = Utc::now();
+        let reports = vec![
+            report("expired", now - Duration::days(60), ReportStatus::Done),
+            report("older", now - Duration::hours(2), ReportStatus::Done),
+            report("newer", now - Duration::hours(1), ReportStatus::Done),
+        ];
+        let stored = sizes(&[("expired", MB), ("older", MB), ("newer", MB)]);
+
+        // Expiring the old report already brings usage down to the quota
+        assert_eq!(select_for_removal(&policy(30, 2), now, reports, &stored), ["expired"]);
+    }
+
+    #[test]
+    fn zero_quota_and_retention_keep_everything() {
+        let now = Utc::now();
+        add_report(report("retention-old", now - Duration::days(400), ReportStatus::Done));
+        store_report_artifact("retention-old", vec![0; 1024]);
+        add_report(report("retention-new", now, ReportStatus::Done));
+        store_report_artifact("retention-new", vec![0; 1024]);
+
+        let removed = enforce(&policy(0, 0), now);
+        assert!(removed.is_empty());
+        assert!(get_report("retention-old").is_some());
+        assert!(get_report("retention-new").is_some());
+    }
+}
@@ -0,0 +1,943 @@
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+use crate::mock_data::{
+    Camera, CameraStatusChange, ReportType, ReportFormat, CameraStatus, CameraFilter,
+    get_cameras_in, get_site, get_users, get_activity_logs, get_status_history, get_settings, now_timestamp, format_timestamp,
+};
+use crate::permissions::CameraAccess;
+use crate::uptime::{period_range, status_intervals, totals, outages};
+
+// Bumped whenever the JSON output changes in a way consumers could notice
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+    Timestamp,
+}
+
+impl ColumnType {
+    fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Integer => "integer",
+            ColumnType::Number => "number",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Timestamp => "timestamp",
+        }
+    }
+}
+
+pub struct Column {
+    // Stable machine-readable name used as the JSON field
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: ColumnType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    Boolean(bool),
+    // RFC 3339 timestamp string
+    Timestamp(String),
+    // No value, e.g. the end of an outage that is still ongoing
+    Empty,
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Text(text) | Cell::Timestamp(text) => write!(f, "{}", text),
+            Cell::Integer(value) => write!(f, "{}", value),
+            Cell::Number(value) => write!(f, "{:.2}", value),
+            Cell::Boolean(value) => write!(f, "{}", if *value { "Yes" } else { "No" }),
+            Cell::Empty => Ok(()),
+        }
+    }
+}
+
+impl Cell {
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Text(text) | Cell::Timestamp(text) => json!(text),
+            Cell::Integer(value) => json!(value),
+            Cell::Number(value) => json!(value),
+            Cell::Boolean(value) => json!(value),
+            Cell::Empty => Value::Null,
+        }
+    }
+}
+
+// Tabular content shared by every output format
+pub struct ReportTable {
+    pub title: String,
+    // Name of the site the camera data was limited to
+    pub site: Option<String>,
+    pub type_: ReportType,
+    pub from: String,
+    pub to: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    // The progress callback asked the renderer to stop
+    Cancelled,
+    Failed(String),
+}
+
+// Rows are rendered in chunks so progress can be reported and cancellation checked
+const PROGRESS_CHUNK: usize = 50;
+
+// Timestamps are RFC 3339 strings, so comparing the date prefix is enough for filtering
+fn in_period(timestamp: &str, from: &str, to: &str) -> bool {
+    let date = timestamp.get(..10).unwrap_or(timestamp);
+    (from.is_empty() || date >= from) && (to.is_empty() || date <= to)
+}
+
+fn text(value: impl Into<String>) -> Cell {
+    Cell::Text(value.into())
+}
+
+fn count(value: usize) -> Cell {
+    Cell::Integer(value as i64)
+}
+
+fn hours(seconds: i64) -> Cell {
+    Cell::Number(seconds as f64 / 3600.0)
+}
+
+fn optional_text(value: Option<String>) -> Cell {
+    value.map_or(Cell::Empty, Cell::Text)
+}
+
+pub fn report_type_label(type_: &ReportType) -> &'static str {
+    match type_ {
+        ReportType::UsageSummary => "Usage Summary",
+        ReportType::CameraStatus => "Camera Status",
+        ReportType::UserActivity => "User Activity",
+        ReportType::UptimeSla => "Uptime SLA",
+        ReportType::OfflineIncidents => "Offline Incidents",
+    }
+}
+
+// Collect the data for a report from the current application state. Cameras
+// are limited to those `access` allows and, with a site, to that site; users
+// and activity logs aren't camera data and are always reported in full.
+pub fn build_table(
+    type_: &ReportType,
+    from: &str,
+    to: &str,
+    site_id: Option<&str>,
+    access: &CameraAccess,
+) -> ReportTable {
+    let filter = CameraFilter { site_id: site_id.map(str::to_string), zone_id: None };
+    let get_cameras = || access.filter(get_cameras_in(&filter));
+
+    let (columns, rows) = match type_ {
+        ReportType::UsageSummary => {
+            let cameras = get_cameras();
+            let users = get_users();
+            let logs: Vec<_> = get_activity_logs()
+                .into_iter()
+                .filter(|log| in_period(&log.timestamp, from, to))
+                .collect();
+
+            let mut rows = vec![
+                vec![text("Total cameras"), count(cameras.len())],
+                vec![text("Active cameras"), count(cameras.iter().filter(|c| c.active).count())],
+                vec![
+                    text("Online cameras"),
+                    count(cameras.iter().filter(|c| c.status == CameraStatus::Online).count()),
+                ],
+                vec![text("Registered users"), count(users.len())],
+                vec![text("Active users"), count(users.iter().filter(|u| u.active).count())],
+                vec![text("Logged actions"), count(logs.len())],
+            ];
+
+            let mut actions: Vec<String> = logs.iter().map(|log| log.action.clone()).collect();
+            actions.sort();
+            actions.dedup();
+            for action in actions {
+                let action_count = logs.iter().filter(|log| log.action == action).count();
+                rows.push(vec![text(format!("Action: {}", action)), count(action_count)]);
+            }
+
+            let columns = vec![
+                Column { key: "metric", label: "Metric", kind: ColumnType::Text },
+                Column { key: "value", label: "Value", kind: ColumnType::Integer },
+            ];
+            (columns, rows)
+        }
+        ReportType::CameraStatus => {
+            let mut cameras = get_cameras();
+            cameras.sort_by(|a, b| a.id.cmp(&b.id));
+
+            let rows = cameras
+                .into_iter()
+                .map(|camera| {
+                    vec![
+                        text(camera.id),
+                        text(camera.name),
+                        text(camera.location),
+                        text(format!("{}:{}", camera.ip_address, camera.port)),
+                        Cell::Boolean(camera.active),
+                        text(format!("{:?}", camera.status)),
+                        Cell::Timestamp(camera.last_update),
+                    ]
+                })
+                .collect();
+
+            let columns = vec![
+                Column { key: "id", label: "ID", kind: ColumnType::Text },
+                Column { key: "name", label: "Name", kind: ColumnType::Text },
+                Column { key: "location", label: "Location", kind: ColumnType::Text },
+                Column { key: "address", label: "Address", kind: ColumnType::Text },
+                Column { key: "active", label: "Active", kind: ColumnType::Boolean },
+                Column { key: "status", label: "Status", kind: ColumnType::Text },
+                Column { key: "last_update", label: "Last Update", kind: ColumnType::Timestamp },
+            ];
+            (columns, rows)
+        }
+        ReportType::UserActivity => {
+            let mut logs: Vec<_> = get_activity_logs()
+                .into_iter()
+                .filter(|log| in_period(&log.timestamp, from, to))
+                .collect();
+            logs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+            let rows = logs
+                .into_iter()
+                .map(|log| {
+                    vec![
+                        Cell::Timestamp(log.timestamp),
+                        text(log.user_id),
+                        text(log.action),
+                        text(log.target),
+                        text(log.details),
+                    ]
+                })
+                .collect();
+
+            let columns = vec![
+                Column { key: "timestamp", label: "Timestamp", kind: ColumnType::Timestamp },
+                Column { key: "user_id", label: "User", kind: ColumnType::Text },
+                Column { key: "action", label: "Action", kind: ColumnType::Text },
+                Column { key: "target", label: "Target", kind: ColumnType::Text },
+                Column { key: "details", label: "Details", kind: ColumnType::Text },
+            ];
+            (columns, rows)
+        }
+        ReportType::UptimeSla => {
+            let now = chrono::Utc::now();
+            let target = get_settings().sla_target_percent;
+            let history = get_status_history(None);
+            let (start, end) = period_range(from, to, &history, now);
+
+            let rows = uptime_sla_rows(get_cameras(), &history, start, end, target);
+
+            let columns = vec![
+                Column { key: "camera_id", label: "Camera ID", kind: ColumnType::Text },
+                Column { key: "name", label: "Name", kind: ColumnType::Text },
+                Column { key: "location", label: "Location", kind: ColumnType::Text },
+                Column { key: "online_hours", label: "Online (h)", kind: ColumnType::Number },
+                Column { key: "offline_hours", label: "Offline (h)", kind: ColumnType::Number },
+                Column { key: "maintenance_hours", label: "Maintenance (h)", kind: ColumnType::Number },
+                Column { key: "availability_percent", label: "Availability (%)", kind: ColumnType::Number },
+                Column { key: "target_percent", label: "Target (%)", kind: ColumnType::Number },
+                Column { key: "meets_target", label: "Meets Target", kind: ColumnType::Boolean },
+            ];
+            (columns, rows)
+        }
+        ReportType::OfflineIncidents => {
+            let now = chrono::Utc::now();
+            let history = get_status_history(None);
+            let (start, end) = period_range(from, to, &history, now);
+
+            let rows = offline_incident_rows(get_cameras(), &history, start, end, now);
+
+            let columns = vec![
+                Column { key: "location", label: "Location", kind: ColumnType::Text },
+                Column { key: "camera_id", label: "Camera ID", kind: ColumnType::Text },
+                Column { key: "camera_name", label: "Camera", kind: ColumnType::Text },
+                Column { key: "started_at", label: "Started", kind: ColumnType::Timestamp },
+                Column { key: "ended_at", label: "Ended", kind: ColumnType::Timestamp },
+                Column { key: "duration_minutes", label: "Duration (min)", kind: ColumnType::Integer },
+                Column { key: "acknowledged", label: "Acknowledged", kind: ColumnType::Boolean },
+                Column { key: "acknowledged_by", label: "Acknowledged By", kind: ColumnType::Text },
+                Column { key: "acknowledged_at", label: "Acknowledged At", kind: ColumnType::Timestamp },
+            ];
+            (columns, rows)
+        }
+    };
+
+    ReportTable {
+        title: report_type_label(type_).to_string(),
+        site: site_id.map(|id| get_site(id).map_or_else(|| id.to_string(), |site| site.name)),
+        type_: type_.clone(),
+        from: from.to_string(),
+        to: to.to_string(),
+        columns,
+        rows,
+    }
+}
+
+// One row per camera with its time in each status and availability against
+// the SLA target
+fn uptime_sla_rows(
+    mut cameras: Vec<Camera>,
+    history: &[CameraStatusChange],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    target: f64,
+) -> Vec<Vec<Cell>> {
+    cameras.sort_by(|a, b| a.id.cmp(&b.id));
+
+    cameras
+        .into_iter()
+        .map(|camera| {
+            let camera_history: Vec<_> = history.iter().filter(|c| c.camera_id == camera.id).cloned().collect();
+            let totals = totals(&status_intervals(&camera_history, start, end));
+            let availability = totals.availability_percent();
+
+            vec![
+                text(camera.id),
+                text(camera.name),
+                text(camera.location),
+                hours(totals.online),
+                hours(totals.offline),
+                hours(totals.maintenance),
+                availability.map_or(Cell::Empty, Cell::Number),
+                Cell::Number(target),
+                availability.map_or(Cell::Empty, |a| Cell::Boolean(a >= target)),
+            ]
+        })
+        .collect()
+}
+
+// One row per outage, grouped by location with a subtotal row closing each
+// group that had any
+fn offline_incident_rows(
+    mut cameras: Vec<Camera>,
+    history: &[CameraStatusChange],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<Vec<Cell>> {
+    cameras.sort_by(|a, b| a.location.cmp(&b.location).then_with(|| a.id.cmp(&b.id)));
+
+    let mut rows = Vec::new();
+    for group in cameras.chunk_by(|a, b| a.location == b.location) {
+        let location = &group[0].location;
+        let mut incidents = 0;
+        let mut minutes = 0;
+        let mut acknowledged = 0;
+
+        for camera in group {
+            let camera_history: Vec<_> = history.iter().filter(|c| c.camera_id == camera.id).cloned().collect();
+            for outage in outages(&camera_history, start, end, now) {
+                let duration = outage.duration(now).num_minutes();
+                incidents += 1;
+                minutes += duration;
+                acknowledged += outage.acknowledged_by.is_some() as usize;
+
+                rows.push(vec![
+                    text(location.clone()),
+                    text(camera.id.clone()),
+                    text(camera.name.clone()),
+                    Cell::Timestamp(format_timestamp(outage.start)),
+                    outage.end.map_or(Cell::Empty, |e| Cell::Timestamp(format_timestamp(e))),
+                    Cell::Integer(duration),
+                    Cell::Boolean(outage.acknowledged_by.is_some()),
+                    optional_text(outage.acknowledged_by),
+                    optional_text(outage.acknowledged_at),
+                ]);
+            }
+        }
+
+        if incidents > 0 {
+            rows.push(vec![
+                text(location.clone()),
+                Cell::Empty,
+                text(format!("Subtotal: {} incident(s), {} acknowledged", incidents, acknowledged)),
+                Cell::Empty,
+                Cell::Empty,
+                Cell::Integer(minutes),
+                Cell::Boolean(acknowledged == incidents),
+                Cell::Empty,
+                Cell::Empty,
+            ]);
+        }
+    }
+    rows
+}
+
+impl ReportTable {
+    // Period line under the title, naming the site when there is one
+    fn subtitle(&self) -> String {
+        match &self.site {
+            Some(site) => format!("Site: {} - Period: {}", site, self.period()),
+            None => format!("Period: {}", self.period()),
+        }
+    }
+
+    fn period(&self) -> String {
+        if self.from.is_empty() && self.to.is_empty() {
+            "All time".to_string()
+        } else {
+            format!("{} to {}", self.from, self.to)
+        }
+    }
+}
+
+// Render a table in the requested format. `on_progress` receives a percentage
+// and returns false when the job should stop.
+pub fn render(
+    table: &ReportTable,
+    format: &ReportFormat,
+    on_progress: &mut dyn FnMut(u8) -> bool,
+) -> Result<Vec<u8>, RenderError> {
+    let output = match format {
+        ReportFormat::CSV => render_csv(table, on_progress)?,
+        ReportFormat::PDF => render_pdf(table, on_progress)?,
+        ReportFormat::XLSX => render_xlsx(table, on_progress)?,
+        ReportFormat::JSON => render_json(table, on_progress)?,
+        ReportFormat::HTML => render_html(table, on_progress)?,
+    };
+
+    on_progress(100);
+    Ok(output)
+}
+
+// Report progress at the start of every chunk; row rendering accounts for 95%
+fn check_progress(
+    index: usize,
+    total: usize,
+    on_progress: &mut dyn FnMut(u8) -> bool,
+) -> Result<(), RenderError> {
+    if index.is_multiple_of(PROGRESS_CHUNK) && !on_progress((index * 95 / total.max(1)) as u8) {
+        return Err(RenderError::Cancelled);
+    }
+    Ok(())
+}
+
+fn finish_rows(on_progress: &mut dyn FnMut(u8) -> bool) -> Result<(), RenderError> {
+    if on_progress(95) {
+        Ok(())
+    } else {
+        Err(RenderError::Cancelled)
+    }
+}
+
+fn render_csv(table: &ReportTable, on_progress: &mut dyn FnMut(u8) -> bool) -> Result<Vec<u8>, RenderError> {
+    let mut lines = Vec::with_capacity(table.rows.len() + 1);
+    lines.push(csv_line(table.columns.iter().map(|c| c.label.to_string())));
+
+    for (index, row) in table.rows.iter().enumerate() {
+        check_progress(index, table.rows.len(), on_progress)?;
+        lines.push(csv_line(row.iter().map(|cell| cell.to_string())));
+    }
+    finish_rows(on_progress)?;
+
+    Ok(lines.join("\r\n").into_bytes())
+}
+
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn render_json(table: &ReportTable, on_progress: &mut dyn FnMut(u8) -> bool) -> Result<Vec<u8>, RenderError> {
+    let mut rows = Vec::with_capacity(table.rows.len());
+
+    for (index, row) in table.rows.iter().enumerate() {
+        check_progress(index, table.rows.len(), on_progress)?;
+        let mut object = Map::new();
+        for (column, cell) in table.columns.iter().zip(row) {
+            object.insert(column.key.to_string(), cell.to_json());
+        }
+        rows.push(Value::Object(object));
+    }
+    finish_rows(on_progress)?;
+
+    let columns: Vec<Value> = table
+        .columns
+        .iter()
+        .map(|c| json!({ "key": c.key, "label": c.label, "type": c.kind.name() }))
+        .collect();
+
+    let document = json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "report": {
+            "type": format!("{:?}", table.type_),
+            "title": table.title,
+            "site": table.site,
+            "from": table.from,
+            "to": table.to,
+            "generated_at": now_timestamp(),
+        },
+        "columns": columns,
+        "rows": rows,
+    });
+
+    serde_json::to_vec_pretty(&document).map_err(|e| RenderError::Failed(e.to_string()))
+}
+
+fn render_xlsx(table: &ReportTable, on_progress: &mut dyn FnMut(u8) -> bool) -> Result<Vec<u8>, RenderError> {
+    let failed = |e: rust_xlsxwriter::XlsxError| RenderError::Failed(e.to_string());
+
+    let header_format = Format::new().set_bold();
+    let timestamp_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+    let number_format = Format::new().set_num_format("0.00");
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(table.title.as_str()).map_err(failed)?;
+
+    for (col, column) in table.columns.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, column.label, &header_format)
+            .map_err(failed)?;
+    }
+    worksheet.set_freeze_panes(1, 0).map_err(failed)?;
+
+    for (index, row) in table.rows.iter().enumerate() {
+        check_progress(index, table.rows.len(), on_progress)?;
+        let row_num = index as u32 + 1;
+
+        for (col, cell) in row.iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(value) => worksheet.write_string(row_num, col, value.as_str()),
+                Cell::Integer(value) => worksheet.write_number(row_num, col, *value as f64),
+                Cell::Number(value) => worksheet.write_number_with_format(row_num, col, *value, &number_format),
+                Cell::Boolean(value) => worksheet.write_boolean(row_num, col, *value),
+                Cell::Timestamp(value) => match ExcelDateTime::parse_from_str(value) {
+                    Ok(datetime) => worksheet.write_datetime_with_format(row_num, col, &datetime, &timestamp_format),
+                    Err(_) => worksheet.write_string(row_num, col, value.as_str()),
+                },
+                Cell::Empty => continue,
+            }
+            .map_err(failed)?;
+        }
+    }
+    finish_rows(on_progress)?;
+
+    worksheet.autofit();
+    workbook.save_to_buffer().map_err(failed)
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Single-file HTML with inline styles so it can be embedded without extra assets
+fn render_html(table: &ReportTable, on_progress: &mut dyn FnMut(u8) -> bool) -> Result<Vec<u8>, RenderError> {
+    const CELL_STYLE: &str = "border:1px solid #d0d7de;padding:6px 10px;text-align:left;";
+    const NUMBER_STYLE: &str = "border:1px solid #d0d7de;padding:6px 10px;text-align:right;";
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n</head>\n", html_escape(&table.title)));
+    html.push_str("<body style=\"font-family:Arial,Helvetica,sans-serif;color:#1f2328;margin:24px;\">\n");
+    html.push_str(&format!(
+        "<h1 style=\"font-size:20px;margin:0 0 4px;\">{}</h1>\n<p style=\"color:#57606a;margin:0 0 16px;\">{} &middot; Generated {}</p>\n",
+        html_escape(&table.title),
+        html_escape(&table.subtitle()),
+        now_timestamp()
+    ));
+    html.push_str("<table style=\"border-collapse:collapse;font-size:13px;\">\n<thead>\n<tr style=\"background:#f6f8fa;\">");
+    for column in &table.columns {
+        html.push_str(&format!("<th style=\"{}\">{}</th>", CELL_STYLE, html_escape(column.label)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for (index, row) in table.rows.iter().enumerate() {
+        check_progress(index, table.rows.len(), on_progress)?;
+        html.push_str("<tr>");
+        for cell in row {
+            let style = match cell {
+                Cell::Integer(_) | Cell::Number(_) => NUMBER_STYLE,
+                _ => CELL_STYLE,
+            };
+            html.push_str(&format!("<td style=\"{}\">{}</td>", style, html_escape(&cell.to_string())));
+        }
+        html.push_str("</tr>\n");
+    }
+    finish_rows(on_progress)?;
+
+    html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+    Ok(html.into_bytes())
+}
+
+fn render_pdf(table: &ReportTable, on_progress: &mut dyn FnMut(u8) -> bool) -> Result<Vec<u8>, RenderError> {
+    let mut lines = Vec::with_capacity(table.rows.len() + 4);
+    lines.push(table.title.clone());
+    lines.push(table.subtitle());
+    lines.push(String::new());
+    lines.push(table.columns.iter().map(|c| c.label).collect::<Vec<_>>().join(" | "));
+
+    for (index, row) in table.rows.iter().enumerate() {
+        check_progress(index, table.rows.len(), on_progress)?;
+        lines.push(row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>().join(" | "));
+    }
+    finish_rows(on_progress)?;
+
+    Ok(write_pdf(&lines))
+}
+
+// Minimal PDF writer: monospaced text lines on A4 pages, no external dependencies
+const PDF_LINES_PER_PAGE: usize = 60;
+
+fn pdf_escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+fn write_pdf(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(PDF_LINES_PER_PAGE).collect()
+    };
+
+    // Object layout: 1 catalog, 2 page tree, 3 font, then a page/content pair per page
+    let mut objects: Vec<String> = Vec::new();
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 4 + i * 2)).collect();
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    ));
+    objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string());
+
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = String::from("BT /F1 9 Tf 11 TL 40 800 Td\n");
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj T*\n", pdf_escape(line)));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + i * 2
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut output = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(output.len());
+        output.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref_offset = output.len();
+    output.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        output.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    output.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+
+    output.into_bytes()
+}
+
+pub fn content_type(format: &ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::PDF => "application/pdf",
+        ReportFormat::CSV => "text/csv; charset=utf-8",
+        ReportFormat::XLSX => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        ReportFormat::JSON => "application/json",
+        ReportFormat::HTML => "text/html; charset=utf-8",
+    }
+}
+
+pub fn file_extension(format: &ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::PDF => "pdf",
+        ReportFormat::CSV => "csv",
+        ReportFormat::XLSX => "xlsx",
+        ReportFormat::JSON => "json",
+        ReportFormat::HTML => "html",
+    }
+}
+
+// Content-Disposition for downloading a report as `name`. Report names are
+// user input, so the quoted filename is an ASCII-only fallback and the real
+// name goes in the RFC 5987 `filename*` parameter.
+pub fn content_disposition(name: &str, format: &ReportFormat) -> String {
+    let filename = format!("{}.{}", name, file_extension(format));
+
+    let fallback: String = filename
+        .chars()
+        .map(|c| if (c.is_ascii_graphic() && !matches!(c, '"' | '\\' | '/')) || c == ' ' { c } else { '_' })
+        .collect();
+
+    let mut encoded = String::new();
+    for byte in filename.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Reader, Xlsx};
+
+    fn table() -> ReportTable {
+        ReportTable {
+            title: "Camera Status".to_string(),
+            site: Some("Headquarters".to_string()),
+            type_: ReportType::CameraStatus,
+            from: "2024-01-01".to_string(),
+            to: "2024-01-31".to_string(),
+            columns: vec![
+                Column { key: "name", label: "Name", kind: ColumnType::Text },
+                Column { key: "events", label: "Events", kind: ColumnType::Integer },
+                Column { key: "availability_percent", label: "Availability (%)", kind: ColumnType::Number },
+                Column { key: "active", label: "Active", kind: ColumnType::Boolean },
+                Column { key: "last_update", label: "Last Update", kind: ColumnType::Timestamp },
+            ],
+            rows: vec![
+                vec![
+                    text("Front <Gate> & \"Lobby\""),
+                    Cell::Integer(12),
+                    Cell::Number(99.5),
+                    Cell::Boolean(true),
+                    Cell::Timestamp("2024-01-15T08:30:00Z".to_string()),
+                ],
+                vec![text("Dock"), Cell::Integer(0), Cell::Empty, Cell::Boolean(false), Cell::Empty],
+            ],
+        }
+    }
+
+    fn render_as(format: ReportFormat) -> Vec<u8> {
+        render(&table(), &format, &mut |_| true).unwrap()
+    }
+
+    fn keys(value: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        crate::mock_data::parse_timestamp(timestamp).unwrap()
+    }
+
+    fn camera(id: &str, location: &str) -> Camera {
+        Camera { id: id.to_string(), name: format!("Camera {}", id), location: location.to_string(), ..Camera::default() }
+    }
+
+    fn change(camera_id: &str, status: CameraStatus, timestamp: &str, acknowledged_by: Option<&str>) -> CameraStatusChange {
+        CameraStatusChange {
+            id: format!("{}-{}", camera_id, timestamp),
+            camera_id: camera_id.to_string(),
+            previous_status: None,
+            status,
+            timestamp: timestamp.to_string(),
+            acknowledged_by: acknowledged_by.map(str::to_string),
+            acknowledged_at: acknowledged_by.map(|_| "2024-01-01T07:00:00Z".to_string()),
+        }
+    }
+
+    // One day of history: camera 1 has an acknowledged six hour outage,
+    // camera 2 six hours of maintenance, camera 3 an outage still open at the
+    // end of the day and camera 4 nothing recorded
+    fn seeded() -> (Vec<Camera>, Vec<CameraStatusChange>) {
+        let cameras = vec![camera("1", "Lobby"), camera("2", "Dock"), camera("3", "Lobby"), camera("4", "Yard")];
+        let history = vec![
+            change("1", CameraStatus::Online, "2024-01-01T00:00:00Z", None),
+            change("2", CameraStatus::Online, "2024-01-01T00:00:00Z", None),
+            change("3", CameraStatus::Online, "2024-01-01T00:00:00Z", None),
+            change("1", CameraStatus::Offline, "2024-01-01T06:00:00Z", Some("admin")),
+            change("1", CameraStatus::Online, "2024-01-01T12:00:00Z", None),
+            change("2", CameraStatus::Maintenance, "2024-01-01T12:00:00Z", None),
+            change("2", CameraStatus::Online, "2024-01-01T18:00:00Z", None),
+            change("3", CameraStatus::Offline, "2024-01-01T20:00:00Z", None),
+        ];
+        (cameras, history)
+    }
+
+    #[test]
+    fn uptime_sla_measures_availability_against_the_target() {
+        let (cameras, history) = seeded();
+        let rows = uptime_sla_rows(cameras, &history, at("2024-01-01T00:00:00Z"), at("2024-01-02T00:00:00Z"), 80.0);
+
+        let summary: Vec<_> = rows.iter().map(|row| (row[0].clone(), row[6].clone(), row[8].clone())).collect();
+        assert_eq!(summary, vec![
+            (text("1"), Cell::Number(75.0), Cell::Boolean(false)),
+            // Maintenance counts for neither side
+            (text("2"), Cell::Number(100.0), Cell::Boolean(true)),
+            (text("3"), Cell::Number(20.0 * 100.0 / 24.0), Cell::Boolean(true)),
+            (text("4"), Cell::Empty, Cell::Empty),
+        ]);
+        assert_eq!(rows[1][3..6], [Cell::Number(18.0), Cell::Number(0.0), Cell::Number(6.0)]);
+        assert_eq!(rows[0][7], Cell::Number(80.0));
+    }
+
+    #[test]
+    fn offline_incidents_are_grouped_by_location_with_subtotals() {
+        let (cameras, history) = seeded();
+        let rows = offline_incident_rows(
+            cameras,
+            &history,
+            at("2024-01-01T00:00:00Z"),
+            at("2024-01-02T00:00:00Z"),
+            at("2024-01-02T00:00:00Z"),
+        );
+
+        // Dock and Yard had no outages, so only Lobby shows up
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], vec![
+            text("Lobby"),
+            text("1"),
+            text("Camera 1"),
+            Cell::Timestamp("2024-01-01T06:00:00Z".to_string()),
+            Cell::Timestamp("2024-01-01T12:00:00Z".to_string()),
+            Cell::Integer(360),
+            Cell::Boolean(true),
+            text("admin"),
+            text("2024-01-01T07:00:00Z"),
+        ]);
+        assert_eq!(rows[1][..7], [
+            text("Lobby"),
+            text("3"),
+            text("Camera 3"),
+            Cell::Timestamp("2024-01-01T20:00:00Z".to_string()),
+            Cell::Empty,
+            Cell::Integer(240),
+            Cell::Boolean(false),
+        ]);
+        assert_eq!(rows[2], vec![
+            text("Lobby"),
+            Cell::Empty,
+            text("Subtotal: 2 incident(s), 1 acknowledged"),
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Integer(600),
+            Cell::Boolean(false),
+            Cell::Empty,
+            Cell::Empty,
+        ]);
+    }
+
+    #[test]
+    fn json_has_a_stable_schema() {
+        let document: Value = serde_json::from_slice(&render_as(ReportFormat::JSON)).unwrap();
+
+        assert_eq!(keys(&document), ["columns", "report", "rows", "schema_version"]);
+        assert_eq!(document["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(keys(&document["report"]), ["from", "generated_at", "site", "title", "to", "type"]);
+        assert_eq!(document["report"]["type"], "CameraStatus");
+        assert_eq!(document["report"]["site"], "Headquarters");
+
+        let columns = document["columns"].as_array().unwrap();
+        assert_eq!(columns[0], json!({ "key": "name", "label": "Name", "type": "text" }));
+        let types: Vec<&str> = columns.iter().map(|c| c["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["text", "integer", "number", "boolean", "timestamp"]);
+
+        let rows = document["rows"].as_array().unwrap();
+        assert_eq!(keys(&rows[0]), ["active", "availability_percent", "events", "last_update", "name"]);
+        assert_eq!(rows[0]["name"], "Front <Gate> & \"Lobby\"");
+        assert!(rows[0]["events"].is_i64());
+        assert!(rows[0]["availability_percent"].is_f64());
+        assert_eq!(rows[0]["active"], true);
+        assert_eq!(rows[0]["last_update"], "2024-01-15T08:30:00Z");
+        // Missing values are null rather than left out
+        assert_eq!(keys(&rows[1]), keys(&rows[0]));
+        assert!(rows[1]["availability_percent"].is_null());
+        assert!(rows[1]["last_update"].is_null());
+    }
+
+    #[test]
+    fn xlsx_has_a_header_row_and_typed_cells() {
+        let mut workbook: Xlsx<_> = calamine::open_workbook_from_rs(std::io::Cursor::new(render_as(ReportFormat::XLSX))).unwrap();
+        assert_eq!(workbook.sheet_names(), ["Camera Status"]);
+        let sheet = workbook.worksheet_range("Camera Status").unwrap();
+
+        let header: Vec<String> = (0..5).map(|col| sheet.get_value((0, col)).unwrap().to_string()).collect();
+        assert_eq!(header, ["Name", "Events", "Availability (%)", "Active", "Last Update"]);
+
+        assert_eq!(sheet.get_value((1, 0)), Some(&Data::String("Front <Gate> & \"Lobby\"".to_string())));
+        assert_eq!(sheet.get_value((1, 1)), Some(&Data::Float(12.0)));
+        assert_eq!(sheet.get_value((1, 2)), Some(&Data::Float(99.5)));
+        assert_eq!(sheet.get_value((1, 3)), Some(&Data::Bool(true)));
+        match sheet.get_value((1, 4)) {
+            Some(Data::DateTime(datetime)) => {
+                assert!(datetime.is_datetime());
+                // 2024-01-15 is Excel serial day 45306; 08:30 is 8.5 / 24 of a day
+                assert!((datetime.as_f64() - (45306.0 + 8.5 / 24.0)).abs() < 1e-6);
+            }
+            other => panic!("expected a date cell, got {:?}", other),
+        }
+        // Empty cells are left blank
+        assert!(matches!(sheet.get_value((2, 2)), None | Some(Data::Empty)));
+    }
+
+    #[test]
+    fn html_escapes_cell_values() {
+        let html = String::from_utf8(render_as(ReportFormat::HTML)).unwrap();
+
+        assert!(html.contains("Front &lt;Gate&gt; &amp; &quot;Lobby&quot;"));
+        assert!(!html.contains("<Gate>"));
+        assert!(html.contains("<th style=\"border:1px solid #d0d7de;padding:6px 10px;text-align:left;\">Availability (%)</th>"));
+        assert!(html.contains("text-align:right;\">99.50</td>"));
+        assert!(html.contains("Site: Headquarters - Period: 2024-01-01 to 2024-01-31"));
+    }
+
+    #[test]
+    fn download_filenames_cannot_break_the_header() {
+        assert_eq!(
+            content_disposition("Monthly report", &ReportFormat::CSV),
+            "attachment; filename=\"Monthly report.csv\"; filename*=UTF-8''Monthly%20report.csv",
+        );
+        assert_eq!(
+            content_disposition("a\"; filename=\"x.exe\r\nSet-Cookie: y", &ReportFormat::PDF),
+            "attachment; filename=\"a_; filename=_x.exe__Set-Cookie: y.pdf\"; \
+             filename*=UTF-8''a%22%3B%20filename%3D%22x.exe%0D%0ASet-Cookie%3A%20y.pdf",
+        );
+        assert_eq!(
+            content_disposition("Übersicht ../2024", &ReportFormat::XLSX),
+            "attachment; filename=\"_bersicht .._2024.xlsx\"; filename*=UTF-8''%C3%9Cbersicht%20..%2F2024.xlsx",
+        );
+    }
+}
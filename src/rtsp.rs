@@ -0,0 +1,433 @@
+use base64::Engine;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::mock_data::{Camera, ProbeError, StreamInfo};
+
+const USER_AGENT: &str = "rust-httpx-app";
+
+// Largest SDP body we are willing to read from a camera
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+struct Response {
+    status: u16,
+    reason: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn unexpected(&self, method: &str) -> ProbeError {
+        ProbeError::Protocol(format!("{} returned {} {}", method, self.status, self.reason))
+    }
+}
+
+// A single RTSP control connection; requests are sent one at a time
+struct Connection {
+    reader: BufReader<TcpStream>,
+    cseq: u32,
+    timeout: Duration,
+}
+
+impl Connection {
+    async fn request(&mut self, method: &str, url: &str, headers: &[(&str, String)]) -> Result<Response, ProbeError> {
+        self.cseq += 1;
+        let mut request = format!(
+            "{} {} RTSP/1.0\r\nCSeq: {}\r\nUser-Agent: {}\r\n",
+            method, url, self.cseq, USER_AGENT
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+
+        let exchange = async {
+            self.reader.get_mut().write_all(request.as_bytes()).await?;
+            read_response(&mut self.reader).await
+        };
+
+        match tokio::time::timeout(self.timeout, exchange).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(ProbeError::Protocol(format!("{} failed: {}", method, e))),
+            Err(_) => Err(ProbeError::Protocol(format!("No response to {}", method))),
+        }
+    }
+}
+
+async fn read_response(reader: &mut BufReader<TcpStream>) -> std::io::Result<Response> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "connection closed"));
+    }
+
+    let mut parts = line.trim_end().splitn(3, ' ');
+    if parts.next() != Some("RTSP/1.0") {
+        return Err(Error::new(ErrorKind::InvalidData, format!("not an RTSP response: {:?}", line.trim_end())));
+    }
+    let status = parts
+        .next()
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing status code"))?;
+    let reason = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "connection closed in headers"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(Error::new(ErrorKind::InvalidData, format!("body of {} bytes is too large", length)));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Response { status, reason, headers, body: String::from_utf8_lossy(&body).into_owned() })
+}
+
+pub fn stream_url(camera: &Camera) -> String {
+    let host = if camera.ip_address.contains(':') {
+        format!("[{}]", camera.ip_address)
+    } else {
+        camera.ip_address.clone()
+    };
+    let path = camera.stream_path.trim_start_matches('/');
+    format!("rtsp://{}:{}/{}", host, camera.port, path)
+}
+
+// Key/value pairs of an authentication header, with quoted values unwrapped
+fn parse_auth_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut rest = input;
+
+    loop {
+        rest = rest.trim_start_matches([' ', ',']);
+        let Some((key, after)) = rest.split_once('=') else { break };
+
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        params.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        rest = remaining;
+    }
+
+    params
+}
+
+fn md5_hex(input: &str) -> String {
+    format!("{:x}", md5::compute(input))
+}
+
+// Build an Authorization header answering one of the server's
+// WWW-Authenticate challenges, preferring Digest so the password never goes
+// over the wire. RTSP borrows HTTP's scheme, so snapshots use this too.
+pub fn authorization(challenges: &[&str], method: &str, uri: &str, username: &str, password: &str) -> Option<String> {
+    if let Some(digest) = challenges.iter().find_map(|c| c.strip_prefix("Digest ")) {
+        let params = parse_auth_params(digest);
+        let realm = params.get("realm")?;
+        let nonce = params.get("nonce")?;
+
+        let ha1 = md5_hex(&format!("{}:{}:{}", username, realm, password));
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\"",
+            username, realm, nonce, uri
+        );
+
+        let wants_auth_qop = params
+            .get("qop")
+            .is_some_and(|qop| qop.split(',').any(|q| q.trim() == "auth"));
+        if wants_auth_qop {
+            let nc = "00000001";
+            let cnonce = md5_hex(&format!("{}:{}", nonce, chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()));
+            let cnonce = &cnonce[..16];
+            let digest = md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, nonce, nc, cnonce, ha2));
+            header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\", response=\"{}\"", nc, cnonce, digest));
+        } else {
+            let digest = md5_hex(&format!("{}:{}:{}", ha1, nonce, ha2));
+            header.push_str(&format!(", response=\"{}\"", digest));
+        }
+
+        if let Some(opaque) = params.get("opaque") {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        return Some(header);
+    }
+
+    if challenges.iter().any(|c| c.starts_with("Basic")) {
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        return Some(format!("Basic {}", token));
+    }
+
+    None
+}
+
+// Pull codec, resolution and frame rate for the first video track out of an
+// SDP description. Resolution is only known when the camera advertises it
+// through one of the common attributes; it is not decoded from H.264 SPS.
+pub fn parse_sdp(sdp: &str) -> StreamInfo {
+    let mut info = StreamInfo::default();
+    let mut payload_type: Option<String> = None;
+    let mut in_video = false;
+
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            // Only the first video section is of interest
+            if payload_type.is_some() {
+                break;
+            }
+            in_video = media.starts_with("video");
+            if in_video {
+                payload_type = media.split_whitespace().nth(3).map(str::to_string);
+            }
+            continue;
+        }
+        if !in_video {
+            continue;
+        }
+        let Some(attribute) = line.strip_prefix("a=") else { continue };
+        let (name, value) = attribute.split_once(':').unwrap_or((attribute, ""));
+
+        match name {
+            "rtpmap" => {
+                if let Some((pt, encoding)) = value.split_once(' ') {
+                    if Some(pt) == payload_type.as_deref() {
+                        info.codec = encoding.split('/').next().map(str::to_string);
+                    }
+                }
+            }
+            "framerate" | "x-framerate" => info.frame_rate = value.trim().parse().ok(),
+            "x-dimensions" => {
+                if let Some((width, height)) = value.split_once(',') {
+                    info.width = width.trim().parse().ok();
+                    info.height = height.trim().parse().ok();
+                }
+            }
+            "framesize" => {
+                let size = value.split_whitespace().nth(1).unwrap_or_default();
+                if let Some((width, height)) = size.split_once('-') {
+                    info.width = width.parse().ok();
+                    info.height = height.parse().ok();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    info
+}
+
+// Check that the camera's stream exists by issuing OPTIONS and DESCRIBE,
+// authenticating with the camera's credentials when challenged
+pub async fn probe(camera: &Camera, timeout: Duration) -> Result<StreamInfo, ProbeError> {
+    let url = stream_url(camera);
+
+    let stream = tokio::time::timeout(timeout, TcpStream::connect((camera.ip_address.as_str(), camera.port)))
+        .await
+        .map_err(|_| ProbeError::Unreachable("Connection timed out".to_string()))?
+        .map_err(|e| ProbeError::Unreachable(e.to_string()))?;
+    let mut connection = Connection { reader: BufReader::new(stream), cseq: 0, timeout };
+
+    // Some cameras challenge OPTIONS too; authentication is handled on DESCRIBE
+    let options = connection.request("OPTIONS", &url, &[]).await?;
+    if options.status != 200 && options.status != 401 {
+        return Err(options.unexpected("OPTIONS"));
+    }
+
+    let accept = ("Accept", "application/sdp".to_string());
+    let mut describe = connection.request("DESCRIBE", &url, std::slice::from_ref(&accept)).await?;
+
+    if describe.status == 401 && !camera.username.is_empty() {
+        let challenges: Vec<&str> = describe.headers("WWW-Authenticate").collect();
+        let header = authorization(&challenges, "DESCRIBE", &url, &camera.username, &camera.password)
+            .ok_or_else(|| ProbeError::Protocol("Unsupported authentication scheme".to_string()))?;
+        describe = connection.request("DESCRIBE", &url, &[accept, ("Authorization", header)]).await?;
+    }
+
+    match describe.status {
+        200 => Ok(parse_sdp(&describe.body)),
+        401 | 403 => Err(ProbeError::AuthFailed),
+        404 => Err(ProbeError::StreamNotFound),
+        _ => Err(describe.unexpected("DESCRIBE")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const SDP: &str = "v=0\r\n\
+        o=- 0 0 IN IP4 127.0.0.1\r\n\
+        s=Fake\r\n\
+        t=0 0\r\n\
+        m=audio 0 RTP/AVP 0\r\n\
+        a=rtpmap:0 PCMU/8000\r\n\
+        m=video 0 RTP/AVP 96\r\n\
+        a=rtpmap:96 H264/90000\r\n\
+        a=framerate:25\r\n\
+        a=x-dimensions:1920,1080\r\n";
+
+    fn camera(port: u16, path: &str, username: &str, password: &str) -> Camera {
+        Camera {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+            ip_address: "127.0.0.1".to_string(),
+            port,
+            location: "Lab".to_string(),
+            active: true,
+            stream_path: path.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            ..Camera::default()
+        }
+    }
+
+    fn authorized(headers: &HashMap<String, String>, method: &str, uri: &str, credentials: Option<(&str, &str)>) -> bool {
+        let Some((username, password)) = credentials else { return true };
+        let Some(params) = headers.get("authorization").and_then(|h| h.strip_prefix("Digest ")) else {
+            return false;
+        };
+        let params = parse_auth_params(params);
+
+        let ha1 = md5_hex(&format!("{}:fake:{}", username, password));
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let expected = md5_hex(&format!(
+            "{}:abc123:{}:{}:auth:{}",
+            ha1, params.get("nc").cloned().unwrap_or_default(), params.get("cnonce").cloned().unwrap_or_default(), ha2
+        ));
+
+        params.get("username").map(String::as_str) == Some(username) && params.get("response") == Some(&expected)
+    }
+
+    // A minimal RTSP server serving one SDP at /stream1, optionally behind digest auth
+    async fn fake_server(credentials: Option<(&'static str, &'static str)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(socket);
+
+            loop {
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).await.unwrap();
+                    let Some((name, value)) = line.trim_end().split_once(':') else { break };
+                    headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                }
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap();
+                let uri = parts.next().unwrap();
+                let cseq = &headers["cseq"];
+
+                let reply = if method == "OPTIONS" {
+                    format!("RTSP/1.0 200 OK\r\nCSeq: {}\r\nPublic: OPTIONS, DESCRIBE\r\n\r\n", cseq)
+                } else if !uri.ends_with("/stream1") {
+                    format!("RTSP/1.0 404 Not Found\r\nCSeq: {}\r\n\r\n", cseq)
+                } else if !authorized(&headers, method, uri, credentials) {
+                    format!(
+                        "RTSP/1.0 401 Unauthorized\r\nCSeq: {}\r\nWWW-Authenticate: Digest realm=\"fake\", nonce=\"abc123\", qop=\"auth\"\r\n\r\n",
+                        cseq
+                    )
+                } else {
+                    format!(
+                        "RTSP/1.0 200 OK\r\nCSeq: {}\r\nContent-Type: application/sdp\r\nContent-Length: {}\r\n\r\n{}",
+                        cseq,
+                        SDP.len(),
+                        SDP
+                    )
+                };
+                reader.get_mut().write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+
+        port
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    #[tokio::test]
+    async fn describes_stream_and_parses_sdp() {
+        let port = fake_server(None).await;
+        let info = probe(&camera(port, "/stream1", "", ""), TIMEOUT).await.unwrap();
+
+        assert_eq!(info.codec.as_deref(), Some("H264"));
+        assert_eq!((info.width, info.height), (Some(1920), Some(1080)));
+        assert_eq!(info.frame_rate, Some(25.0));
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_digest() {
+        let port = fake_server(Some(("admin", "secret"))).await;
+        let info = probe(&camera(port, "stream1", "admin", "secret"), TIMEOUT).await.unwrap();
+
+        assert_eq!(info.codec.as_deref(), Some("H264"));
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_auth_failure() {
+        let port = fake_server(Some(("admin", "secret"))).await;
+        let result = probe(&camera(port, "/stream1", "admin", "wrong"), TIMEOUT).await;
+
+        assert_eq!(result, Err(ProbeError::AuthFailed));
+    }
+
+    #[tokio::test]
+    async fn missing_credentials_is_auth_failure() {
+        let port = fake_server(Some(("admin", "secret"))).await;
+        let result = probe(&camera(port, "/stream1", "", ""), TIMEOUT).await;
+
+        assert_eq!(result, Err(ProbeError::AuthFailed));
+    }
+
+    #[tokio::test]
+    async fn unknown_path_is_stream_not_found() {
+        let port = fake_server(None).await;
+        let result = probe(&camera(port, "/nope", "", ""), TIMEOUT).await;
+
+        assert_eq!(result, Err(ProbeError::StreamNotFound));
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let result = probe(&camera(port, "/stream1", "", ""), TIMEOUT).await;
+        assert!(matches!(result, Err(ProbeError::Unreachable(_))));
+    }
+}
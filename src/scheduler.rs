@@ -0,0 +1,249 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use cron::Schedule;
+use std::str::FromStr;
+
+use crate::mock_data::{
+    ActivityLog, PeriodRule, ReportSchedule,
+    get_report_schedules, record_schedule_run, add_activity_log,
+    next_id, now_timestamp, parse_timestamp,
+};
+use crate::report_jobs;
+
+// How often the scheduler looks for due report schedules
+const TICK_SECONDS: u64 = 30;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+// Unix weekday numbers (0 or 7 = Sunday, 1 = Monday) in one item of a weekday
+// list, as the cron crate's 1 = Sunday to 7 = Saturday. Numeric ranges become
+// lists, since 5-7 (Friday to Sunday) would otherwise wrap. Names and `*` mean
+// the same in both and are left alone.
+fn unix_weekday_item(item: &str) -> Result<String, String> {
+    let (base, step) = match item.split_once('/') {
+        Some((base, step)) => (base, Some(step)),
+        None => (item, None),
+    };
+    let number = |value: &str| value.parse::<u32>().ok().filter(|day| *day <= 7);
+    let bounds = match base.split_once('-') {
+        Some((first, last)) => number(first).zip(number(last)),
+        None => number(base).map(|day| (day, if step.is_some() { 7 } else { day })),
+    };
+    let Some((first, last)) = bounds else {
+        return Ok(item.to_string());
+    };
+
+    let step = match step {
+        Some(step) => step.parse::<usize>().ok().filter(|step| *step > 0).ok_or(format!("Invalid weekday step in '{}'", item))?,
+        None => 1,
+    };
+    if first > last {
+        return Err(format!("Invalid weekday range '{}'", item));
+    }
+    let days: Vec<String> = (first..=last).step_by(step).map(|day| (day % 7 + 1).to_string()).collect();
+    Ok(days.join(","))
+}
+
+// Accepts the usual five-field form (minute hour day month weekday, with Unix
+// weekday numbers) as well as the six/seven-field form with leading seconds and
+// trailing year, which the cron crate reads as is (weekdays 1 = Sunday to 7)
+pub fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let expression = expression.trim();
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let normalized = if fields.len() == 5 {
+        let weekdays = fields[4].split(',').map(unix_weekday_item).collect::<Result<Vec<_>, _>>()?;
+        format!("0 {} {}", fields[..4].join(" "), weekdays.join(","))
+    } else {
+        expression.to_string()
+    };
+
+    Schedule::from_str(&normalized).map_err(|e| format!("Invalid cron expression '{}': {}", expression, e))
+}
+
+pub fn next_run_after(expression: &str, after: DateTime<Utc>) -> Option<String> {
+    parse_cron(expression)
+        .ok()?
+        .after(&after)
+        .next()
+        .map(|time| time.format(TIMESTAMP_FORMAT).to_string())
+}
+
+fn first_of_quarter(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).unwrap()
+}
+
+// Inclusive date range a schedule's report covers when it runs on `today`
+pub fn period_bounds(rule: &PeriodRule, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let yesterday = today - Duration::days(1);
+
+    match rule {
+        PeriodRule::PreviousDay => (yesterday, yesterday),
+        PeriodRule::PreviousWeek => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (monday - Duration::days(7), monday - Duration::days(1))
+        }
+        PeriodRule::PreviousMonth => {
+            let end = today.with_day(1).unwrap() - Duration::days(1);
+            (end.with_day(1).unwrap(), end)
+        }
+        PeriodRule::PreviousQuarter => {
+            let end = first_of_quarter(today) - Duration::days(1);
+            (first_of_quarter(end), end)
+        }
+        PeriodRule::Last7Days => (today - Duration::days(7), yesterday),
+        PeriodRule::Last30Days => (today - Duration::days(30), yesterday),
+    }
+}
+
+// Spawn the background task that generates scheduled reports on time
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            run_due_schedules(Utc::now());
+        }
+    });
+}
+
+fn run_due_schedules(now: DateTime<Utc>) {
+    for schedule in get_report_schedules() {
+        if !schedule.enabled {
+            continue;
+        }
+
+        match schedule.next_run.as_deref().and_then(parse_timestamp) {
+            Some(next) if next <= now => run_schedule(&schedule, now),
+            Some(_) => {}
+            // Schedules created before the scheduler started have no next run yet
+            None => record_schedule_run(&schedule.id, None, next_run_after(&schedule.cron, now), None),
+        }
+    }
+}
+
+fn run_schedule(schedule: &ReportSchedule, now: DateTime<Utc>) {
+    let (from, to) = period_bounds(&schedule.period_rule, now.date_naive());
+    let name = format!("{}_{}", schedule.name.replace(' ', "_"), from.format("%Y-%m-%d"));
+
+    let result = report_jobs::submit(
+        Some(name),
+        schedule.type_.clone(),
+        schedule.format.clone(),
+        from.format("%Y-%m-%d").to_string(),
+        to.format("%Y-%m-%d").to_string(),
+        schedule.created_by.clone(),
+        schedule.site_id.clone(),
+        schedule.recipients.clone(),
+    );
+
+    match result {
+        Ok(report) => {
+            println!("Scheduled report '{}' queued as report {}", schedule.name, report.id);
+            add_activity_log(ActivityLog {
+                id: next_id(),
+                timestamp: now_timestamp(),
+                user_id: schedule.created_by.clone(),
+                action: "GENERATE_REPORT".to_string(),
+                target: format!("{:?}", schedule.type_),
+                details: format!("Scheduled report '{}' generated for {} to {}", schedule.name, from, to),
+            });
+            record_schedule_run(
+                &schedule.id,
+                Some(now.format(TIMESTAMP_FORMAT).to_string()),
+                next_run_after(&schedule.cron, now),
+                Some(report.id),
+            );
+        }
+        // Nothing was stored, so leave next_run untouched and retry on the next tick
+        Err(e) => eprintln!("Failed to queue scheduled report '{}': {:?}", schedule.name, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        parse_timestamp(timestamp).unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    // Weekdays of the next runs after Friday 2024-01-05 noon
+    fn next_weekdays(expression: &str, count: usize) -> Vec<String> {
+        parse_cron(expression)
+            .unwrap()
+            .after(&at("2024-01-05T12:00:00Z"))
+            .take(count)
+            .map(|time| time.format("%a").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn five_field_weekdays_use_unix_numbering() {
+        assert_eq!(next_weekdays("0 6 * * 1", 2), ["Mon", "Mon"]);
+        assert_eq!(next_weekdays("0 6 * * 1-5", 5), ["Mon", "Tue", "Wed", "Thu", "Fri"]);
+        assert_eq!(next_weekdays("0 6 * * 0", 1), ["Sun"]);
+        assert_eq!(next_weekdays("0 6 * * 7", 1), ["Sun"]);
+        assert_eq!(next_weekdays("0 6 * * 5-7", 3), ["Sat", "Sun", "Fri"]);
+        assert_eq!(next_weekdays("0 6 * * 0,6", 2), ["Sat", "Sun"]);
+        assert_eq!(next_weekdays("0 6 * * */2", 4), ["Sat", "Sun", "Tue", "Thu"]);
+        assert_eq!(next_weekdays("0 6 * * 1/2", 4), ["Sun", "Mon", "Wed", "Fri"]);
+        assert_eq!(next_weekdays("0 6 * * MON-FRI", 3), ["Mon", "Tue", "Wed"]);
+    }
+
+    #[test]
+    fn six_field_form_is_read_as_is() {
+        // The cron crate's own numbering, where 2 is Monday
+        assert_eq!(next_weekdays("0 0 6 * * 2", 1), ["Mon"]);
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!(parse_cron("not a cron").is_err());
+        assert!(parse_cron("0 6 * * 8").is_err());
+        assert!(parse_cron("0 6 * * 5-1").is_err());
+        assert!(parse_cron("0 25 * * *").is_err());
+    }
+
+    #[test]
+    fn next_run_is_the_first_match_after() {
+        let after = at("2024-01-05T12:00:00Z");
+        assert_eq!(next_run_after("0 6 * * 1", after).as_deref(), Some("2024-01-08T06:00:00Z"));
+        assert_eq!(next_run_after("30 12 * * *", after).as_deref(), Some("2024-01-05T12:30:00Z"));
+        assert_eq!(next_run_after("0 12 * * *", after).as_deref(), Some("2024-01-06T12:00:00Z"));
+        assert_eq!(next_run_after("0 6 1 * *", after).as_deref(), Some("2024-02-01T06:00:00Z"));
+        assert_eq!(next_run_after("bad", after), None);
+    }
+
+    #[test]
+    fn period_bounds_cover_the_previous_period() {
+        // Wednesday 2024-05-15
+        let today = date("2024-05-15");
+        let bounds = |rule| {
+            let (from, to) = period_bounds(&rule, today);
+            (from.to_string(), to.to_string())
+        };
+
+        assert_eq!(bounds(PeriodRule::PreviousDay), ("2024-05-14".to_string(), "2024-05-14".to_string()));
+        assert_eq!(bounds(PeriodRule::PreviousWeek), ("2024-05-06".to_string(), "2024-05-12".to_string()));
+        assert_eq!(bounds(PeriodRule::PreviousMonth), ("2024-04-01".to_string(), "2024-04-30".to_string()));
+        assert_eq!(bounds(PeriodRule::PreviousQuarter), ("2024-01-01".to_string(), "2024-03-31".to_string()));
+        assert_eq!(bounds(PeriodRule::Last7Days), ("2024-05-08".to_string(), "2024-05-14".to_string()));
+        assert_eq!(bounds(PeriodRule::Last30Days), ("2024-04-15".to_string(), "2024-05-14".to_string()));
+    }
+
+    #[test]
+    fn period_bounds_cross_year_boundaries() {
+        let (from, to) = period_bounds(&PeriodRule::PreviousMonth, date("2024-01-01"));
+        assert_eq!((from, to), (date("2023-12-01"), date("2023-12-31")));
+
+        let (from, to) = period_bounds(&PeriodRule::PreviousQuarter, date("2024-02-29"));
+        assert_eq!((from, to), (date("2023-10-01"), date("2023-12-31")));
+
+        // On a Monday the previous week ends yesterday
+        let (from, to) = period_bounds(&PeriodRule::PreviousWeek, date("2024-01-01"));
+        assert_eq!((from, to), (date("2023-12-25"), date("2023-12-31")));
+    }
+}
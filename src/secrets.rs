@@ -0,0 +1,134 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use std::sync::RwLock;
+
+// AES-256-GCM key that camera secrets are encrypted with at rest
+#[derive(Clone)]
+pub struct MasterKey([u8; 32]);
+
+impl MasterKey {
+    pub fn generate() -> MasterKey {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        MasterKey(key)
+    }
+
+    pub fn from_base64(value: &str) -> Result<MasterKey, SecretError> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(value.trim())
+            .map_err(|_| SecretError::InvalidKey)?;
+        let key: [u8; 32] = bytes.try_into().map_err(|_| SecretError::InvalidKey)?;
+        Ok(MasterKey(key))
+    }
+
+    // Short fingerprint recorded with each secret so we can tell which key sealed it
+    pub fn id(&self) -> String {
+        format!("{:x}", md5::compute(self.0))[..8].to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SealedSecret {
+    pub key_id: String,
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SecretError {
+    // Not 32 bytes of base64
+    InvalidKey,
+    // Sealed with a key other than the current one
+    WrongKey(String),
+    // The ciphertext failed authentication
+    Corrupted,
+}
+
+impl std::fmt::Display for SecretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SecretError::InvalidKey => write!(f, "master key must be 32 bytes encoded as base64"),
+            SecretError::WrongKey(key_id) => write!(f, "secret was sealed with key {}", key_id),
+            SecretError::Corrupted => write!(f, "secret could not be decrypted"),
+        }
+    }
+}
+
+// Until a key is configured, secrets only live as long as the process
+static MASTER_KEY: Lazy<RwLock<MasterKey>> = Lazy::new(|| RwLock::new(MasterKey::generate()));
+
+pub fn set_master_key(key: MasterKey) {
+    *MASTER_KEY.write().unwrap() = key;
+}
+
+pub fn current_key_id() -> String {
+    MASTER_KEY.read().unwrap().id()
+}
+
+pub fn seal_with(key: &MasterKey, plaintext: &[u8]) -> SealedSecret {
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.0));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .expect("AES-GCM encryption does not fail for in-memory buffers");
+
+    SealedSecret { key_id: key.id(), nonce, ciphertext }
+}
+
+pub fn open_with(key: &MasterKey, sealed: &SealedSecret) -> Result<Vec<u8>, SecretError> {
+    if sealed.key_id != key.id() {
+        return Err(SecretError::WrongKey(sealed.key_id.clone()));
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.0));
+    cipher
+        .decrypt(Nonce::from_slice(&sealed.nonce), sealed.ciphertext.as_ref())
+        .map_err(|_| SecretError::Corrupted)
+}
+
+pub fn seal(plaintext: &[u8]) -> SealedSecret {
+    seal_with(&MASTER_KEY.read().unwrap(), plaintext)
+}
+
+pub fn open(sealed: &SealedSecret) -> Result<Vec<u8>, SecretError> {
+    open_with(&MASTER_KEY.read().unwrap(), sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_with_the_same_key() {
+        let key = MasterKey::generate();
+        let sealed = seal_with(&key, b"admin:secret");
+
+        assert!(!sealed.ciphertext.windows(6).any(|w| w == b"secret"));
+        assert_eq!(open_with(&key, &sealed).unwrap(), b"admin:secret");
+    }
+
+    #[test]
+    fn rejects_other_keys_and_tampering() {
+        let key = MasterKey::generate();
+        let other = MasterKey::generate();
+        let mut sealed = seal_with(&key, b"admin:secret");
+
+        assert_eq!(open_with(&other, &sealed), Err(SecretError::WrongKey(key.id())));
+
+        sealed.ciphertext[0] ^= 1;
+        assert_eq!(open_with(&key, &sealed), Err(SecretError::Corrupted));
+    }
+
+    #[test]
+    fn parses_base64_keys() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+
+        assert!(MasterKey::from_base64(&encoded).is_ok());
+        assert!(matches!(MasterKey::from_base64("c2hvcnQ="), Err(SecretError::InvalidKey)));
+    }
+}
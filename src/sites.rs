@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::mock_data::{Camera, CameraStatus, Site, Zone};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatusCounts {
+    pub total: usize,
+    pub online: usize,
+    pub offline: usize,
+    pub maintenance: usize,
+}
+
+impl StatusCounts {
+    fn add(&mut self, camera: &Camera) {
+        self.total += 1;
+        match camera.status {
+            CameraStatus::Online => self.online += 1,
+            CameraStatus::Offline => self.offline += 1,
+            CameraStatus::Maintenance => self.maintenance += 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZoneSummary {
+    pub zone: Zone,
+    pub counts: StatusCounts,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SiteSummary {
+    pub site: Site,
+    // Every camera in the site's zones
+    pub counts: StatusCounts,
+    pub zones: Vec<ZoneSummary>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SitesSummary {
+    pub sites: Vec<SiteSummary>,
+    // Cameras not assigned to any zone, or to a zone that no longer exists
+    pub unassigned: StatusCounts,
+}
+
+// Status counts of `cameras` rolled up per zone and per site. Callers pass
+// only the cameras the user may see, so counts don't reveal the others.
+pub fn summary(cameras: &[Camera], sites: Vec<Site>, zones: &[Zone]) -> SitesSummary {
+    let zone_counts = |zone: &Zone| {
+        let mut counts = StatusCounts::default();
+        for camera in cameras.iter().filter(|c| c.zone_id.as_deref() == Some(zone.id.as_str())) {
+            counts.add(camera);
+        }
+        counts
+    };
+
+    let sites = sites
+        .into_iter()
+        .map(|site| {
+            let zones: Vec<ZoneSummary> = zones
+                .iter()
+                .filter(|z| z.site_id == site.id)
+                .map(|zone| ZoneSummary { counts: zone_counts(zone), zone: zone.clone() })
+                .collect();
+
+            let counts = zones.iter().fold(StatusCounts::default(), |total, z| StatusCounts {
+                total: total.total + z.counts.total,
+                online: total.online + z.counts.online,
+                offline: total.offline + z.counts.offline,
+                maintenance: total.maintenance + z.counts.maintenance,
+            });
+            SiteSummary { site, counts, zones }
+        })
+        .collect();
+
+    let mut unassigned = StatusCounts::default();
+    for camera in cameras {
+        let assigned = camera
+            .zone_id
+            .as_deref()
+            .is_some_and(|zone_id| zones.iter().any(|z| z.id == zone_id));
+        if !assigned {
+            unassigned.add(camera);
+        }
+    }
+
+    SitesSummary { sites, unassigned }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(id: &str) -> Site {
+        Site { id: id.to_string(), name: format!("Site {}", id), address: String::new() }
+    }
+
+    fn zone(id: &str, site_id: &str) -> Zone {
+        Zone { id: id.to_string(), site_id: site_id.to_string(), name: format!("Zone {}", id) }
+    }
+
+    fn camera(zone_id: Option<&str>, status: CameraStatus) -> Camera {
+        Camera { zone_id: zone_id.map(str::to_string), status, ..Camera::default() }
+    }
+
+    fn counts(total: usize, online: usize, offline: usize, maintenance: usize) -> StatusCounts {
+        StatusCounts { total, online, offline, maintenance }
+    }
+
+    #[test]
+    fn zones_roll_up_into_their_site() {
+        let cameras = [
+            camera(Some("z1"), CameraStatus::Online),
+            camera(Some("z1"), CameraStatus::Offline),
+            camera(Some("z2"), CameraStatus::Maintenance),
+            camera(Some("z3"), CameraStatus::Online),
+        ];
+        let zones = [zone("z1", "s1"), zone("z2", "s1"), zone("z3", "s2")];
+        let summary = summary(&cameras, vec![site("s1"), site("s2"), site("s3")], &zones);
+
+        let s1 = &summary.sites[0];
+        assert_eq!(s1.counts, counts(3, 1, 1, 1));
+        assert_eq!(s1.zones.iter().map(|z| z.counts.clone()).collect::<Vec<_>>(), [counts(2, 1, 1, 0), counts(1, 0, 0, 1)]);
+        assert_eq!(summary.sites[1].counts, counts(1, 1, 0, 0));
+        // A site without zones is still listed
+        assert_eq!(summary.sites[2].counts, StatusCounts::default());
+        assert!(summary.sites[2].zones.is_empty());
+        assert_eq!(summary.unassigned, StatusCounts::default());
+    }
+
+    #[test]
+    fn cameras_outside_known_zones_are_unassigned() {
+        let cameras = [
+            camera(None, CameraStatus::Online),
+            camera(Some("deleted"), CameraStatus::Offline),
+            camera(Some("z1"), CameraStatus::Online),
+        ];
+        let summary = summary(&cameras, vec![site("s1")], &[zone("z1", "s1")]);
+
+        assert_eq!(summary.unassigned, counts(2, 1, 1, 0));
+        assert_eq!(summary.sites[0].counts, counts(1, 1, 0, 0));
+    }
+
+    #[test]
+    fn only_the_given_cameras_are_counted() {
+        let summary = summary(&[], vec![site("s1")], &[zone("z1", "s1")]);
+        assert_eq!(summary.sites[0].counts, StatusCounts::default());
+        assert_eq!(summary.sites[0].zones[0].counts, StatusCounts::default());
+    }
+}
@@ -0,0 +1,348 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::alerts;
+use crate::email::{render, retry_delay};
+use crate::mock_data::{
+    Alert, AlertRule, SmsBodyFormat, SmsGatewaySettings, SmsMessage, SmsStatus, User,
+    get_settings, get_sms_gateway, add_sms_message, count_sms_since, due_sms_messages, record_sms_attempt,
+    format_timestamp, next_id,
+};
+
+// How often queued messages are checked for sending
+const TICK_SECONDS: u64 = 15;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Longer texts are cut so an alert fits in a single SMS
+const MAX_CHARS: usize = 160;
+
+const ALERT_TEXT: &str = "[{{severity}}] {{rule}}: {{message}}";
+const TEST_TEXT: &str = "Test message from {{registered_to}}'s camera management system.";
+
+#[derive(Debug, PartialEq)]
+pub struct SendError {
+    // HTTP status the gateway answered with, if it answered at all
+    pub gateway_status: Option<u16>,
+    pub message: String,
+}
+
+impl SendError {
+    fn new(gateway_status: Option<u16>, message: impl Into<String>) -> Self {
+        SendError { gateway_status, message: message.into() }
+    }
+}
+
+// International format: a plus sign followed by 8 to 15 digits
+pub fn valid_phone(phone: &str) -> bool {
+    phone
+        .strip_prefix('+')
+        .is_some_and(|digits| (8..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(MAX_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+pub fn alert_text(alert: &Alert, rule: &AlertRule) -> String {
+    let severity = format!("{:?}", alert.severity);
+    truncate(&render(ALERT_TEXT, &[
+        ("severity", &severity),
+        ("rule", &rule.name),
+        ("message", &alert.message),
+    ]))
+}
+
+pub fn test_text() -> String {
+    render(TEST_TEXT, &[("registered_to", &get_settings().registered_to)])
+}
+
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+fn escape_form(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// The request body for one message, with the values escaped so they can't
+// break out of the template's JSON strings or form fields
+pub fn request_body(gateway: &SmsGatewaySettings, to: &str, text: &str) -> String {
+    let escape = match gateway.body_format {
+        SmsBodyFormat::Json => escape_json,
+        SmsBodyFormat::Form => escape_form,
+    };
+    render(&gateway.body_template, &[("to", &escape(to)), ("message", &escape(text))])
+}
+
+// Deliver one message straight away. Any 2xx answer counts as accepted.
+pub async fn send(gateway: &SmsGatewaySettings, to: &str, text: &str) -> Result<u16, SendError> {
+    if gateway.url.is_empty() {
+        return Err(SendError::new(None, "SMS gateway is not configured"));
+    }
+
+    let content_type = match gateway.body_format {
+        SmsBodyFormat::Json => "application/json",
+        SmsBodyFormat::Form => "application/x-www-form-urlencoded",
+    };
+    let client = reqwest::Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .map_err(|e| SendError::new(None, e.to_string()))?;
+    let mut request = client
+        .post(&gateway.url)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .body(request_body(gateway, to, text));
+    if !gateway.auth_header.is_empty() {
+        request = request.header(gateway.auth_header.as_str(), gateway.auth_value.as_str());
+    }
+
+    let response = request.send().await.map_err(|e| SendError::new(None, e.to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(status.as_u16());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(SendError::new(
+        Some(status.as_u16()),
+        format!("Gateway answered {}: {}", status, truncate(body.trim())),
+    ))
+}
+
+// Queue a message for a user, unless they have already had their hourly
+// allowance. Dropped messages are kept in the log as rate limited.
+pub fn queue(user: &User, text: String) -> Option<SmsMessage> {
+    if user.phone.is_empty() {
+        return None;
+    }
+
+    let now = Utc::now();
+    let limit = get_settings().sms.max_per_hour;
+    let limited = limit > 0 && count_sms_since(&user.phone, now - chrono::Duration::hours(1)) >= limit as usize;
+    if limited {
+        eprintln!("SMS to {} dropped: over the limit of {} per hour", user.phone, limit);
+    }
+
+    let now = format_timestamp(now);
+    Some(add_sms_message(SmsMessage {
+        id: next_id(),
+        user_id: user.id.clone(),
+        to: user.phone.clone(),
+        body: text,
+        status: if limited { SmsStatus::RateLimited } else { SmsStatus::Pending },
+        attempts: 0,
+        created_at: now.clone(),
+        next_attempt_at: now,
+        sent_at: None,
+        gateway_status: None,
+        last_error: None,
+    }))
+}
+
+pub fn notify_alert(alert: &Alert, rule: &AlertRule) {
+    if !get_settings().sms_alerts {
+        return;
+    }
+    let text = alert_text(alert, rule);
+    for user in alerts::audience(alert) {
+        queue(&user, text.clone());
+    }
+}
+
+// Spawn the background task that sends queued messages
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            deliver_due(Utc::now()).await;
+        }
+    });
+}
+
+async fn deliver_due(now: DateTime<Utc>) {
+    let due = due_sms_messages(now);
+    if due.is_empty() {
+        return;
+    }
+
+    let gateway = get_sms_gateway();
+    for message in due {
+        let attempts = message.attempts + 1;
+        match send(&gateway, &message.to, &message.body).await {
+            Ok(status) => {
+                record_sms_attempt(&message.id, Some(status), Ok(()), None);
+            }
+            Err(error) => {
+                eprintln!("SMS {} to {} failed (attempt {}): {}", message.id, message.to, attempts, error.message);
+                let retry_at = retry_delay(attempts).map(|delay| format_timestamp(Utc::now() + delay));
+                record_sms_attempt(&message.id, error.gateway_status, Err(error.message), retry_at);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // What the stub server saw of one request
+    struct Received {
+        head: Vec<String>,
+        body: String,
+    }
+
+    impl Received {
+        fn header(&self, name: &str) -> Option<&str> {
+            let prefix = format!("{}: ", name.to_ascii_lowercase());
+            self.head
+                .iter()
+                .find(|line| line.to_ascii_lowercase().starts_with(&prefix))
+                .map(|line| &line[prefix.len()..])
+        }
+    }
+
+    // A minimal HTTP server for one request, answering with `status`
+    async fn stub_gateway(status: &'static str) -> (String, tokio::task::JoinHandle<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/send", listener.local_addr().unwrap());
+
+        let request = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut reader = BufReader::new(read);
+
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                if line.is_empty() {
+                    break;
+                }
+                head.push(line);
+            }
+            let length = head
+                .iter()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ").map(str::to_string))
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+
+            let reply = format!("HTTP/1.1 {}\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok", status);
+            write.write_all(reply.as_bytes()).await.unwrap();
+            Received { head, body: String::from_utf8(body).unwrap() }
+        });
+
+        (url, request)
+    }
+
+    fn gateway(url: String) -> SmsGatewaySettings {
+        SmsGatewaySettings {
+            url,
+            auth_header: "Authorization".to_string(),
+            auth_value: "Bearer secret-token".to_string(),
+            ..SmsGatewaySettings::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_json_body_with_auth_header() {
+        let (url, request) = stub_gateway("200 OK").await;
+
+        let status = send(&gateway(url), "+15550100001", "Front Gate is \"offline\"").await.unwrap();
+        assert_eq!(status, 200);
+
+        let received = request.await.unwrap();
+        assert_eq!(received.head[0], "POST /send HTTP/1.1");
+        assert_eq!(received.header("authorization"), Some("Bearer secret-token"));
+        assert_eq!(received.header("content-type"), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+        assert_eq!(body["to"], "+15550100001");
+        assert_eq!(body["message"], "Front Gate is \"offline\"");
+    }
+
+    #[tokio::test]
+    async fn form_body_is_url_encoded() {
+        let (url, request) = stub_gateway("202 Accepted").await;
+        let form = SmsGatewaySettings {
+            body_format: SmsBodyFormat::Form,
+            body_template: "To={{to}}&Body={{message}}".to_string(),
+            auth_header: String::new(),
+            ..gateway(url)
+        };
+
+        send(&form, "+15550100001", "Gate & door offline").await.unwrap();
+
+        let received = request.await.unwrap();
+        assert_eq!(received.header("content-type"), Some("application/x-www-form-urlencoded"));
+        assert_eq!(received.header("authorization"), None);
+        assert_eq!(received.body, "To=%2B15550100001&Body=Gate+%26+door+offline");
+    }
+
+    #[tokio::test]
+    async fn error_status_is_a_failure_carrying_the_status() {
+        let (url, _request) = stub_gateway("429 Too Many Requests").await;
+
+        let error = send(&gateway(url), "+15550100001", "hello").await.unwrap_err();
+        assert_eq!(error.gateway_status, Some(429));
+    }
+
+    #[tokio::test]
+    async fn unreachable_or_unconfigured_gateway_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/send", listener.local_addr().unwrap());
+        drop(listener);
+
+        let error = send(&gateway(url), "+15550100001", "hello").await.unwrap_err();
+        assert_eq!(error.gateway_status, None);
+        let unconfigured = gateway(String::new());
+        assert_eq!(
+            send(&unconfigured, "+15550100001", "hello").await,
+            Err(SendError::new(None, "SMS gateway is not configured"))
+        );
+    }
+
+    #[test]
+    fn messages_over_the_hourly_limit_are_dropped() {
+        let limit = get_settings().sms.max_per_hour as usize;
+        let user = User {
+            id: "sms-test".to_string(),
+            username: "sms-test".to_string(),
+            name: "SMS Test".to_string(),
+            email: String::new(),
+            phone: "+15559990001".to_string(),
+            role: crate::mock_data::UserRole::Viewer,
+            active: true,
+            last_login: String::new(),
+        };
+
+        let statuses: Vec<SmsStatus> = (0..=limit).map(|i| queue(&user, format!("message {}", i)).unwrap().status).collect();
+        assert!(statuses[..limit].iter().all(|status| *status == SmsStatus::Pending));
+        assert_eq!(statuses[limit], SmsStatus::RateLimited);
+    }
+
+    #[test]
+    fn phone_numbers_need_international_format() {
+        assert!(valid_phone("+15550100001"));
+        assert!(!valid_phone("15550100001"));
+        assert!(!valid_phone("+1555"));
+        assert!(!valid_phone("+1 555 0100 001"));
+    }
+}
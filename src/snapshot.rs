@@ -0,0 +1,232 @@
+use axum::body::Bytes;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::mock_data::Camera;
+use crate::rtsp;
+
+// How long a fetched frame is served to other viewers before asking the camera again
+const CACHE_TTL: Duration = Duration::from_secs(5);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+// Largest image we are willing to pull through the server
+const MAX_SNAPSHOT_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub content_type: String,
+    pub bytes: Bytes,
+    fetched_at: Instant,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotError {
+    // No profile advertised a snapshot URI; refresh-info hasn't run or the
+    // camera can't take snapshots
+    NoSnapshotUri,
+    Unreachable(String),
+    AuthFailed,
+    Status(u16),
+    TooLarge,
+    NotAnImage(String),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SnapshotError::NoSnapshotUri => write!(f, "camera has no snapshot URI"),
+            SnapshotError::Unreachable(message) => write!(f, "unreachable: {}", message),
+            SnapshotError::AuthFailed => write!(f, "authentication failed"),
+            SnapshotError::Status(status) => write!(f, "camera returned {}", status),
+            SnapshotError::TooLarge => write!(f, "snapshot larger than {} bytes", MAX_SNAPSHOT_BYTES),
+            SnapshotError::NotAnImage(content_type) => write!(f, "unexpected content type {}", content_type),
+        }
+    }
+}
+
+static CACHE: Lazy<Mutex<HashMap<String, Snapshot>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn snapshot_uri(camera: &Camera) -> Option<String> {
+    camera.media_profiles.iter().find_map(|profile| profile.snapshot_uri.clone())
+}
+
+// Path and query of a URL, which is what a digest response is computed over
+fn request_uri(url: &reqwest::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+// GET a URL on the camera, answering a basic or digest challenge with the
+// camera's credentials. Also used by the MJPEG proxy.
+pub async fn authorized_get(
+    client: &reqwest::Client,
+    camera: &Camera,
+    url: reqwest::Url,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let response = client.get(url.clone()).send().await?;
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED || camera.username.is_empty() {
+        return Ok(response);
+    }
+
+    let challenges: Vec<&str> = response
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    match rtsp::authorization(&challenges, "GET", &request_uri(&url), &camera.username, &camera.password) {
+        Some(header) => client.get(url).header(reqwest::header::AUTHORIZATION, header).send().await,
+        None => Ok(response),
+    }
+}
+
+async fn fetch(camera: &Camera, uri: &str) -> Result<Snapshot, SnapshotError> {
+    let url = reqwest::Url::parse(uri).map_err(|e| SnapshotError::Unreachable(e.to_string()))?;
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| SnapshotError::Unreachable(e.to_string()))?;
+    let unreachable = |e: reqwest::Error| SnapshotError::Unreachable(e.to_string());
+
+    let mut response = authorized_get(&client, camera, url).await.map_err(unreachable)?;
+
+    match response.status().as_u16() {
+        200 => {}
+        401 | 403 => return Err(SnapshotError::AuthFailed),
+        status => return Err(SnapshotError::Status(status)),
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("image/jpeg")
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return Err(SnapshotError::NotAnImage(content_type));
+    }
+    if response.content_length().is_some_and(|length| length > MAX_SNAPSHOT_BYTES as u64) {
+        return Err(SnapshotError::TooLarge);
+    }
+
+    // Cameras don't always send a length, so keep counting while reading
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(unreachable)? {
+        if bytes.len() + chunk.len() > MAX_SNAPSHOT_BYTES {
+            return Err(SnapshotError::TooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(Snapshot {
+        content_type,
+        bytes: Bytes::from(bytes),
+        fetched_at: Instant::now(),
+    })
+}
+
+// The camera's current frame, from the cache when it was fetched recently
+pub async fn get(camera: &Camera) -> Result<Snapshot, SnapshotError> {
+    if let Some(snapshot) = CACHE.lock().unwrap().get(&camera.id) {
+        if snapshot.fetched_at.elapsed() < CACHE_TTL {
+            return Ok(snapshot.clone());
+        }
+    }
+
+    let uri = snapshot_uri(camera).ok_or(SnapshotError::NoSnapshotUri)?;
+    let snapshot = fetch(camera, &uri).await?;
+
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|_, cached| cached.fetched_at.elapsed() < CACHE_TTL);
+    cache.insert(camera.id.clone(), snapshot.clone());
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::MediaProfile;
+    use axum::{
+        extract::Path,
+        http::{header, HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing,
+        Router,
+    };
+
+    const JPEG: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0xff, 0xd9];
+
+    async fn serve(Path(name): Path<String>, headers: HeaderMap) -> axum::response::Response {
+        match name.as_str() {
+            "digest.jpg" => {
+                let authorized = headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.starts_with("Digest ") && value.contains("uri=\"/digest.jpg\""));
+                if !authorized {
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        [(header::WWW_AUTHENTICATE, "Digest realm=\"cam\", nonce=\"n1\", qop=\"auth\"")],
+                    )
+                        .into_response();
+                }
+                ([(header::CONTENT_TYPE, "image/jpeg")], JPEG).into_response()
+            }
+            "huge.jpg" => ([(header::CONTENT_TYPE, "image/jpeg")], vec![0u8; MAX_SNAPSHOT_BYTES + 1]).into_response(),
+            "page.html" => ([(header::CONTENT_TYPE, "text/html")], "<html></html>").into_response(),
+            _ => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    // Serve fake snapshots and return a camera whose profile points at `name`
+    async fn camera(id: &str, name: &str) -> Camera {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().route("/:name", routing::get(serve));
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        Camera {
+            id: id.to_string(),
+            username: "admin".to_string(),
+            password: "secret".to_string(),
+            media_profiles: vec![MediaProfile {
+                token: "main".to_string(),
+                name: "Main".to_string(),
+                encoding: None,
+                width: None,
+                height: None,
+                frame_rate: None,
+                stream_uri: None,
+                snapshot_uri: Some(format!("{}/{}", base, name)),
+            }],
+            ..Camera::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn fetches_snapshot_with_digest_auth() {
+        let snapshot = get(&camera("snapshot-digest", "digest.jpg").await).await.unwrap();
+
+        assert_eq!(snapshot.content_type, "image/jpeg");
+        assert_eq!(&snapshot.bytes[..], JPEG);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_and_non_image_responses() {
+        let huge = get(&camera("snapshot-huge", "huge.jpg").await).await;
+        let page = get(&camera("snapshot-page", "page.html").await).await;
+
+        assert!(matches!(huge, Err(SnapshotError::TooLarge)));
+        assert!(matches!(page, Err(SnapshotError::NotAnImage(_))));
+    }
+
+    #[tokio::test]
+    async fn camera_without_snapshot_uri() {
+        let result = get(&Camera { id: "snapshot-none".to_string(), ..Camera::default() }).await;
+
+        assert_eq!(result.err(), Some(SnapshotError::NoSnapshotUri));
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::mock_data::{CameraStatus, CameraStatusChange, parse_timestamp};
+
+// Longest range the status history endpoint reports on
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+// A span of time during which a camera stayed in one status
+#[derive(Clone)]
+pub struct StatusInterval {
+    pub status: CameraStatus,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+// Seconds spent in each status over some range
+#[derive(Default)]
+pub struct StatusTotals {
+    pub online: i64,
+    pub offline: i64,
+    pub maintenance: i64,
+}
+
+impl StatusTotals {
+    // Maintenance is planned downtime, so it counts for neither side of the ratio
+    pub fn availability_percent(&self) -> Option<f64> {
+        let monitored = self.online + self.offline;
+        if monitored == 0 {
+            None
+        } else {
+            Some(self.online as f64 * 100.0 / monitored as f64)
+        }
+    }
+}
+
+// A period the camera was offline, identified by the change that started it
+pub struct Outage {
+    pub change_id: String,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<String>,
+}
+
+impl Outage {
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+// Resolve an inclusive YYYY-MM-DD date range into timestamps. An empty start
+// falls back to the oldest history entry and an empty end to now.
+pub fn period_range(from: &str, to: &str, history: &[CameraStatusChange], now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = NaiveDate::parse_from_str(from, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .or_else(|| history.iter().filter_map(|c| parse_timestamp(&c.timestamp)).min())
+        .unwrap_or(now);
+
+    let end = NaiveDate::parse_from_str(to, "%Y-%m-%d")
+        .ok()
+        .map(|date| (date + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_or(now, |end| end.min(now));
+
+    (start, end.max(start))
+}
+
+// Intervals covering [from, to) for one camera's time-ordered history. Time
+// before the first recorded change is unknown and left out.
+pub fn status_intervals(history: &[CameraStatusChange], from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<StatusInterval> {
+    let mut intervals = Vec::new();
+    let mut push_clipped = |status: CameraStatus, start: DateTime<Utc>, end: DateTime<Utc>| {
+        let (start, end) = (start.max(from), end.min(to));
+        if end > start {
+            intervals.push(StatusInterval { status, start, end });
+        }
+    };
+
+    let mut current: Option<(CameraStatus, DateTime<Utc>)> = None;
+    for change in history {
+        let Some(at) = parse_timestamp(&change.timestamp) else { continue };
+        if at >= to {
+            break;
+        }
+        if let Some((status, start)) = current.take() {
+            push_clipped(status, start, at);
+        }
+        current = Some((change.status.clone(), at));
+    }
+    if let Some((status, start)) = current {
+        push_clipped(status, start, to);
+    }
+
+    intervals
+}
+
+pub fn totals(intervals: &[StatusInterval]) -> StatusTotals {
+    let mut totals = StatusTotals::default();
+    for interval in intervals {
+        let seconds = (interval.end - interval.start).num_seconds();
+        match interval.status {
+            CameraStatus::Online => totals.online += seconds,
+            CameraStatus::Offline => totals.offline += seconds,
+            CameraStatus::Maintenance => totals.maintenance += seconds,
+        }
+    }
+    totals
+}
+
+// Offline windows of one camera that overlap [from, to), with their real start and end
+pub fn outages(history: &[CameraStatusChange], from: DateTime<Utc>, to: DateTime<Utc>, now: DateTime<Utc>) -> Vec<Outage> {
+    let mut outages: Vec<Outage> = Vec::new();
+    let mut open: Option<Outage> = None;
+
+    for change in history {
+        let Some(at) = parse_timestamp(&change.timestamp) else { continue };
+        match (change.status == CameraStatus::Offline, open.is_some()) {
+            (true, false) => {
+                open = Some(Outage {
+                    change_id: change.id.clone(),
+                    start: at,
+                    end: None,
+                    acknowledged_by: change.acknowledged_by.clone(),
+                    acknowledged_at: change.acknowledged_at.clone(),
+                });
+            }
+            (false, true) => {
+                let mut outage = open.take().unwrap();
+                outage.end = Some(at);
+                outages.push(outage);
+            }
+            _ => {}
+        }
+    }
+    outages.extend(open);
+
+    outages.retain(|outage| outage.start < to && outage.end.unwrap_or(now) > from);
+    outages
+}
+
+#[derive(Clone, Copy)]
+pub enum Bucket {
+    Day,
+    // Weeks start on Monday
+    Week,
+}
+
+// Availability for each UTC day or week overlapping [from, to), keyed by the
+// bucket's start. Buckets are clipped to the range at both ends.
+pub fn availability_by(intervals: &[StatusInterval], from: DateTime<Utc>, to: DateTime<Utc>, bucket: Bucket) -> Vec<(DateTime<Utc>, Option<f64>)> {
+    let first_day = from.date_naive();
+    let (mut bucket_start, length) = match bucket {
+        Bucket::Day => (first_day, Duration::days(1)),
+        Bucket::Week => (
+            first_day - Duration::days(first_day.weekday().num_days_from_monday() as i64),
+            Duration::weeks(1),
+        ),
+    };
+
+    let mut buckets = Vec::new();
+    loop {
+        let start = bucket_start.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        if start >= to {
+            break;
+        }
+        let (clip_start, clip_end) = (start.max(from), (start + length).min(to));
+
+        let clipped: Vec<StatusInterval> = intervals
+            .iter()
+            .filter(|i| i.start < clip_end && i.end > clip_start)
+            .map(|i| StatusInterval { status: i.status.clone(), start: i.start.max(clip_start), end: i.end.min(clip_end) })
+            .collect();
+        buckets.push((start, totals(&clipped).availability_percent()));
+
+        bucket_start += length;
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        parse_timestamp(timestamp).unwrap()
+    }
+
+    fn interval(status: CameraStatus, start: &str, end: &str) -> StatusInterval {
+        StatusInterval { status, start: at(start), end: at(end) }
+    }
+
+    fn percents(buckets: &[(DateTime<Utc>, Option<f64>)]) -> Vec<(String, Option<f64>)> {
+        buckets.iter().map(|(start, percent)| (start.format("%Y-%m-%d").to_string(), *percent)).collect()
+    }
+
+    #[test]
+    fn daily_buckets_are_clipped_to_the_range() {
+        // Monday 2024-01-01 noon until Wednesday 06:00
+        let intervals = [
+            interval(CameraStatus::Offline, "2024-01-01T00:00:00Z", "2024-01-01T12:00:00Z"),
+            interval(CameraStatus::Online, "2024-01-01T12:00:00Z", "2024-01-02T00:00:00Z"),
+            interval(CameraStatus::Offline, "2024-01-02T00:00:00Z", "2024-01-02T06:00:00Z"),
+            interval(CameraStatus::Online, "2024-01-02T06:00:00Z", "2024-01-03T00:00:00Z"),
+            interval(CameraStatus::Offline, "2024-01-03T06:00:00Z", "2024-01-04T00:00:00Z"),
+        ];
+        let days = availability_by(&intervals, at("2024-01-01T12:00:00Z"), at("2024-01-03T06:00:00Z"), Bucket::Day);
+
+        assert_eq!(percents(&days), vec![
+            ("2024-01-01".to_string(), Some(100.0)),
+            ("2024-01-02".to_string(), Some(75.0)),
+            // Only time after the last interval is left, and that isn't known
+            ("2024-01-03".to_string(), None),
+        ]);
+    }
+
+    #[test]
+    fn weekly_buckets_start_on_monday_and_are_clipped() {
+        // Wednesday 2024-01-03 until Wednesday 2024-01-10
+        let intervals = [
+            interval(CameraStatus::Offline, "2024-01-01T00:00:00Z", "2024-01-03T00:00:00Z"),
+            interval(CameraStatus::Online, "2024-01-03T00:00:00Z", "2024-01-08T00:00:00Z"),
+            interval(CameraStatus::Offline, "2024-01-08T00:00:00Z", "2024-01-09T00:00:00Z"),
+            interval(CameraStatus::Maintenance, "2024-01-09T00:00:00Z", "2024-01-09T12:00:00Z"),
+            interval(CameraStatus::Online, "2024-01-09T12:00:00Z", "2024-01-12T00:00:00Z"),
+        ];
+        let weeks = availability_by(&intervals, at("2024-01-03T00:00:00Z"), at("2024-01-10T00:00:00Z"), Bucket::Week);
+
+        assert_eq!(percents(&weeks), vec![
+            ("2024-01-01".to_string(), Some(100.0)),
+            // One day offline and half a day online; maintenance doesn't count
+            ("2024-01-08".to_string(), Some(100.0 / 3.0)),
+        ]);
+    }
+
+    #[test]
+    fn empty_range_has_no_buckets() {
+        let from = at("2024-01-01T00:00:00Z");
+        assert!(availability_by(&[], from, from, Bucket::Day).is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+
+use crate::mock_data::{
+    WebhookDelivery, WebhookEvent, WebhookSubscription,
+    get_webhook, get_webhook_secret, queue_webhook_event, due_webhook_deliveries, record_webhook_attempt,
+    format_timestamp,
+};
+
+// How often pending deliveries are checked for sending
+const TICK_SECONDS: u64 = 5;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Attempts before a delivery goes to the dead-letter list
+const MAX_ATTEMPTS: u32 = 6;
+
+// Wait after the first failure, doubled after each one after that
+const FIRST_RETRY_SECONDS: i64 = 10;
+
+#[derive(Debug, PartialEq)]
+pub struct DeliveryError {
+    // HTTP status the receiver answered with, if it answered at all
+    pub response_status: Option<u16>,
+    pub message: String,
+}
+
+// Queue an event for every subscription that wants it
+pub fn emit(event: WebhookEvent, data: impl Serialize) {
+    match serde_json::to_value(data) {
+        Ok(data) => {
+            queue_webhook_event(event, data);
+        }
+        Err(e) => eprintln!("Cannot serialize {} webhook payload: {}", event.name(), e),
+    }
+}
+
+pub fn generate_secret() -> String {
+    let bytes: [u8; 24] = rand::random();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn valid_subscription(webhook: &WebhookSubscription) -> bool {
+    !webhook.name.trim().is_empty()
+        && (webhook.url.starts_with("http://") || webhook.url.starts_with("https://"))
+        && !webhook.events.is_empty()
+}
+
+// Value of the X-Webhook-Signature header. The timestamp is signed along with
+// the body so receivers can reject replayed requests.
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    let digest: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", digest)
+}
+
+// How long to wait before the next attempt, after `attempts` failed ones.
+// None once the delivery should be dead-lettered.
+pub fn retry_delay(attempts: u32) -> Option<chrono::Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    Some(chrono::Duration::seconds(FIRST_RETRY_SECONDS << attempts.saturating_sub(1)))
+}
+
+// POST one delivery to the receiver. Any 2xx answer counts as delivered.
+pub async fn deliver(url: &str, secret: &str, delivery: &WebhookDelivery) -> Result<u16, DeliveryError> {
+    let failed = |response_status, message: String| DeliveryError { response_status, message };
+
+    let client = reqwest::Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .map_err(|e| failed(None, e.to_string()))?;
+    let timestamp = Utc::now().timestamp();
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Webhook-Id", delivery.id.as_str())
+        .header("X-Webhook-Event", delivery.event.name())
+        .header("X-Webhook-Timestamp", timestamp.to_string())
+        .header("X-Webhook-Signature", signature(secret, timestamp, &delivery.payload))
+        .body(delivery.payload.clone())
+        .send()
+        .await
+        .map_err(|e| failed(None, e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(failed(Some(status.as_u16()), format!("Receiver answered {}", status)))
+    }
+}
+
+// Spawn the background task that sends pending deliveries
+pub fn start() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+        loop {
+            interval.tick().await;
+            deliver_due(Utc::now()).await;
+        }
+    });
+}
+
+async fn deliver_due(now: DateTime<Utc>) {
+    for delivery in due_webhook_deliveries(now) {
+        // Deliveries for a paused subscription wait until it is switched back on
+        let Some(webhook) = get_webhook(&delivery.subscription_id).filter(|w| w.active) else {
+            continue;
+        };
+
+        let attempts = delivery.attempts + 1;
+        let result = match get_webhook_secret(&webhook.id) {
+            Some(secret) => deliver(&webhook.url, &secret, &delivery).await,
+            None => Err(DeliveryError { response_status: None, message: "The signing secret can't be read".to_string() }),
+        };
+        match result {
+            Ok(status) => {
+                record_webhook_attempt(&delivery.id, Some(status), Ok(()), None);
+            }
+            Err(error) => {
+                let retry_at = retry_delay(attempts).map(|delay| format_timestamp(Utc::now() + delay));
+                if retry_at.is_none() {
+                    eprintln!("Webhook delivery {} to {} dead-lettered after {} attempts: {}", delivery.id, webhook.url, attempts, error.message);
+                }
+                record_webhook_attempt(&delivery.id, error.response_status, Err(error.message), retry_at);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_data::WebhookDeliveryStatus;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // A minimal HTTP receiver for one request, answering with `status`.
+    // Returns the request's header lines and body.
+    async fn receiver(status: &'static str) -> (String, tokio::task::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let request = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut reader = BufReader::new(read);
+
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                if line.is_empty() {
+                    break;
+                }
+                head.push(line);
+            }
+            let length = head
+                .iter()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ").map(str::to_string))
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+
+            let reply = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            write.write_all(reply.as_bytes()).await.unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        (url, request)
+    }
+
+    fn header<'a>(head: &'a [String], name: &str) -> Option<&'a str> {
+        let prefix = format!("{}: ", name.to_ascii_lowercase());
+        head.iter()
+            .find(|line| line.to_ascii_lowercase().starts_with(&prefix))
+            .map(|line| &line[prefix.len()..])
+    }
+
+    fn delivery() -> WebhookDelivery {
+        WebhookDelivery {
+            id: "d1".to_string(),
+            subscription_id: "w1".to_string(),
+            event: WebhookEvent::CameraStatusChanged,
+            payload: r#"{"event":"camera.status_changed","data":{"camera_id":"101","status":"Offline"}}"#.to_string(),
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            created_at: String::new(),
+            next_attempt_at: String::new(),
+            delivered_at: None,
+            response_status: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256_of_timestamp_and_body() {
+        // Same as Python's hmac.new(b"s3cret", b'1700000000.{"a":1}', hashlib.sha256)
+        assert_eq!(
+            signature("s3cret", 1_700_000_000, r#"{"a":1}"#),
+            "sha256=1698a50bc74d1ff1db85c4e0a5297c2ad9fdba245d5737cdb789e4cc6e098940"
+        );
+        assert_ne!(signature("s3cret", 1, "{}"), signature("s3cret", 2, "{}"));
+    }
+
+    #[tokio::test]
+    async fn posts_signed_payload() {
+        let (url, request) = receiver("204 No Content").await;
+        let delivery = delivery();
+
+        assert_eq!(deliver(&url, "s3cret", &delivery).await, Ok(204));
+
+        let (head, body) = request.await.unwrap();
+        assert_eq!(body, delivery.payload);
+        assert_eq!(header(&head, "content-type"), Some("application/json"));
+        assert_eq!(header(&head, "x-webhook-event"), Some("camera.status_changed"));
+        assert_eq!(header(&head, "x-webhook-id"), Some("d1"));
+        let timestamp: i64 = header(&head, "x-webhook-timestamp").unwrap().parse().unwrap();
+        assert_eq!(header(&head, "x-webhook-signature"), Some(signature("s3cret", timestamp, &body).as_str()));
+    }
+
+    #[tokio::test]
+    async fn error_status_fails_with_the_status() {
+        let (url, _request) = receiver("500 Internal Server Error").await;
+
+        let error = deliver(&url, "s3cret", &delivery()).await.unwrap_err();
+        assert_eq!(error.response_status, Some(500));
+    }
+
+    #[test]
+    fn retries_back_off_then_dead_letter() {
+        assert_eq!(retry_delay(1), Some(chrono::Duration::seconds(10)));
+        assert_eq!(retry_delay(2), Some(chrono::Duration::seconds(20)));
+        assert_eq!(retry_delay(5), Some(chrono::Duration::seconds(160)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+}
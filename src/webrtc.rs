@@ -0,0 +1,408 @@
+use axum::{
+    async_trait,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::Response,
+};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::mock_data::{Camera, User, next_id, now_timestamp};
+
+// An ICE candidate in the shape browsers produce with RTCIceCandidate.toJSON()
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IceCandidate {
+    pub candidate: String,
+    #[serde(default)]
+    pub sdp_mid: Option<String>,
+    #[serde(default)]
+    pub sdp_m_line_index: Option<u16>,
+}
+
+// Messages the browser sends over the signaling socket
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Offer { sdp: String },
+    IceCandidate(IceCandidate),
+    Hangup,
+}
+
+// Messages the server sends back
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Session { session_id: String, camera_id: String },
+    Answer { sdp: String },
+    IceCandidate(IceCandidate),
+    Error { message: String },
+    Bye { reason: String },
+}
+
+// What a relay reports about a session after the answer has been sent. Only
+// relay implementations construct these, and none ship with the server yet.
+#[allow(dead_code)]
+pub enum RelayEvent {
+    IceCandidate(IceCandidate),
+    Closed(String),
+}
+
+// The piece that actually pulls video from the camera and serves it over
+// WebRTC. Signaling only shuttles SDP and candidates between it and the browser.
+#[async_trait]
+pub trait MediaRelay: Send + Sync {
+    // Start serving `camera` for a browser's offer and return the SDP answer.
+    // Trickled candidates and the end of the session are reported on `events`.
+    async fn open(
+        &self,
+        session_id: &str,
+        camera: &Camera,
+        offer_sdp: &str,
+        events: mpsc::UnboundedSender<RelayEvent>,
+    ) -> Result<String, String>;
+
+    async fn add_ice_candidate(&self, session_id: &str, candidate: IceCandidate) -> Result<(), String>;
+
+    async fn close(&self, session_id: &str);
+}
+
+// Used until a real relay is installed: sessions can be opened, but offers are refused
+pub struct NoRelay;
+
+#[async_trait]
+impl MediaRelay for NoRelay {
+    async fn open(
+        &self,
+        _session_id: &str,
+        _camera: &Camera,
+        _offer_sdp: &str,
+        _events: mpsc::UnboundedSender<RelayEvent>,
+    ) -> Result<String, String> {
+        Err("No media relay is configured on this server".to_string())
+    }
+
+    async fn add_ice_candidate(&self, _session_id: &str, _candidate: IceCandidate) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn close(&self, _session_id: &str) {}
+}
+
+static RELAY: OnceCell<Arc<dyn MediaRelay>> = OnceCell::new();
+
+// Choose the relay for all signaling sessions; only the first call has an effect
+pub fn install_relay(relay: Arc<dyn MediaRelay>) {
+    let _ = RELAY.set(relay);
+}
+
+fn relay() -> Arc<dyn MediaRelay> {
+    RELAY.get().cloned().unwrap_or_else(|| Arc::new(NoRelay))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum SessionState {
+    AwaitingOffer,
+    Connected,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignalingSession {
+    pub id: String,
+    pub camera_id: String,
+    pub user_id: String,
+    pub state: SessionState,
+    pub started_at: String,
+}
+
+static SESSIONS: Lazy<Mutex<HashMap<String, SignalingSession>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn sessions() -> Vec<SignalingSession> {
+    let mut sessions: Vec<SignalingSession> = SESSIONS.lock().unwrap().values().cloned().collect();
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at).then_with(|| a.id.cmp(&b.id)));
+    sessions
+}
+
+fn set_state(session_id: &str, state: SessionState) {
+    if let Some(session) = SESSIONS.lock().unwrap().get_mut(session_id) {
+        session.state = state;
+    }
+}
+
+// Upgrade to a signaling socket for one viewing session of `camera`
+pub fn accept(ws: WebSocketUpgrade, camera: Camera, user: User) -> Response {
+    ws.on_upgrade(move |socket| run_session(socket, camera, user, relay()))
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> bool {
+    let text = serde_json::to_string(message).expect("signaling messages serialize");
+    socket.send(Message::Text(text)).await.is_ok()
+}
+
+async fn run_session(mut socket: WebSocket, camera: Camera, user: User, relay: Arc<dyn MediaRelay>) {
+    let session = SignalingSession {
+        id: next_id(),
+        camera_id: camera.id.clone(),
+        user_id: user.id.clone(),
+        state: SessionState::AwaitingOffer,
+        started_at: now_timestamp(),
+    };
+    let session_id = session.id.clone();
+    SESSIONS.lock().unwrap().insert(session_id.clone(), session);
+    println!("WebRTC session {} opened for camera {} by user {}", session_id, camera.id, user.id);
+
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let mut negotiated = false;
+    let mut reason = "closed by viewer".to_string();
+
+    if send(&mut socket, &ServerMessage::Session { session_id: session_id.clone(), camera_id: camera.id.clone() }).await {
+        loop {
+            tokio::select! {
+                message = socket.recv() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        // Pings are answered by axum; binary frames mean nothing here
+                        Some(Ok(_)) => continue,
+                    };
+
+                    let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                        Err(e) => Some(ServerMessage::Error { message: format!("Invalid message: {}", e) }),
+                        Ok(ClientMessage::Hangup) => {
+                            reason = "hung up".to_string();
+                            let _ = send(&mut socket, &ServerMessage::Bye { reason: reason.clone() }).await;
+                            break;
+                        }
+                        Ok(ClientMessage::Offer { .. }) if negotiated => {
+                            Some(ServerMessage::Error { message: "Session already negotiated".to_string() })
+                        }
+                        Ok(ClientMessage::Offer { sdp }) => {
+                            match relay.open(&session_id, &camera, &sdp, events_tx.clone()).await {
+                                Ok(answer) => {
+                                    negotiated = true;
+                                    set_state(&session_id, SessionState::Connected);
+                                    Some(ServerMessage::Answer { sdp: answer })
+                                }
+                                Err(message) => Some(ServerMessage::Error { message }),
+                            }
+                        }
+                        Ok(ClientMessage::IceCandidate(_)) if !negotiated => {
+                            Some(ServerMessage::Error { message: "Send an offer before ICE candidates".to_string() })
+                        }
+                        Ok(ClientMessage::IceCandidate(candidate)) => {
+                            relay
+                                .add_ice_candidate(&session_id, candidate)
+                                .await
+                                .err()
+                                .map(|message| ServerMessage::Error { message })
+                        }
+                    };
+
+                    if let Some(reply) = reply {
+                        if !send(&mut socket, &reply).await {
+                            break;
+                        }
+                    }
+                }
+                Some(event) = events_rx.recv() => {
+                    match event {
+                        RelayEvent::IceCandidate(candidate) => {
+                            if !send(&mut socket, &ServerMessage::IceCandidate(candidate)).await {
+                                break;
+                            }
+                        }
+                        RelayEvent::Closed(why) => {
+                            reason = why;
+                            let _ = send(&mut socket, &ServerMessage::Bye { reason: reason.clone() }).await;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if negotiated {
+        relay.close(&session_id).await;
+    }
+    SESSIONS.lock().unwrap().remove(&session_id);
+    println!("WebRTC session {} ended: {}", session_id, reason);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::testing::login;
+    use axum::{routing::get, Router};
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite;
+
+    type Client = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+    // Stands in for a media server: answers every offer from the camera side
+    // and offers one host candidate of its own
+    #[derive(Default)]
+    struct LoopbackRelay {
+        candidates: Mutex<Vec<(String, IceCandidate)>>,
+        closed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MediaRelay for LoopbackRelay {
+        async fn open(
+            &self,
+            _session_id: &str,
+            _camera: &Camera,
+            offer_sdp: &str,
+            events: mpsc::UnboundedSender<RelayEvent>,
+        ) -> Result<String, String> {
+            if !offer_sdp.starts_with("v=0") {
+                return Err("Malformed offer".to_string());
+            }
+
+            let _ = events.send(RelayEvent::IceCandidate(IceCandidate {
+                candidate: "candidate:1 1 udp 2130706431 127.0.0.1 50000 typ host".to_string(),
+                sdp_mid: Some("0".to_string()),
+                sdp_m_line_index: Some(0),
+            }));
+            Ok(offer_sdp.replace("a=setup:actpass", "a=setup:passive").replace("a=recvonly", "a=sendonly"))
+        }
+
+        async fn add_ice_candidate(&self, session_id: &str, candidate: IceCandidate) -> Result<(), String> {
+            self.candidates.lock().unwrap().push((session_id.to_string(), candidate));
+            Ok(())
+        }
+
+        async fn close(&self, session_id: &str) {
+            self.closed.lock().unwrap().push(session_id.to_string());
+        }
+    }
+
+    static LOOPBACK: Lazy<Arc<LoopbackRelay>> = Lazy::new(|| Arc::new(LoopbackRelay::default()));
+
+    const OFFER: &str = "v=0\r\no=- 1 2 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\nm=video 9 UDP/TLS/RTP/SAVPF 96\r\na=mid:0\r\na=setup:actpass\r\na=recvonly\r\na=rtpmap:96 H264/90000\r\n";
+
+    // Serve the real signaling route with the loopback relay installed
+    async fn server() -> String {
+        install_relay(LOOPBACK.clone());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/api/cameras/:id/webrtc", get(crate::camera_webrtc_handler))
+            .with_state(Arc::new(Mutex::new(())));
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        format!("ws://{}/api/cameras", address)
+    }
+
+    async fn receive(client: &mut Client) -> serde_json::Value {
+        loop {
+            match client.next().await.unwrap().unwrap() {
+                tungstenite::Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                tungstenite::Message::Close(_) => panic!("socket closed"),
+                _ => continue,
+            }
+        }
+    }
+
+    async fn send_json(client: &mut Client, value: serde_json::Value) {
+        client.send(tungstenite::Message::Text(value.to_string())).await.unwrap();
+    }
+
+    async fn connect(url: &str) -> (Client, String) {
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let session = receive(&mut client).await;
+        assert_eq!(session["type"], "session");
+        (client, session["session_id"].as_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn loopback_peer_negotiates_and_hangs_up() {
+        let base = server().await;
+        let token = login("asmith").await;
+        let (mut client, session_id) = connect(&format!("{}/101/webrtc?token={}", base, token)).await;
+        assert!(sessions().iter().any(|s| s.id == session_id && s.state == SessionState::AwaitingOffer));
+
+        send_json(&mut client, serde_json::json!({ "type": "offer", "sdp": OFFER })).await;
+        let mut answer = None;
+        let mut remote_candidate = None;
+        while answer.is_none() || remote_candidate.is_none() {
+            let message = receive(&mut client).await;
+            match message["type"].as_str().unwrap() {
+                "answer" => answer = Some(message["sdp"].as_str().unwrap().to_string()),
+                "ice_candidate" => remote_candidate = Some(message),
+                other => panic!("unexpected {}", other),
+            }
+        }
+        let answer = answer.unwrap();
+        assert!(answer.contains("a=setup:passive") && answer.contains("a=sendonly"));
+        assert_eq!(remote_candidate.unwrap()["sdpMid"], "0");
+        assert!(sessions().iter().any(|s| s.id == session_id && s.state == SessionState::Connected));
+
+        send_json(
+            &mut client,
+            serde_json::json!({ "type": "ice_candidate", "candidate": "candidate:2 1 udp 1 10.0.0.5 40000 typ host", "sdpMid": "0", "sdpMLineIndex": 0 }),
+        )
+        .await;
+        send_json(&mut client, serde_json::json!({ "type": "hangup" })).await;
+        assert_eq!(receive(&mut client).await["type"], "bye");
+
+        // The session is torn down once the server loop finishes
+        for _ in 0..50 {
+            if !sessions().iter().any(|s| s.id == session_id) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!sessions().iter().any(|s| s.id == session_id));
+        assert!(LOOPBACK.closed.lock().unwrap().contains(&session_id));
+        assert!(LOOPBACK
+            .candidates
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(id, c)| *id == session_id && c.candidate.contains("10.0.0.5")));
+    }
+
+    #[tokio::test]
+    async fn candidates_before_offer_are_rejected() {
+        let base = server().await;
+        let token = login("admin").await;
+        let (mut client, _) = connect(&format!("{}/101/webrtc?token={}", base, token)).await;
+
+        send_json(&mut client, serde_json::json!({ "type": "ice_candidate", "candidate": "candidate:1 1 udp 1 10.0.0.5 1 typ host" })).await;
+        let reply = receive(&mut client).await;
+        assert_eq!(reply["type"], "error");
+
+        send_json(&mut client, serde_json::json!({ "type": "offer", "sdp": "nonsense" })).await;
+        assert_eq!(receive(&mut client).await["message"], "Malformed offer");
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_unknown_and_ungranted_cameras_are_refused() {
+        let base = server().await;
+        let (admin, viewer) = (login("admin").await, login("asmith").await);
+
+        let status = |result: Result<_, tungstenite::Error>| match result {
+            Err(tungstenite::Error::Http(response)) => response.status().as_u16(),
+            _ => 0,
+        };
+        assert_eq!(status(tokio_tungstenite::connect_async(format!("{}/101/webrtc", base)).await), 401);
+        assert_eq!(
+            status(tokio_tungstenite::connect_async(format!("{}/101/webrtc?token=mock-token-999", base)).await),
+            401
+        );
+        assert_eq!(
+            status(tokio_tungstenite::connect_async(format!("{}/nope/webrtc?token={}", base, admin)).await),
+            404
+        );
+        // The viewer is only granted the Headquarters site, not the parking lot camera
+        assert_eq!(
+            status(tokio_tungstenite::connect_async(format!("{}/103/webrtc?token={}", base, viewer)).await),
+            404
+        );
+    }
+}
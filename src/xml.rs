@@ -0,0 +1,87 @@
+// Just enough XML handling for the SOAP dialects cameras speak (WS-Discovery,
+// ONVIF). Elements are matched by local name, ignoring namespace prefixes.
+
+pub struct Element<'a> {
+    attributes: &'a str,
+    pub text: &'a str,
+}
+
+impl<'a> Element<'a> {
+    pub fn attribute(&self, name: &str) -> Option<&'a str> {
+        let mut rest = self.attributes;
+        while let Some((key, after)) = rest.split_once('=') {
+            let key = key.split_whitespace().last().unwrap_or_default();
+            let after = after.trim_start();
+            let quote = after.chars().next()?;
+            let value_and_rest = after.get(1..)?;
+            let (value, remaining) = value_and_rest.split_once(quote)?;
+
+            if key.rsplit(':').next() == Some(name) {
+                return Some(value);
+            }
+            rest = remaining;
+        }
+        None
+    }
+}
+
+// Every element with the given local name, in document order. Elements nested
+// inside a match of the same name are not returned separately.
+pub fn elements<'a>(xml: &'a str, local_name: &str) -> Vec<Element<'a>> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let Some(tag_end) = rest.find('>') else { break };
+        let tag = &rest[..tag_end];
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let name = tag.split_whitespace().next().unwrap_or_default().trim_end_matches('/');
+        if name.rsplit(':').next() != Some(local_name) {
+            continue;
+        }
+        let attributes = tag[name.len()..].trim_end_matches('/');
+
+        // Self-closing elements have attributes but no text
+        if tag.ends_with('/') {
+            found.push(Element { attributes, text: "" });
+            rest = &rest[tag_end + 1..];
+            continue;
+        }
+
+        let content = &rest[tag_end + 1..];
+        let closing = format!("</{}>", name);
+        if let Some(close) = content.find(&closing) {
+            found.push(Element { attributes, text: content[..close].trim() });
+            rest = &content[close + closing.len()..];
+        }
+    }
+
+    found
+}
+
+// Text of the first element with the given local name
+pub fn text<'a>(xml: &'a str, local_name: &str) -> Option<&'a str> {
+    elements(xml, local_name).first().map(|e| e.text)
+}
+
+pub fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}